@@ -0,0 +1,170 @@
+use indexmap::IndexMap;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub const REPEAT_TARGET_POINT: &str = "point";
+pub const REPEAT_TARGET_ELEMENT: &str = "element";
+
+pub const BUTTON_LEFT: &str = "left";
+pub const BUTTON_RIGHT: &str = "right";
+pub const BUTTON_MIDDLE: &str = "middle";
+pub const BUTTON_X1: &str = "x1";
+pub const BUTTON_X2: &str = "x2";
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct ActionConfig {
+    // 重复上一次动作时的目标: point-同一屏幕坐标, element-在当前界面中重新定位同一元素
+    pub repeat_target: String,
+    // 具名的动作流水线, 可通过 pipeline:<name> 绑定到任意按键
+    #[serde(default)]
+    pub pipelines: IndexMap<String, Vec<PipelineStep>>,
+    // 具名的文本片段, 可通过 type_text:<name> 输入到hint处的元素
+    #[serde(default)]
+    pub snippets: IndexMap<String, String>,
+    // 输入文本时每个字符之间的间隔, 单位毫秒
+    #[serde(default = "default_type_interval")]
+    pub type_interval: u64,
+    // 点击时按键按下的持续时间, 单位毫秒
+    #[serde(default)]
+    pub click_press_duration: u64,
+    #[serde(default)]
+    pub drag: DragConfig,
+    #[serde(default)]
+    pub marks: MarksConfig,
+}
+
+// button_down 之后拖拽到hint的配置
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct DragConfig {
+    // 选中的第一个hint即为放下位置; 否则选中的hint作为途经点, 由 button_up 放下
+    #[serde(default = "default_drop_on_label")]
+    pub drop_on_label: bool,
+    // 拖拽移动速度, 单位像素/秒, 0表示直接跳到目标位置
+    #[serde(default)]
+    pub speed: u32,
+}
+
+fn default_drop_on_label() -> bool {
+    true
+}
+
+impl Default for DragConfig {
+    fn default() -> Self {
+        Self {
+            drop_on_label: default_drop_on_label(),
+            speed: 0,
+        }
+    }
+}
+
+// 标记和跳转列表的配置
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(default)]
+pub struct MarksConfig {
+    // 跳转列表中最多保存的光标位置数
+    pub jump_list_size: usize,
+    // 按前台程序分别保存标记, 位置相对于程序窗口, 并写入配置文件旁的marks.json, 重启后仍可用
+    pub persist: bool,
+}
+
+impl Default for MarksConfig {
+    fn default() -> Self {
+        Self {
+            jump_list_size: 20,
+            persist: false,
+        }
+    }
+}
+
+// 一次点击: 按住modifiers, 用button点击count次
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClickSpec {
+    pub button: String,
+    pub count: u32,
+    pub modifiers: Vec<String>,
+}
+
+impl ClickSpec {
+    // 解析形如 LCtrl+left, left*3, x1 的点击描述, 修饰键用+连接, 点击次数用*指定
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut keys = spec
+            .split('+')
+            .map(|key| key.trim())
+            .filter(|key| !key.is_empty())
+            .collect::<Vec<_>>();
+        let last = keys.pop()?;
+        let (button, count) = match last.split_once('*') {
+            Some((button, count)) => (button.trim(), count.trim().parse().ok()?),
+            None => (last, 1),
+        };
+        let button = button.to_lowercase();
+        if !is_button(&button) || count == 0 {
+            return None;
+        }
+        Some(Self {
+            button,
+            count,
+            modifiers: keys.iter().map(|key| key.to_string()).collect(),
+        })
+    }
+}
+
+pub fn is_button(button: &str) -> bool {
+    [BUTTON_LEFT, BUTTON_RIGHT, BUTTON_MIDDLE, BUTTON_X1, BUTTON_X2].contains(&button)
+}
+
+// 流水线中的一个基本步骤
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum PipelineStep {
+    // 移动到当前hint(没有hint时保持光标位置), 再偏移x, y
+    Move {
+        #[serde(default)]
+        x: i32,
+        #[serde(default)]
+        y: i32,
+    },
+    // 在光标处点击, 点击期间按住modifiers
+    Click {
+        #[serde(default = "default_button")]
+        button: String,
+        #[serde(default = "default_count")]
+        count: u32,
+        #[serde(default)]
+        modifiers: Vec<String>,
+    },
+    // 按下组合键, 按顺序按下并逆序释放
+    Key { keys: Vec<String> },
+    // 输入文本
+    Type { text: String },
+    // 等待ms毫秒
+    Wait { ms: u64 },
+    // 结束hints会话
+    Exit,
+}
+
+fn default_button() -> String {
+    BUTTON_LEFT.to_string()
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+fn default_type_interval() -> u64 {
+    10
+}
+
+impl Default for ActionConfig {
+    fn default() -> Self {
+        Self {
+            repeat_target: REPEAT_TARGET_ELEMENT.to_string(),
+            pipelines: IndexMap::new(),
+            snippets: IndexMap::new(),
+            type_interval: default_type_interval(),
+            click_press_duration: 0,
+            drag: DragConfig::default(),
+            marks: MarksConfig::default(),
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashSet;
+
+use log::{error, info};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+
+use super::{error, layer, location, Config, ConfigError};
+use crate::window::window::AppWindow;
+
+pub const AT_HINT_KEY: &str = "at_hint";
+
+// 前台应用的覆盖配置, 在show_hints开始时按前台窗口选择第一个匹配的
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(default)]
+pub struct AppOverride {
+    // 进程名, 如 Code.exe, 不区分大小写, 为空表示不限
+    pub process_name: String,
+    // 窗口类名, 完全一致, 为空表示不限
+    pub class_name: String,
+    // 窗口标题的正则表达式, 为空表示不限
+    pub title: String,
+    // 为false时不显示元素hint, 只显示grid和固定hint
+    pub elements: bool,
+    // 对hint的部分覆盖, 如 types, grid, charsets
+    #[schemars(with = "serde_json::Map<String, serde_json::Value>")]
+    pub hint: Table,
+    // 对keybinding的部分覆盖, 只能包含at_hint
+    #[schemars(with = "serde_json::Map<String, serde_json::Value>")]
+    pub keybinding: Table,
+}
+
+impl Default for AppOverride {
+    fn default() -> Self {
+        Self {
+            process_name: String::new(),
+            class_name: String::new(),
+            title: String::new(),
+            elements: true,
+            hint: Table::new(),
+            keybinding: Table::new(),
+        }
+    }
+}
+
+impl AppOverride {
+    pub fn matches(&self, window: &AppWindow) -> bool {
+        if self.process_name.is_empty() && self.class_name.is_empty() && self.title.is_empty() {
+            return false;
+        }
+        if !self.process_name.is_empty() && !self.process_name.eq_ignore_ascii_case(&window.process_name) {
+            return false;
+        }
+        if !self.class_name.is_empty() && self.class_name != window.class_name {
+            return false;
+        }
+        if self.title.is_empty() {
+            return true;
+        }
+        match Regex::new(&self.title) {
+            Ok(regex) => regex.is_match(&window.title),
+            Err(e) => {
+                error!("[AppOverride::matches] invalid title regex: {}: {}", self.title, e);
+                false
+            }
+        }
+    }
+
+    // 对hint.types中控件类型的覆盖
+    fn get_control_types(&self) -> Vec<i32> {
+        let types = match self.hint.get("types").and_then(Value::as_table) {
+            Some(types) => types,
+            None => return Vec::new(),
+        };
+        types
+            .values()
+            .filter_map(|hint_type| hint_type.get("element_control_types")?.as_array())
+            .flatten()
+            .filter_map(Value::as_integer)
+            .map(|control_type| control_type as i32)
+            .collect()
+    }
+}
+
+// 合并覆盖后的配置, 覆盖值无法解析时返回错误
+pub fn apply(config: &Config, app: &AppOverride) -> Result<Config, String> {
+    let mut table = match Value::try_from(config).map_err(|e| e.to_string())? {
+        Value::Table(table) => table,
+        _ => return Err("config is not a table".to_string()),
+    };
+    let mut upper = Table::new();
+    upper.insert("hint".to_string(), Value::Table(app.hint.clone()));
+    upper.insert("keybinding".to_string(), Value::Table(app.keybinding.clone()));
+    layer::merge(&mut table, upper);
+    if !app.elements {
+        if let Some(Value::Table(types)) = table.get_mut("hint").and_then(|hint| hint.get_mut("types")) {
+            for hint_type in types.values_mut() {
+                if let Value::Table(hint_type) = hint_type {
+                    hint_type.insert("element_control_types".to_string(), Value::Array(Vec::new()));
+                }
+            }
+        }
+    }
+    table.try_into::<Config>().map_err(|e| e.to_string())
+}
+
+// 合并覆盖后的配置中无法使用的取值, 其中的[[app]]已随基础配置检查过
+pub fn validate(app_config: &Config, path: &str) -> Vec<ConfigError> {
+    let mut app_config = app_config.clone();
+    app_config.app.clear();
+    error::validate_config(&app_config, path)
+}
+
+// 前台窗口匹配到的覆盖配置, 没有匹配或覆盖无法使用时返回None, 使用基础配置
+pub fn resolve(config: &Config, window: &AppWindow) -> Option<Config> {
+    let (index, app) = config
+        .app
+        .iter()
+        .enumerate()
+        .find(|(_, app)| app.matches(window))?;
+    let app_config = match apply(config, app) {
+        Ok(app_config) => app_config,
+        Err(e) => {
+            error!("[resolve] app[{}] is not usable: {}", index, e);
+            return None;
+        }
+    };
+    let errors = validate(&app_config, &location::get_config_file());
+    if !errors.is_empty() {
+        error!("[resolve] app[{}] is not usable, use base config: {:?}", index, errors);
+        return None;
+    }
+    info!(
+        "[resolve] use app[{}] for window: {} ({}, {})",
+        index, window.title, window.process_name, window.class_name
+    );
+    Some(app_config)
+}
+
+// 需要收集的控件类型: hint.types和所有[[app]]中用到的, 生成hints时再按当前配置筛选
+pub(super) fn get_collected_control_types(config: &Config) -> HashSet<i32> {
+    config
+        .hint
+        .types
+        .values()
+        .flat_map(|hint_type| hint_type.element_control_types.iter().copied())
+        .chain(config.app.iter().flat_map(|app| app.get_control_types()))
+        .collect()
+}
@@ -0,0 +1,58 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+use indexmap::IndexMap;
+use log::info;
+use once_cell::sync::Lazy;
+
+use super::{app, keybinding, Config};
+use crate::input::key::Key;
+
+// 由配置计算出的查找表, 配置重新加载时整体替换, 读取方拿到的各个表总是来自同一份配置
+pub struct DerivedTables {
+    // hint.charset_extra不为空时生成n+1位hints
+    pub has_extra_charset: bool,
+    pub hint_type_id_map: HashMap<String, usize>,
+    pub hint_control_types_id_z_map: IndexMap<i32, (usize, i32)>,
+    // hint.types和所有[[app]]中用到的控件类型
+    pub collected_control_types: HashSet<i32>,
+    pub modifiers: HashSet<String>,
+    pub global_key_down_keybindings: IndexMap<String, Vec<String>>,
+    pub global_key_up_keybindings: IndexMap<String, Vec<String>>,
+    pub at_hint_keybindings: IndexMap<String, Vec<String>>,
+    // keyboard.available_key中列出的非字符键
+    pub available_keys: HashSet<Key>,
+}
+
+impl DerivedTables {
+    fn new(config: &Config) -> Self {
+        DerivedTables {
+            has_extra_charset: !config.hint.charset_extra.is_empty(),
+            hint_type_id_map: config.hint.get_hint_type_id_map(),
+            hint_control_types_id_z_map: config.hint.get_control_types_id_z_map(),
+            collected_control_types: app::get_collected_control_types(config),
+            modifiers: keybinding::get_modifiers(config),
+            global_key_down_keybindings: config.keybinding.get_global_keybindings(true),
+            global_key_up_keybindings: config.keybinding.get_global_keybindings(false),
+            at_hint_keybindings: config.keybinding.get_at_hint_keybindings(),
+            available_keys: config.keyboard.get_available_keys(),
+        }
+    }
+}
+
+static DERIVED_TABLES: Lazy<RwLock<Arc<DerivedTables>>> = Lazy::new(|| {
+    let config = super::get_config().unwrap();
+    RwLock::new(Arc::new(DerivedTables::new(&config)))
+});
+
+// 获取当前的派生表, 持有期间不受配置重新加载影响
+pub fn get_derived_tables() -> Arc<DerivedTables> {
+    DERIVED_TABLES.read().unwrap().clone()
+}
+
+// 根据新的配置计算全部派生表, 再一次性替换
+pub fn reload_derived_tables(config: &Config) {
+    let tables = Arc::new(DerivedTables::new(config));
+    *DERIVED_TABLES.write().unwrap() = tables;
+    info!("[reload_derived_tables] derived tables reloaded");
+}
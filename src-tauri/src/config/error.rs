@@ -0,0 +1,371 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+use tauri_plugin_global_shortcut::Shortcut;
+
+use super::keybinding::{parse_click_send_keys_cmd, parse_send_keys_cmd, split_combo};
+use super::Config;
+use crate::input::key::Key;
+
+// 配置加载或校验失败的原因, 发送给设置界面显示
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ConfigError {
+    Read { path: String, message: String },
+    // line和column从1开始, 无法定位时为0
+    Parse {
+        path: String,
+        line: usize,
+        column: usize,
+        field: String,
+        message: String,
+    },
+    // 可以解析但取值不可用
+    Invalid { path: String, field: String, message: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read { path, message } => {
+                write!(f, "{}: failed to read: {}", path, message)
+            }
+            ConfigError::Parse {
+                path,
+                line,
+                column,
+                field,
+                message,
+            } => {
+                if *line > 0 {
+                    write!(f, "{}:{}:{}: ", path, line, column)?;
+                } else {
+                    write!(f, "{}: ", path)?;
+                }
+                if !field.is_empty() {
+                    write!(f, "`{}`: ", field)?;
+                }
+                write!(f, "{}", message)
+            }
+            ConfigError::Invalid {
+                path,
+                field,
+                message,
+            } => write!(f, "{}: `{}`: {}", path, field, message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl ConfigError {
+    pub fn from_toml(path: &str, content: &str, error: &toml::de::Error) -> Self {
+        let (line, column, field) = match error.span() {
+            Some(span) => {
+                let (line, column) = line_column(content, span.start);
+                (line, column, field_at(content, line))
+            }
+            None => (0, 0, String::new()),
+        };
+        ConfigError::Parse {
+            path: path.to_string(),
+            line,
+            column,
+            field,
+            message: error.message().to_string(),
+        }
+    }
+
+    // 合并各层后的配置没有对应的文件位置, 只给出字段路径
+    pub fn from_merged(path: &str, rendered: &str, error: &toml::de::Error) -> Self {
+        match Self::from_toml(path, rendered, error) {
+            ConfigError::Parse {
+                path,
+                field,
+                message,
+                ..
+            } => ConfigError::Parse {
+                path,
+                line: 0,
+                column: 0,
+                field,
+                message,
+            },
+            e => e,
+        }
+    }
+
+    pub fn invalid(path: &str, field: &str, message: &str) -> Self {
+        ConfigError::Invalid {
+            path: path.to_string(),
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+// 字节偏移转换为从1开始的行号和列号
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(content.len());
+    let before = &content[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+// 出错行所在的字段路径: 最近的表头加上该行的键名, 出错行是表头时只返回表名
+fn field_at(content: &str, line: usize) -> String {
+    let mut table = String::new();
+    let mut key = String::new();
+    for (i, text) in content.lines().take(line).enumerate() {
+        let text = text.trim();
+        if text.starts_with('[') {
+            table = text.trim_matches(|c| c == '[' || c == ']').trim().to_string();
+            key.clear();
+        } else if i + 1 == line {
+            if let Some((name, _)) = text.split_once('=') {
+                key = name.trim().trim_matches('"').to_string();
+            }
+        }
+    }
+    match (table.is_empty(), key.is_empty()) {
+        (_, true) => table,
+        (true, false) => key,
+        (false, false) => format!("{}.{}", table, key),
+    }
+}
+
+// 组合键为空或含有未知的按键名时返回错误信息
+fn check_combo(combo: &str) -> Option<String> {
+    let keys = split_combo(combo);
+    if keys.is_empty() {
+        return Some("key combination is empty".to_string());
+    }
+    keys.iter()
+        .find(|key| Key::from_name(key).is_none())
+        .map(|key| format!("unknown key `{}` in `{}`", key, combo))
+}
+
+// 合并[[app]]覆盖后的配置中的问题, 字段加上该覆盖的前缀
+fn with_field_prefix(error: ConfigError, prefix: &str) -> ConfigError {
+    match error {
+        ConfigError::Invalid { path, field, message } => ConfigError::Invalid {
+            path,
+            field: format!("{}.{}", prefix, field),
+            message,
+        },
+        error => error,
+    }
+}
+
+// 检查能解析但无法使用的取值, 返回所有发现的问题
+pub fn validate_config(config: &Config, path: &str) -> Vec<ConfigError> {
+    let mut errors = Vec::new();
+    let hint = &config.hint;
+    if hint.charsets.is_empty() {
+        errors.push(ConfigError::invalid(path, "hint.charsets", "must contain at least one charset"));
+    }
+    for (i, charset) in hint.charsets.iter().enumerate() {
+        if charset.is_empty() {
+            errors.push(ConfigError::invalid(path, &format!("hint.charsets[{}]", i), "charset is empty"));
+        }
+    }
+    if !hint.types.contains_key(&hint.grid.hint_type) {
+        errors.push(ConfigError::invalid(
+            path,
+            "hint.grid.hint_type",
+            &format!("unknown hint type `{}`, expected one of hint.types", hint.grid.hint_type),
+        ));
+    }
+    if hint.grid.rows <= 0 || hint.grid.columns <= 0 {
+        errors.push(ConfigError::invalid(path, "hint.grid", "rows and columns must be positive"));
+    }
+    for (i, pin) in hint.pins.iter().enumerate() {
+        if !pin.hint_type.is_empty() && !hint.types.contains_key(&pin.hint_type) {
+            errors.push(ConfigError::invalid(
+                path,
+                &format!("hint.pins[{}].hint_type", i),
+                &format!("unknown hint type `{}`", pin.hint_type),
+            ));
+        }
+    }
+    // 固定的标签只能对应一个位置, 空标签自动分配
+    let mut pin_labels = HashSet::new();
+    for (i, pin) in hint.pins.iter().enumerate() {
+        if !pin.label.is_empty() && !pin_labels.insert(&pin.label) {
+            errors.push(ConfigError::invalid(
+                path,
+                &format!("hint.pins[{}].label", i),
+                &format!("label `{}` is used by another pin", pin.label),
+            ));
+        }
+    }
+    let sub_grid = &hint.sub_grid;
+    if (sub_grid.rows * sub_grid.columns).max(0) as usize > sub_grid.chars.len() {
+        errors.push(ConfigError::invalid(
+            path,
+            "hint.sub_grid.chars",
+            "must have at least rows * columns labels",
+        ));
+    }
+    let steps = [
+        ("mouse.step.translate", &config.mouse.step.translate),
+        ("mouse.step.scroll", &config.mouse.step.scroll),
+        ("mouse.step.drag", &config.mouse.step.drag),
+    ];
+    for (field, step) in steps {
+        if step.is_empty() {
+            errors.push(ConfigError::invalid(path, field, "must contain at least one step"));
+        }
+    }
+    if config.keybinding.hotkey_buoy.is_empty() {
+        errors.push(ConfigError::invalid(path, "keybinding.hotkey_buoy", "hotkey is empty"));
+    }
+    let mut hotkeys = vec![
+        ("keybinding.hotkey_buoy".to_string(), &config.keybinding.hotkey_buoy),
+        ("keybinding.hotkey_repeat".to_string(), &config.keybinding.hotkey_repeat),
+        ("profile.hotkey".to_string(), &config.profile.hotkey),
+    ];
+    for (name, mode) in config.keybinding.modes.iter() {
+        hotkeys.push((format!("keybinding.modes.{}.hotkey", name), &mode.hotkey));
+    }
+    // 与注册时一样按全局快捷键插件的格式解析, 空字符串表示不注册
+    for (field, hotkey) in hotkeys {
+        if hotkey.is_empty() {
+            continue;
+        }
+        if let Err(e) = Shortcut::from_str(hotkey) {
+            errors.push(ConfigError::invalid(
+                path,
+                &field,
+                &format!("invalid hotkey `{}`: {}", hotkey, e),
+            ));
+        }
+    }
+    for (i, name) in config.keyboard.available_key.iter().enumerate() {
+        match Key::from_name(name) {
+            Some(key) if key.is_char() => errors.push(ConfigError::invalid(
+                path,
+                &format!("keyboard.available_key[{}]", i),
+                &format!("`{}` is a character key, which is always available", name),
+            )),
+            Some(_) => {}
+            None => errors.push(ConfigError::invalid(
+                path,
+                &format!("keyboard.available_key[{}]", i),
+                &format!("unknown key `{}`", name),
+            )),
+        }
+    }
+    for (i, name) in config.keyboard.propagation_modifier.iter().enumerate() {
+        if Key::from_name(name).is_none() {
+            errors.push(ConfigError::invalid(
+                path,
+                &format!("keyboard.propagation_modifier[{}]", i),
+                &format!("unknown key `{}`", name),
+            ));
+        }
+    }
+    // send_keys和click_send_keys的组合键在注入时才会用到, 提前检查按键名
+    let keybinding = &config.keybinding;
+    let mut combos = Vec::new();
+    for combo in keybinding.global.send_keys.keys() {
+        combos.push((format!("keybinding.global.send_keys.{}", combo), combo.as_str()));
+    }
+    for combo in keybinding.at_hint.send_keys.keys() {
+        combos.push((format!("keybinding.at_hint.send_keys.{}", combo), combo.as_str()));
+    }
+    for combo in keybinding.at_hint.click_send_keys.keys() {
+        combos.push((format!("keybinding.at_hint.click_send_keys.{}", combo), combo.as_str()));
+    }
+    for (name, mode) in keybinding.modes.iter() {
+        for cmd in mode.bindings.keys() {
+            let combo = parse_send_keys_cmd(cmd).or_else(|| parse_click_send_keys_cmd(cmd));
+            if let Some(combo) = combo {
+                combos.push((format!("keybinding.modes.{}.bindings.{}", name, cmd), combo));
+            }
+        }
+    }
+    for (field, combo) in combos {
+        if let Some(message) = check_combo(combo) {
+            errors.push(ConfigError::invalid(path, &field, &message));
+        }
+    }
+    let profile = &config.profile;
+    if !profile.default.is_empty() && !config.profiles.contains_key(&profile.default) {
+        errors.push(ConfigError::invalid(
+            path,
+            "profile.default",
+            &format!("unknown profile `{}`, expected one of profiles", profile.default),
+        ));
+    }
+    for (i, rule) in profile.auto_select.iter().enumerate() {
+        if !config.profiles.contains_key(&rule.profile) {
+            errors.push(ConfigError::invalid(
+                path,
+                &format!("profile.auto_select[{}].profile", i),
+                &format!("unknown profile `{}`", rule.profile),
+            ));
+        }
+        for resolution in rule.resolutions.iter() {
+            let valid = resolution
+                .to_lowercase()
+                .split_once('x')
+                .map_or(false, |(w, h)| w.parse::<u32>().is_ok() && h.parse::<u32>().is_ok());
+            if !valid {
+                errors.push(ConfigError::invalid(
+                    path,
+                    &format!("profile.auto_select[{}].resolutions", i),
+                    &format!("expected WIDTHxHEIGHT, found `{}`", resolution),
+                ));
+            }
+        }
+    }
+    // 合并覆盖后的配置与基础配置有相同的问题时不重复报告
+    let base_errors = errors.clone();
+    for (i, app) in config.app.iter().enumerate() {
+        if app.process_name.is_empty() && app.class_name.is_empty() && app.title.is_empty() {
+            errors.push(ConfigError::invalid(
+                path,
+                &format!("app[{}]", i),
+                "one of process_name, class_name and title is required",
+            ));
+        }
+        if let Err(e) = regex::Regex::new(&app.title) {
+            errors.push(ConfigError::invalid(path, &format!("app[{}].title", i), &e.to_string()));
+        }
+        for key in app.keybinding.keys().filter(|key| *key != super::app::AT_HINT_KEY) {
+            errors.push(ConfigError::invalid(
+                path,
+                &format!("app[{}].keybinding.{}", i, key),
+                "only keybinding.at_hint can be overridden per app",
+            ));
+        }
+        // 新的hint类型没有对应的overlay样式, 只能修改已有的类型
+        let types = app.hint.get("types").and_then(toml::Value::as_table);
+        for name in types.into_iter().flat_map(|types| types.keys()) {
+            if !hint.types.contains_key(name) {
+                errors.push(ConfigError::invalid(
+                    path,
+                    &format!("app[{}].hint.types.{}", i, name),
+                    &format!("unknown hint type `{}`, expected one of hint.types", name),
+                ));
+            }
+        }
+        match super::app::apply(config, app) {
+            Ok(app_config) => {
+                let prefix = format!("app[{}]", i);
+                for e in super::app::validate(&app_config, path) {
+                    if !base_errors.contains(&e) {
+                        errors.push(with_field_prefix(e, &prefix));
+                    }
+                }
+            }
+            Err(e) => errors.push(ConfigError::invalid(path, &format!("app[{}]", i), &e)),
+        }
+    }
+    errors
+}
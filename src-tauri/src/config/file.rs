@@ -0,0 +1,174 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use log::{debug, info};
+use serde::Serialize;
+use toml_edit::{Array, ArrayOfTables, DocumentMut, InlineTable, Item, Table, Value};
+
+// 保留的历史版本数量, 1为最近一次保存前的版本
+pub const CONFIG_BACKUP_COUNT: usize = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigBackup {
+    pub index: usize,
+    // 备份时间, 自1970年起的毫秒数
+    pub modified: u64,
+}
+
+// 备份文件为 <文件名>.bak.<序号>
+pub fn get_backup_path(path: &str, index: usize) -> PathBuf {
+    let file_name = Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    Path::new(path).with_file_name(format!("{}.bak.{}", file_name, index))
+}
+
+pub fn list_backups(path: &str) -> Vec<ConfigBackup> {
+    (1..=CONFIG_BACKUP_COUNT)
+        .filter_map(|index| {
+            let modified = fs::metadata(get_backup_path(path, index))
+                .and_then(|metadata| metadata.modified())
+                .ok()?;
+            let modified = modified.duration_since(UNIX_EPOCH).ok()?.as_millis() as u64;
+            Some(ConfigBackup { index, modified })
+        })
+        .collect()
+}
+
+// 先写入同目录的临时文件<文件名>.tmp再替换, 写入中途崩溃不会留下不完整的文件
+pub fn write_atomic(path: &str, content: &str) -> io::Result<()> {
+    let temp_path = format!("{}.tmp", path);
+    {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+    }
+    fs::rename(&temp_path, path)
+}
+
+// 备份当前文件后写入新内容, 最旧的备份被丢弃
+pub fn write_with_backup(path: &str, content: &str) -> io::Result<()> {
+    if Path::new(path).exists() {
+        for index in (1..CONFIG_BACKUP_COUNT).rev() {
+            let from = get_backup_path(path, index);
+            if from.exists() {
+                fs::rename(&from, get_backup_path(path, index + 1))?;
+            }
+        }
+        fs::copy(path, get_backup_path(path, 1))?;
+        debug!("[write_with_backup] backup {} to {:?}", path, get_backup_path(path, 1));
+    }
+    write_atomic(path, content)
+}
+
+// 在原有文档上只修改有变化的值, 保留注释和格式
+// view为用户配置应有的完整取值, 原文档中已有的键按它更新; additions为需要写入的键, 原文档中没有的从这里添加
+pub fn update_document(content: &str, view: &toml::Table, additions: &toml::Table) -> String {
+    let mut document = content.parse::<DocumentMut>().unwrap_or_else(|e| {
+        info!("[update_document] failed to parse the existing file, rewrite it: {}", e);
+        DocumentMut::new()
+    });
+    update_table(document.as_table_mut(), view, Some(additions));
+    document.to_string()
+}
+
+fn update_table(table: &mut Table, view: &toml::Table, additions: Option<&toml::Table>) {
+    let removed = table
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| !view.contains_key(key))
+        .collect::<Vec<_>>();
+    for key in removed {
+        debug!("[update_table] remove: {}", key);
+        table.remove(&key);
+    }
+    for (key, value) in view {
+        let addition = additions.and_then(|additions| additions.get(key));
+        match (table.get_mut(key), value) {
+            (Some(Item::Table(child)), toml::Value::Table(child_view)) => {
+                update_table(child, child_view, addition.and_then(toml::Value::as_table));
+            }
+            (Some(item), value) => {
+                if to_toml_value(item).as_ref() == Some(value) {
+                    continue;
+                }
+                debug!("[update_table] update: {}", key);
+                let new_item = match &*item {
+                    // 保留值前后的空白和注释
+                    Item::Value(old) => {
+                        let mut new = to_edit_value(value);
+                        *new.decor_mut() = old.decor().clone();
+                        Item::Value(new)
+                    }
+                    _ => to_item(value),
+                };
+                *item = new_item;
+            }
+            (None, _) => {
+                if let Some(addition) = addition {
+                    debug!("[update_table] add: {}", key);
+                    table.insert(key, to_item(addition));
+                }
+            }
+        }
+    }
+}
+
+// 通过输出再解析得到文档中条目的取值, 用于比较是否有变化
+fn to_toml_value(item: &Item) -> Option<toml::Value> {
+    let mut table = Table::new();
+    table.insert("value", item.clone());
+    DocumentMut::from(table)
+        .to_string()
+        .parse::<toml::Table>()
+        .ok()?
+        .remove("value")
+}
+
+// 新建的表不设置位置, 输出时跟在所属的表后面
+fn to_item(value: &toml::Value) -> Item {
+    match value {
+        toml::Value::Table(table) => Item::Table(to_table(table)),
+        toml::Value::Array(array)
+            if !array.is_empty() && array.iter().all(toml::Value::is_table) =>
+        {
+            let mut tables = ArrayOfTables::new();
+            for table in array.iter().filter_map(toml::Value::as_table) {
+                tables.push(to_table(table));
+            }
+            Item::ArrayOfTables(tables)
+        }
+        value => Item::Value(to_edit_value(value)),
+    }
+}
+
+fn to_table(table: &toml::Table) -> Table {
+    let mut result = Table::new();
+    for (key, value) in table {
+        result.insert(key, to_item(value));
+    }
+    result
+}
+
+fn to_edit_value(value: &toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::from(s.as_str()),
+        toml::Value::Integer(i) => Value::from(*i),
+        toml::Value::Float(f) => Value::from(*f),
+        toml::Value::Boolean(b) => Value::from(*b),
+        toml::Value::Datetime(d) => Value::from(*d),
+        toml::Value::Array(array) => {
+            Value::Array(array.iter().map(to_edit_value).collect::<Array>())
+        }
+        toml::Value::Table(table) => {
+            let mut inline = InlineTable::new();
+            for (key, value) in table {
+                inline.insert(key, to_edit_value(value));
+            }
+            Value::InlineTable(inline)
+        }
+    }
+}
@@ -2,26 +2,31 @@ use std::collections::HashMap;
 
 use indexmap::IndexMap;
 use log::info;
-use once_cell::sync::Lazy;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 pub struct HintConfig {
     pub charsets: Vec<Vec<char>>,
     pub charset_extra: Vec<char>,
     pub style: String,
     pub types: IndexMap<String, HintType>,
     pub grid: GridConfig,
+    #[serde(default)]
+    pub sub_grid: SubGridConfig,
+    // 固定位置的hint, 用于无法通过UI Automation获取元素的界面
+    #[serde(default)]
+    pub pins: Vec<PinConfig>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 pub struct HintType {
     pub style: String,
     pub z_index: i32,
     pub element_control_types: Vec<i32>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 pub struct GridConfig {
     pub rows: i32,
     pub columns: i32,
@@ -30,46 +35,90 @@ pub struct GridConfig {
     pub hint_type: String,
 }
 
-pub static HAS_EXTRA_CHARSET: Lazy<bool> = Lazy::new(|| {
-    let config = super::get_config().unwrap().hint;
-    !config.charset_extra.is_empty()
-});
+// 固定hint, 坐标为相对于显示器或窗口左上角的逻辑坐标
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct PinConfig {
+    pub x: i32,
+    pub y: i32,
+    // 未指定窗口时相对于该序号的显示器
+    pub monitor: usize,
+    // 按窗口类名(完全一致)或标题(包含即可)匹配窗口, 都为空时相对于显示器
+    pub window_class: String,
+    pub window_title: String,
+    // 固定的hint文本, 为空时自动分配
+    pub label: String,
+    // hint类型, 为空时使用 grid.hint_type
+    pub hint_type: String,
+}
 
-pub static HINT_TYPE_ID_MAP: Lazy<HashMap<String, usize>> = Lazy::new(|| {
-    let config = super::get_config().unwrap().hint;
-    let mut map = HashMap::new();
-    for (i, (name, _)) in config.types.iter().enumerate() {
-        map.insert(name.clone(), i);
+impl PinConfig {
+    pub fn matches_window(&self, class_name: &str, title: &str) -> bool {
+        (!self.window_class.is_empty() || !self.window_title.is_empty())
+            && (self.window_class.is_empty() || self.window_class == class_name)
+            && (self.window_title.is_empty() || title.contains(&self.window_title))
     }
-    map
-});
+}
+
+// 选中hint后用于微调目标点的细分网格
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(default)]
+pub struct SubGridConfig {
+    pub rows: i32,
+    pub columns: i32,
+    // 网格标签, 按行依次分配给每个格子
+    pub chars: Vec<char>,
+    // 为true时网格覆盖元素边界, 否则或没有元素边界时使用radius
+    pub use_bounds: bool,
+    // 网格中心到边缘的距离, 逻辑像素
+    pub radius: i32,
+}
 
-pub static HINT_CONTROL_TYPES_ID_Z_MAP: Lazy<IndexMap<i32, (usize, i32)>> = Lazy::new(|| {
-    let config = super::get_config().unwrap().hint;
-    let mut map = IndexMap::new();
-    for (i, (_name, hint_type)) in config.types.iter().enumerate() {
-        for control_type in hint_type.element_control_types.iter() {
-            map.insert(*control_type, (i, hint_type.z_index));
+impl Default for SubGridConfig {
+    fn default() -> Self {
+        Self {
+            rows: 3,
+            columns: 3,
+            chars: vec!['Q', 'W', 'E', 'A', 'S', 'D', 'Z', 'X', 'C'],
+            use_bounds: true,
+            radius: 40,
         }
     }
-    map
-});
+}
+
+impl HintConfig {
+    pub(super) fn get_hint_type_id_map(&self) -> HashMap<String, usize> {
+        let mut map = HashMap::new();
+        for (i, (name, _)) in self.types.iter().enumerate() {
+            map.insert(name.clone(), i);
+        }
+        map
+    }
+
+    pub(super) fn get_control_types_id_z_map(&self) -> IndexMap<i32, (usize, i32)> {
+        let mut map = IndexMap::new();
+        for (i, (_name, hint_type)) in self.types.iter().enumerate() {
+            for control_type in hint_type.element_control_types.iter() {
+                map.insert(*control_type, (i, hint_type.z_index));
+            }
+        }
+        map
+    }
+}
 
 #[tauri::command]
-pub async fn get_hint_default_style(
-    state: tauri::State<'_, crate::config::Config>,
-) -> Result<String, String> {
-    let style = state.hint.style.clone();
+pub async fn get_hint_default_style() -> Result<String, String> {
+    let config = super::get_config().ok_or("config not loaded")?;
+    let style = config.hint.style.clone();
     info!("[get_hint_default_style] style: {:?}", style);
     Ok(style)
 }
 
 #[tauri::command]
-pub async fn get_hint_types_styles(
-    state: tauri::State<'_, crate::config::Config>,
-) -> Result<Vec<String>, String> {
+pub async fn get_hint_types_styles() -> Result<Vec<String>, String> {
+    let config = super::get_config().ok_or("config not loaded")?;
     let mut styles: Vec<String> = Vec::new();
-    for (_, hint_type) in state.hint.types.iter() {
+    for (_, hint_type) in config.hint.types.iter() {
         styles.push(hint_type.style.clone());
     }
     info!("[get_hint_types_styles] styles: {:?}", styles);
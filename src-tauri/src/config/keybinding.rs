@@ -1,17 +1,24 @@
 use indexmap::IndexMap;
 use log::info;
-use once_cell::sync::Lazy;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+use crate::input::key::Key;
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 pub struct KeybindingConfig {
     pub global: GlobalKeybindingConfig,
     pub at_hint: AtHintKeybindingConfig,
     pub hotkey_buoy: String,
+    // 在不显示hints的情况下重复上一次动作, 为空则不注册
+    #[serde(default)]
+    pub hotkey_repeat: String,
+    #[serde(default)]
+    pub modes: IndexMap<String, ModeConfig>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 pub struct GlobalKeybindingConfig {
     pub move_to_hint: Vec<String>,
     pub move_to_hint_exit: Vec<String>,
@@ -19,9 +26,32 @@ pub struct GlobalKeybindingConfig {
     pub hold_at_hint: Vec<String>,
     pub exit: Vec<String>,
     pub translate: DirectionKeybindingsConfig,
+    #[serde(default)]
+    pub repeat: Vec<String>,
+    // 流水线名 -> 按键
+    #[serde(default)]
+    pub pipelines: IndexMap<String, Vec<String>>,
+    // 组合键(如 LCtrl+C) -> 按键
+    #[serde(default)]
+    pub send_keys: IndexMap<String, Vec<String>>,
+    #[serde(default)]
+    pub button_up: Vec<String>,
+    // 之后按下的按键作为标记名, 保存光标位置或跳转到标记
+    #[serde(default)]
+    pub set_mark: Vec<String>,
+    #[serde(default)]
+    pub jump_mark: Vec<String>,
+    // 在跳转列表中后退和前进
+    #[serde(default)]
+    pub jump_back: Vec<String>,
+    #[serde(default)]
+    pub jump_forward: Vec<String>,
+    // 将光标位置保存为固定hint
+    #[serde(default)]
+    pub capture_pin: Vec<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 pub struct AtHintKeybindingConfig {
     pub exit: Vec<String>,
     pub left_click: Vec<String>,
@@ -35,9 +65,67 @@ pub struct AtHintKeybindingConfig {
     pub translate: DirectionKeybindingsConfig,
     pub drag: DirectionKeybindingsConfig,
     pub scroll: DirectionKeybindingsConfig,
+    #[serde(default)]
+    pub repeat: Vec<String>,
+    // 流水线名 -> 按键
+    #[serde(default)]
+    pub pipelines: IndexMap<String, Vec<String>>,
+    // 文本片段名 -> 按键
+    #[serde(default)]
+    pub type_text: IndexMap<String, Vec<String>>,
+    // 组合键(如 LCtrl+C) -> 按键
+    #[serde(default)]
+    pub send_keys: IndexMap<String, Vec<String>>,
+    // 先点击hint再发送组合键
+    #[serde(default)]
+    pub click_send_keys: IndexMap<String, Vec<String>>,
+    // 点击描述(如 LCtrl+left, left*3, x1) -> 按键
+    #[serde(default)]
+    pub click: IndexMap<String, Vec<String>>,
+    #[serde(default)]
+    pub button_down: Vec<String>,
+    #[serde(default)]
+    pub button_up: Vec<String>,
+    // 跳转到方向上最近的元素
+    #[serde(default)]
+    pub navigate: DirectionKeybindingsConfig,
+    // 在元素树中跳转到父元素, 第一个子元素, 下一个和上一个兄弟元素
+    #[serde(default)]
+    pub select_parent: Vec<String>,
+    #[serde(default)]
+    pub select_child: Vec<String>,
+    #[serde(default)]
+    pub select_next: Vec<String>,
+    #[serde(default)]
+    pub select_prev: Vec<String>,
+    // 打开细分网格微调目标点, 再次按下关闭
+    #[serde(default)]
+    pub sub_grid: Vec<String>,
+    // 之后按下的按键作为标记名, 保存当前hint的目标点
+    #[serde(default)]
+    pub set_mark: Vec<String>,
+    // 结束会话并在hint处打开输入框, 提交后点击hint并输入文本
+    #[serde(default)]
+    pub prompt_text: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct ModeConfig {
+    // 在hints会话中进入该模式的按键
+    #[serde(default)]
+    pub enter: Vec<String>,
+    // 不显示hints直接进入该模式的全局快捷键, 为空则不注册
+    #[serde(default)]
+    pub hotkey: String,
+    // 模式中的按键绑定: 命令 -> 按键
+    #[serde(default)]
+    pub bindings: IndexMap<String, Vec<String>>,
+    // 在overlay中显示的模式指示文字, 为空则不显示
+    #[serde(default)]
+    pub indicator: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
 pub struct DirectionKeybindingsConfig {
     pub up: Vec<String>,
     pub down: Vec<String>,
@@ -69,81 +157,269 @@ pub const DRAG_UP_CMD: &str = "drag_up";
 pub const DRAG_DOWN_CMD: &str = "drag_down";
 pub const DRAG_LEFT_CMD: &str = "drag_left";
 pub const DRAG_RIGHT_CMD: &str = "drag_right";
+pub const REPEAT_CMD: &str = "repeat";
+pub const ENTER_MODE_CMD: &str = "enter_mode";
+pub const LEAVE_MODE_CMD: &str = "leave_mode";
+pub const PIPELINE_CMD: &str = "pipeline";
+pub const TYPE_TEXT_CMD: &str = "type_text";
+pub const SEND_KEYS_CMD: &str = "send_keys";
+pub const CLICK_SEND_KEYS_CMD: &str = "click_send_keys";
+pub const CLICK_CMD: &str = "click";
+pub const BUTTON_DOWN_CMD: &str = "button_down";
+pub const BUTTON_UP_CMD: &str = "button_up";
+pub const NAVIGATE_UP_CMD: &str = "navigate_up";
+pub const NAVIGATE_DOWN_CMD: &str = "navigate_down";
+pub const NAVIGATE_LEFT_CMD: &str = "navigate_left";
+pub const NAVIGATE_RIGHT_CMD: &str = "navigate_right";
+pub const SELECT_PARENT_CMD: &str = "select_parent";
+pub const SELECT_CHILD_CMD: &str = "select_child";
+pub const SELECT_NEXT_CMD: &str = "select_next";
+pub const SELECT_PREV_CMD: &str = "select_prev";
+pub const SUB_GRID_CMD: &str = "sub_grid";
+pub const SET_MARK_CMD: &str = "set_mark";
+pub const JUMP_MARK_CMD: &str = "jump_mark";
+pub const JUMP_BACK_CMD: &str = "jump_back";
+pub const JUMP_FORWARD_CMD: &str = "jump_forward";
+pub const CAPTURE_PIN_CMD: &str = "capture_pin";
+pub const PROMPT_TEXT_CMD: &str = "prompt_text";
 
-pub static MODIFIERS: Lazy<HashSet<String>> = Lazy::new(|| {
-    let mouse_config = super::get_config().unwrap().mouse;
-    let mut modifiers = mouse_config.get_modifiers();
-    let keyboard = super::get_config().unwrap().keyboard;
-    modifiers.extend(keyboard.propagation_modifier.iter().map(|s| s.to_string()));
-    modifiers
-});
+// 不带参数的命令, 可以在模式的bindings中使用
+pub const COMMANDS: &[&str] = &[
+    MOVE_TO_HINT_CMD,
+    MOVE_TO_HINT_EXIT_CMD,
+    HOLD_AT_HINT_CMD,
+    LEFT_CLICK_CMD,
+    LEFT_CLICK_EXIT_CMD,
+    DOUBLE_CLICK_CMD,
+    DOUBLE_CLICK_EXIT_CMD,
+    RIGHT_CLICK_CMD,
+    RIGHT_CLICK_EXIT_CMD,
+    MIDDLE_CLICK_CMD,
+    MIDDLE_CLICK_EXIT_CMD,
+    EXIT_CMD,
+    TRANSLATE_UP_CMD,
+    TRANSLATE_DOWN_CMD,
+    TRANSLATE_LEFT_CMD,
+    TRANSLATE_RIGHT_CMD,
+    SCROLL_UP_CMD,
+    SCROLL_DOWN_CMD,
+    SCROLL_LEFT_CMD,
+    SCROLL_RIGHT_CMD,
+    DRAG_UP_CMD,
+    DRAG_DOWN_CMD,
+    DRAG_LEFT_CMD,
+    DRAG_RIGHT_CMD,
+    REPEAT_CMD,
+    LEAVE_MODE_CMD,
+    BUTTON_DOWN_CMD,
+    BUTTON_UP_CMD,
+    NAVIGATE_UP_CMD,
+    NAVIGATE_DOWN_CMD,
+    NAVIGATE_LEFT_CMD,
+    NAVIGATE_RIGHT_CMD,
+    SELECT_PARENT_CMD,
+    SELECT_CHILD_CMD,
+    SELECT_NEXT_CMD,
+    SELECT_PREV_CMD,
+    SUB_GRID_CMD,
+    SET_MARK_CMD,
+    JUMP_MARK_CMD,
+    JUMP_BACK_CMD,
+    JUMP_FORWARD_CMD,
+    CAPTURE_PIN_CMD,
+    PROMPT_TEXT_CMD,
+];
+
+// 带参数的命令, 形如 <命令>:<参数>
+pub const PARAMETERIZED_COMMANDS: &[&str] = &[
+    ENTER_MODE_CMD,
+    PIPELINE_CMD,
+    TYPE_TEXT_CMD,
+    SEND_KEYS_CMD,
+    CLICK_SEND_KEYS_CMD,
+    CLICK_CMD,
+];
+
+// enter_mode命令带有模式名, 形如 enter_mode:scroll
+pub fn enter_mode_cmd(mode: &str) -> String {
+    format!("{}:{}", ENTER_MODE_CMD, mode)
+}
+
+pub fn parse_enter_mode_cmd(cmd: &str) -> Option<&str> {
+    cmd.strip_prefix(ENTER_MODE_CMD)?.strip_prefix(':')
+}
+
+// pipeline命令带有流水线名, 形如 pipeline:paste_submit
+pub fn pipeline_cmd(pipeline: &str) -> String {
+    format!("{}:{}", PIPELINE_CMD, pipeline)
+}
+
+pub fn parse_pipeline_cmd(cmd: &str) -> Option<&str> {
+    cmd.strip_prefix(PIPELINE_CMD)?.strip_prefix(':')
+}
+
+// type_text命令带有文本片段名, 形如 type_text:ticket
+pub fn type_text_cmd(snippet: &str) -> String {
+    format!("{}:{}", TYPE_TEXT_CMD, snippet)
+}
+
+pub fn parse_type_text_cmd(cmd: &str) -> Option<&str> {
+    cmd.strip_prefix(TYPE_TEXT_CMD)?.strip_prefix(':')
+}
+
+// send_keys命令带有组合键, 形如 send_keys:LCtrl+C
+pub fn send_keys_cmd(combo: &str) -> String {
+    format!("{}:{}", SEND_KEYS_CMD, combo)
+}
+
+pub fn parse_send_keys_cmd(cmd: &str) -> Option<&str> {
+    cmd.strip_prefix(SEND_KEYS_CMD)?.strip_prefix(':')
+}
+
+pub fn click_send_keys_cmd(combo: &str) -> String {
+    format!("{}:{}", CLICK_SEND_KEYS_CMD, combo)
+}
+
+pub fn parse_click_send_keys_cmd(cmd: &str) -> Option<&str> {
+    cmd.strip_prefix(CLICK_SEND_KEYS_CMD)?.strip_prefix(':')
+}
+
+// click命令带有点击描述, 形如 click:LCtrl+left
+pub fn click_cmd(spec: &str) -> String {
+    format!("{}:{}", CLICK_CMD, spec)
+}
 
-pub static GLOBAL_KEY_DOWN_KEYBINDINGS: Lazy<IndexMap<&'static str, Vec<String>>> =
-    Lazy::new(|| {
-        let keybindings = super::get_config().unwrap().keybinding;
-        keybindings.get_global_keybindings(true)
-    });
+pub fn parse_click_cmd(cmd: &str) -> Option<&str> {
+    cmd.strip_prefix(CLICK_CMD)?.strip_prefix(':')
+}
 
-pub static GLOBAL_KEY_UP_KEYBINDINGS: Lazy<IndexMap<&'static str, Vec<String>>> = Lazy::new(|| {
-    let keybindings = super::get_config().unwrap().keybinding;
-    keybindings.get_global_keybindings(false)
-});
+// 组合键中表示加号的名称, 对应数字键盘的加号键, 在任何键盘布局上都输入+
+const PLUS_KEY_NAMES: &[&str] = &["+", "Plus"];
 
-pub static AT_HINT_KEYBINDINGS: Lazy<IndexMap<&'static str, Vec<String>>> = Lazy::new(|| {
-    let keybindings = super::get_config().unwrap().keybinding;
-    keybindings.get_at_hint_keybindings()
-});
+// 将组合键拆分为按键名, 如 LCtrl+C -> [LCtrl, C]
+// +只在两个按键名之间作为分隔符, 因此 LCtrl++ 和 LCtrl+Plus 都是 [LCtrl, NumAdd]
+pub fn split_combo(combo: &str) -> Vec<String> {
+    let mut keys = Vec::new();
+    let mut key = String::new();
+    for c in combo.chars() {
+        if c == '+' && !key.trim().is_empty() {
+            keys.push(std::mem::take(&mut key));
+        } else {
+            key.push(c);
+        }
+    }
+    keys.push(key);
+    keys.iter()
+        .map(|key| key.trim())
+        .filter(|key| !key.is_empty())
+        .map(|key| {
+            if PLUS_KEY_NAMES.contains(&key) {
+                Key::NumAdd.name().to_string()
+            } else {
+                key.to_string()
+            }
+        })
+        .collect()
+}
+
+pub(super) fn get_modifiers(config: &super::Config) -> HashSet<String> {
+    let mut modifiers = config.mouse.get_modifiers();
+    modifiers.extend(config.keyboard.propagation_modifier.iter().map(|s| s.to_string()));
+    modifiers
+}
 
 impl KeybindingConfig {
-    fn get_global_keybindings(&self, key_down: bool) -> IndexMap<&'static str, Vec<String>> {
+    pub(super) fn get_global_keybindings(&self, key_down: bool) -> IndexMap<String, Vec<String>> {
         let mut keybindings = IndexMap::new();
         if !key_down {
-            keybindings.insert(HOLD_AT_HINT_CMD, self.global.hold_at_hint.clone());
-            keybindings.insert(LEFT_CLICK_EXIT_CMD, self.global.left_click_exit.clone());
+            keybindings.insert(HOLD_AT_HINT_CMD.to_string(), self.global.hold_at_hint.clone());
+            keybindings.insert(LEFT_CLICK_EXIT_CMD.to_string(), self.global.left_click_exit.clone());
         } else {
-            keybindings.insert(EXIT_CMD, self.global.exit.clone());
-            keybindings.insert(HOLD_AT_HINT_CMD, self.global.hold_at_hint.clone());
-            keybindings.insert(MOVE_TO_HINT_CMD, self.global.move_to_hint.clone());
-            keybindings.insert(MOVE_TO_HINT_EXIT_CMD, self.global.move_to_hint_exit.clone());
-            keybindings.insert(TRANSLATE_UP_CMD, self.global.translate.up.clone());
-            keybindings.insert(TRANSLATE_DOWN_CMD, self.global.translate.down.clone());
-            keybindings.insert(TRANSLATE_LEFT_CMD, self.global.translate.left.clone());
-            keybindings.insert(TRANSLATE_RIGHT_CMD, self.global.translate.right.clone());
+            keybindings.insert(EXIT_CMD.to_string(), self.global.exit.clone());
+            keybindings.insert(HOLD_AT_HINT_CMD.to_string(), self.global.hold_at_hint.clone());
+            keybindings.insert(MOVE_TO_HINT_CMD.to_string(), self.global.move_to_hint.clone());
+            keybindings.insert(MOVE_TO_HINT_EXIT_CMD.to_string(), self.global.move_to_hint_exit.clone());
+            keybindings.insert(TRANSLATE_UP_CMD.to_string(), self.global.translate.up.clone());
+            keybindings.insert(TRANSLATE_DOWN_CMD.to_string(), self.global.translate.down.clone());
+            keybindings.insert(TRANSLATE_LEFT_CMD.to_string(), self.global.translate.left.clone());
+            keybindings.insert(TRANSLATE_RIGHT_CMD.to_string(), self.global.translate.right.clone());
+            keybindings.insert(REPEAT_CMD.to_string(), self.global.repeat.clone());
+            keybindings.insert(BUTTON_UP_CMD.to_string(), self.global.button_up.clone());
+            keybindings.insert(SET_MARK_CMD.to_string(), self.global.set_mark.clone());
+            keybindings.insert(JUMP_MARK_CMD.to_string(), self.global.jump_mark.clone());
+            keybindings.insert(JUMP_BACK_CMD.to_string(), self.global.jump_back.clone());
+            keybindings.insert(JUMP_FORWARD_CMD.to_string(), self.global.jump_forward.clone());
+            keybindings.insert(CAPTURE_PIN_CMD.to_string(), self.global.capture_pin.clone());
+            for (pipeline, keys) in self.global.pipelines.iter() {
+                keybindings.insert(pipeline_cmd(pipeline), keys.clone());
+            }
+            for (combo, keys) in self.global.send_keys.iter() {
+                keybindings.insert(send_keys_cmd(combo), keys.clone());
+            }
         }
         info!("[get_global_keybindings] is key_down: {}, keybindings: {:?}", key_down, keybindings);
         keybindings
     }
 
-    fn get_at_hint_keybindings(&self) -> IndexMap<&'static str, Vec<String>> {
+    pub(super) fn get_at_hint_keybindings(&self) -> IndexMap<String, Vec<String>> {
         let mut keybindings = IndexMap::new();
-        keybindings.insert(HOLD_AT_HINT_CMD, self.global.hold_at_hint.clone());
-        keybindings.insert(EXIT_CMD, self.at_hint.exit.clone());
-        keybindings.insert(LEFT_CLICK_CMD, self.at_hint.left_click.clone());
-        keybindings.insert(LEFT_CLICK_EXIT_CMD, self.at_hint.left_click_exit.clone());
-        keybindings.insert(DOUBLE_CLICK_CMD, self.at_hint.double_click.clone());
+        keybindings.insert(HOLD_AT_HINT_CMD.to_string(), self.global.hold_at_hint.clone());
+        keybindings.insert(EXIT_CMD.to_string(), self.at_hint.exit.clone());
+        keybindings.insert(LEFT_CLICK_CMD.to_string(), self.at_hint.left_click.clone());
+        keybindings.insert(LEFT_CLICK_EXIT_CMD.to_string(), self.at_hint.left_click_exit.clone());
+        keybindings.insert(DOUBLE_CLICK_CMD.to_string(), self.at_hint.double_click.clone());
         keybindings.insert(
-            DOUBLE_CLICK_EXIT_CMD,
+            DOUBLE_CLICK_EXIT_CMD.to_string(),
             self.at_hint.double_click_exit.clone(),
         );
-        keybindings.insert(RIGHT_CLICK_CMD, self.at_hint.right_click.clone());
-        keybindings.insert(RIGHT_CLICK_EXIT_CMD, self.at_hint.right_click_exit.clone());
-        keybindings.insert(MIDDLE_CLICK_CMD, self.at_hint.middle_click.clone());
+        keybindings.insert(RIGHT_CLICK_CMD.to_string(), self.at_hint.right_click.clone());
+        keybindings.insert(RIGHT_CLICK_EXIT_CMD.to_string(), self.at_hint.right_click_exit.clone());
+        keybindings.insert(MIDDLE_CLICK_CMD.to_string(), self.at_hint.middle_click.clone());
         keybindings.insert(
-            MIDDLE_CLICK_EXIT_CMD,
+            MIDDLE_CLICK_EXIT_CMD.to_string(),
             self.at_hint.middle_click_exit.clone(),
         );
-        keybindings.insert(TRANSLATE_UP_CMD, self.at_hint.translate.up.clone());
-        keybindings.insert(TRANSLATE_DOWN_CMD, self.at_hint.translate.down.clone());
-        keybindings.insert(TRANSLATE_LEFT_CMD, self.at_hint.translate.left.clone());
-        keybindings.insert(TRANSLATE_RIGHT_CMD, self.at_hint.translate.right.clone());
-        keybindings.insert(DRAG_UP_CMD, self.at_hint.drag.up.clone());
-        keybindings.insert(DRAG_DOWN_CMD, self.at_hint.drag.down.clone());
-        keybindings.insert(DRAG_LEFT_CMD, self.at_hint.drag.left.clone());
-        keybindings.insert(DRAG_RIGHT_CMD, self.at_hint.drag.right.clone());
-        keybindings.insert(SCROLL_UP_CMD, self.at_hint.scroll.up.clone());
-        keybindings.insert(SCROLL_DOWN_CMD, self.at_hint.scroll.down.clone());
-        keybindings.insert(SCROLL_LEFT_CMD, self.at_hint.scroll.left.clone());
-        keybindings.insert(SCROLL_RIGHT_CMD, self.at_hint.scroll.right.clone());
+        keybindings.insert(TRANSLATE_UP_CMD.to_string(), self.at_hint.translate.up.clone());
+        keybindings.insert(TRANSLATE_DOWN_CMD.to_string(), self.at_hint.translate.down.clone());
+        keybindings.insert(TRANSLATE_LEFT_CMD.to_string(), self.at_hint.translate.left.clone());
+        keybindings.insert(TRANSLATE_RIGHT_CMD.to_string(), self.at_hint.translate.right.clone());
+        keybindings.insert(DRAG_UP_CMD.to_string(), self.at_hint.drag.up.clone());
+        keybindings.insert(DRAG_DOWN_CMD.to_string(), self.at_hint.drag.down.clone());
+        keybindings.insert(DRAG_LEFT_CMD.to_string(), self.at_hint.drag.left.clone());
+        keybindings.insert(DRAG_RIGHT_CMD.to_string(), self.at_hint.drag.right.clone());
+        keybindings.insert(SCROLL_UP_CMD.to_string(), self.at_hint.scroll.up.clone());
+        keybindings.insert(SCROLL_DOWN_CMD.to_string(), self.at_hint.scroll.down.clone());
+        keybindings.insert(SCROLL_LEFT_CMD.to_string(), self.at_hint.scroll.left.clone());
+        keybindings.insert(SCROLL_RIGHT_CMD.to_string(), self.at_hint.scroll.right.clone());
+        keybindings.insert(REPEAT_CMD.to_string(), self.at_hint.repeat.clone());
+        keybindings.insert(BUTTON_DOWN_CMD.to_string(), self.at_hint.button_down.clone());
+        keybindings.insert(BUTTON_UP_CMD.to_string(), self.at_hint.button_up.clone());
+        keybindings.insert(NAVIGATE_UP_CMD.to_string(), self.at_hint.navigate.up.clone());
+        keybindings.insert(NAVIGATE_DOWN_CMD.to_string(), self.at_hint.navigate.down.clone());
+        keybindings.insert(NAVIGATE_LEFT_CMD.to_string(), self.at_hint.navigate.left.clone());
+        keybindings.insert(NAVIGATE_RIGHT_CMD.to_string(), self.at_hint.navigate.right.clone());
+        keybindings.insert(SELECT_PARENT_CMD.to_string(), self.at_hint.select_parent.clone());
+        keybindings.insert(SELECT_CHILD_CMD.to_string(), self.at_hint.select_child.clone());
+        keybindings.insert(SELECT_NEXT_CMD.to_string(), self.at_hint.select_next.clone());
+        keybindings.insert(SELECT_PREV_CMD.to_string(), self.at_hint.select_prev.clone());
+        keybindings.insert(SUB_GRID_CMD.to_string(), self.at_hint.sub_grid.clone());
+        keybindings.insert(SET_MARK_CMD.to_string(), self.at_hint.set_mark.clone());
+        keybindings.insert(PROMPT_TEXT_CMD.to_string(), self.at_hint.prompt_text.clone());
+        for (pipeline, keys) in self.at_hint.pipelines.iter() {
+            keybindings.insert(pipeline_cmd(pipeline), keys.clone());
+        }
+        for (snippet, keys) in self.at_hint.type_text.iter() {
+            keybindings.insert(type_text_cmd(snippet), keys.clone());
+        }
+        for (combo, keys) in self.at_hint.send_keys.iter() {
+            keybindings.insert(send_keys_cmd(combo), keys.clone());
+        }
+        for (combo, keys) in self.at_hint.click_send_keys.iter() {
+            keybindings.insert(click_send_keys_cmd(combo), keys.clone());
+        }
+        for (spec, keys) in self.at_hint.click.iter() {
+            keybindings.insert(click_cmd(spec), keys.clone());
+        }
         info!("[get_at_hint_keybindings] keybindings: {:?}", keybindings);
         keybindings
     }
@@ -173,6 +449,13 @@ impl AtHintKeybindingConfig {
             || self.drag.right.contains(&key.to_string())
     }
 
+    pub fn is_navigate_key(&self, key: &str) -> bool {
+        self.navigate.up.contains(&key.to_string())
+            || self.navigate.down.contains(&key.to_string())
+            || self.navigate.left.contains(&key.to_string())
+            || self.navigate.right.contains(&key.to_string())
+    }
+
     pub fn is_scroll_key(&self, key: &str) -> bool {
         self.scroll.up.contains(&key.to_string())
             || self.scroll.down.contains(&key.to_string())
@@ -180,3 +463,48 @@ impl AtHintKeybindingConfig {
             || self.scroll.right.contains(&key.to_string())
     }
 }
+
+impl ModeConfig {
+    pub fn is_bound_key(&self, key: &str) -> bool {
+        self.bindings
+            .values()
+            .any(|keys| keys.contains(&key.to_string()))
+    }
+
+    // 从模式绑定中取出某一组方向命令的按键
+    pub fn get_direction_keybindings(
+        &self,
+        up_cmd: &str,
+        down_cmd: &str,
+        left_cmd: &str,
+        right_cmd: &str,
+    ) -> DirectionKeybindingsConfig {
+        let get_keys = |cmd: &str| self.bindings.get(cmd).cloned().unwrap_or_default();
+        DirectionKeybindingsConfig {
+            up: get_keys(up_cmd),
+            down: get_keys(down_cmd),
+            left: get_keys(left_cmd),
+            right: get_keys(right_cmd),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_combo_on_plus_between_keys() {
+        assert_eq!(split_combo("LCtrl+C"), vec!["LCtrl", "C"]);
+        assert_eq!(split_combo(" LCtrl + LShift + Tab "), vec!["LCtrl", "LShift", "Tab"]);
+        assert!(split_combo("").is_empty());
+    }
+
+    #[test]
+    fn plus_key_in_combo() {
+        assert_eq!(split_combo("LCtrl++"), vec!["LCtrl", "NumAdd"]);
+        assert_eq!(split_combo("LCtrl+Plus"), vec!["LCtrl", "NumAdd"]);
+        assert_eq!(split_combo("+"), vec!["NumAdd"]);
+        assert_eq!(split_combo("LCtrl+++A"), vec!["LCtrl", "NumAdd", "A"]);
+    }
+}
@@ -1,17 +1,20 @@
-use std::sync::Mutex;
+use std::collections::HashSet;
 
 use indexmap::IndexMap;
-use once_cell::sync::Lazy;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+use crate::input::key::Key;
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 pub struct KeyboardConfig {
-    pub available_key: IndexMap<String, u16>,
+    // 需要处理的非字符键, 字母, 数字和符号键总是可用
+    pub available_key: Vec<String>,
     pub propagation_modifier: Vec<String>,
     pub map_left_right: IndexMap<String, LeftRightConfig>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 pub struct LeftRightConfig {
     pub left: Option<String>,
     pub right: Option<String>,
@@ -21,15 +24,20 @@ pub const HINT_KEY: &str = "HintKey";
 pub const HINT_RIGHT_KEY: &str = "HintRightKey";
 pub const HINT_LEFT_KEY: &str = "HintLeftKey";
 
-pub static VIRTUAL_KEY_MAP: Lazy<Mutex<IndexMap<u16, String>>> = Lazy::new(|| Mutex::new({
-    let mut map = IndexMap::new();
-    for (key, vk) in &super::get_config().unwrap().keyboard.available_key {
-        map.insert(*vk, key.clone());
-    }
-    map
-}));
+// 为前端提供的可以在 keyboard.available_key 中列出的按键名
+#[tauri::command]
+pub fn get_named_keys_for_frontend() -> Vec<&'static str> {
+    crate::input::key::get_named_keys()
+}
 
 impl KeyboardConfig {
+    pub(super) fn get_available_keys(&self) -> HashSet<Key> {
+        self.available_key
+            .iter()
+            .filter_map(|name| Key::from_name(name))
+            .collect()
+    }
+
     pub fn get_left_key(&self, key: &str) -> Option<&str> {
         let config = self.map_left_right.get(key)?;
         if let Some(left) = &config.left {
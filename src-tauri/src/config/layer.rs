@@ -0,0 +1,420 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use log::{debug, error, info};
+use toml::{Table, Value};
+
+use super::{migrate, profile, ConfigError};
+
+// 编译进程序的默认配置, 作为最底层
+pub const BUILTIN_CONFIG: &str = include_str!("../../config.default");
+// 没有用户配置文件时错误信息中使用的来源名
+pub const BUILTIN_CONFIG_NAME: &str = "<built-in>";
+// 环境变量覆盖的前缀, 层级用双下划线分隔, 如 SCREEN_BUOY__HINT__GRID__ROWS=4
+const ENV_OVERRIDE_PREFIX: &str = "SCREEN_BUOY__";
+// 命令行覆盖参数, 如 --set hint.grid.rows=4
+const CLI_OVERRIDE_FLAG: &str = "--set";
+// 具名条目的表默认按条目合并, 上层的表中含有 _replace = true 时整体替换下层的表, 用于删除下层的条目
+// 路径中的 * 匹配任意一个键, 如每个模式的bindings
+pub const REPLACE_MARKER: &str = "_replace";
+pub const REPLACEABLE_TABLES: &[&str] = &[
+    "hint.types",
+    "keybinding.modes",
+    "keybinding.modes.*.bindings",
+    "keybinding.global.pipelines",
+    "keybinding.global.send_keys",
+    "keybinding.at_hint.pipelines",
+    "keybinding.at_hint.type_text",
+    "keybinding.at_hint.send_keys",
+    "keybinding.at_hint.click_send_keys",
+    "keybinding.at_hint.click",
+    "action.pipelines",
+    "action.snippets",
+    "keyboard.map_left_right",
+    "profiles",
+];
+
+// 配置的各层, 从下到上依次为内置默认配置, 系统配置文件, 用户配置文件, 当前profile, 环境变量和命令行覆盖
+pub struct Layers {
+    // 内置默认配置和系统配置文件合并后的结果, 保存时用户配置只写入与它不同的值
+    pub base: Table,
+    pub overrides: Table,
+}
+
+// 所有用户共享的系统配置文件
+pub fn get_system_config_path() -> String {
+    if cfg!(windows) {
+        let program_data = env::var("PROGRAMDATA").unwrap_or_else(|_| "C:\\ProgramData".to_string());
+        format!("{}\\ScreenBuoy\\config.toml", program_data)
+    } else {
+        "/etc/screen-buoy/config.toml".to_string()
+    }
+}
+
+// 读取并解析一个配置层, 语法错误带有行列号, 旧版本的配置在内存中升级
+pub fn read_layer(path: &str) -> Result<Table, ConfigError> {
+    let content = fs::read_to_string(path).map_err(|e| ConfigError::Read {
+        path: path.to_string(),
+        message: e.to_string(),
+    })?;
+    let mut table = content
+        .parse::<Table>()
+        .map_err(|e| ConfigError::from_toml(path, &content, &e))?;
+    for change in migrate::migrate(&mut table, path)? {
+        debug!("[read_layer] migrate {}: {}", path, change);
+    }
+    Ok(table)
+}
+
+pub fn load_layers() -> Result<Layers, ConfigError> {
+    let mut base: Table = BUILTIN_CONFIG
+        .parse()
+        .expect("[load_layers] built-in config is invalid");
+    let system_path = get_system_config_path();
+    if Path::new(&system_path).exists() {
+        info!("[load_layers] merge system config: {}", system_path);
+        merge(&mut base, read_layer(&system_path)?);
+    }
+    let mut overrides = Table::new();
+    for (key, value) in env::vars() {
+        if let Some(path) = key.strip_prefix(ENV_OVERRIDE_PREFIX) {
+            let path = path.split("__").map(|s| s.to_lowercase()).collect::<Vec<_>>();
+            insert_path(&mut overrides, &path, parse_value(&value));
+        }
+    }
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg != CLI_OVERRIDE_FLAG {
+            continue;
+        }
+        match args.next().as_deref().and_then(|s| s.split_once('=')) {
+            Some((key, value)) => {
+                let path = key.trim().split('.').map(|s| s.to_string()).collect::<Vec<_>>();
+                insert_path(&mut overrides, &path, parse_value(value.trim()));
+            }
+            None => error!("[load_layers] {} expects key=value", CLI_OVERRIDE_FLAG),
+        }
+    }
+    debug!("[load_layers] overrides: {:?}", overrides);
+    Ok(Layers { base, overrides })
+}
+
+impl Layers {
+    // 依次合并用户配置, 当前profile和覆盖值, 用户配置可以只包含需要修改的值
+    pub fn resolve(&self, user: Option<Table>) -> Table {
+        let mut table = self.base.clone();
+        if let Some(user) = user {
+            merge(&mut table, user);
+        }
+        let name = profile::get_active_profile_in_table(&table);
+        if let Some(profile) = profile::get_profile(&table, &name) {
+            debug!("[resolve] merge profile: {}", name);
+            merge(&mut table, profile);
+        }
+        merge(&mut table, self.overrides.clone());
+        table
+    }
+
+    // 用户配置之上的层: profile和覆盖值, 其中的值不写入用户配置
+    fn upper(&self, table: &Table, profile: &str) -> Table {
+        let mut upper = profile::get_profile(table, profile).unwrap_or_default();
+        merge(&mut upper, self.overrides.clone());
+        upper
+    }
+
+    // 用户配置应有的完整取值: 来自上层且未被修改的值还原为用户配置原有的内容
+    pub fn user_view(&self, table: &Table, previous_user: Option<&Table>, profile: &str) -> Table {
+        let mut user = table.clone();
+        mark_replaced(&mut user, &self.base, "");
+        keep_previous(&mut user, table, &self.upper(table, profile), previous_user);
+        user
+    }
+
+    // 用户配置中需要保存的部分: 与底层不同的值, 来自上层且未被修改的值保持用户配置原有的内容
+    pub fn user_diff(&self, table: &Table, previous_user: Option<&Table>, profile: &str) -> Table {
+        let mut user = diff(table, &self.base, "");
+        keep_previous(&mut user, table, &self.upper(table, profile), previous_user);
+        user
+    }
+}
+
+// 将上层合并到下层, 表按键递归合并, 其他值(包括数组)整体替换, 带有REPLACE_MARKER的表整体替换
+pub fn merge(lower: &mut Table, upper: Table) {
+    merge_at(lower, upper, "");
+}
+
+fn merge_at(lower: &mut Table, upper: Table, path: &str) {
+    for (key, value) in upper {
+        let key_path = join_path(path, &key);
+        match (lower.get_mut(&key), value) {
+            (Some(Value::Table(lower_table)), Value::Table(mut upper_table)) => {
+                if take_replace_marker(&mut upper_table, &key_path) {
+                    *lower_table = upper_table;
+                } else {
+                    merge_at(lower_table, upper_table, &key_path);
+                }
+            }
+            (_, Value::Table(mut upper_table)) => {
+                strip_replace_markers(&mut upper_table, &key_path);
+                lower.insert(key, Value::Table(upper_table));
+            }
+            (_, value) => {
+                lower.insert(key, value);
+            }
+        }
+    }
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn is_replaceable(path: &str) -> bool {
+    REPLACEABLE_TABLES.iter().any(|pattern| {
+        let mut keys = path.split('.');
+        pattern
+            .split('.')
+            .all(|part| keys.next().is_some_and(|key| part == "*" || part == key))
+            && keys.next().is_none()
+    })
+}
+
+// 移除表的REPLACE_MARKER, 返回是否整体替换下层的表
+fn take_replace_marker(table: &mut Table, path: &str) -> bool {
+    is_replaceable(path) && table.remove(REPLACE_MARKER) == Some(Value::Boolean(true))
+}
+
+// 下层没有的表整体插入, 其中各可替换表的标记不再需要
+fn strip_replace_markers(table: &mut Table, path: &str) {
+    take_replace_marker(table, path);
+    for (key, value) in table.iter_mut() {
+        if let Value::Table(child) = value {
+            strip_replace_markers(child, &join_path(path, key));
+        }
+    }
+}
+
+// 可替换的表中删除了下层的条目, 只能整体写入
+fn is_replaced(table: &Table, base: &Table, path: &str) -> bool {
+    is_replaceable(path) && base.keys().any(|key| !table.contains_key(key))
+}
+
+fn with_replace_marker(table: &Table) -> Table {
+    let mut result = Table::new();
+    result.insert(REPLACE_MARKER.to_string(), Value::Boolean(true));
+    result.extend(table.clone());
+    result
+}
+
+// 为删除了下层条目的可替换表加上REPLACE_MARKER
+fn mark_replaced(table: &mut Table, base: &Table, path: &str) {
+    for (key, value) in table.iter_mut() {
+        let key_path = join_path(path, key);
+        if let (Value::Table(child), Some(Value::Table(base))) = (value, base.get(key)) {
+            if is_replaced(child, base, &key_path) {
+                *child = with_replace_marker(child);
+            } else {
+                mark_replaced(child, base, &key_path);
+            }
+        }
+    }
+}
+
+// 只保留与base不同的值, 没有差异的表不输出, 删除了base中条目的可替换表整体输出
+fn diff(table: &Table, base: &Table, path: &str) -> Table {
+    let mut result = Table::new();
+    for (key, value) in table {
+        let key_path = join_path(path, key);
+        match (value, base.get(key)) {
+            (Value::Table(table), Some(Value::Table(base))) if is_replaced(table, base, &key_path) => {
+                result.insert(key.clone(), Value::Table(with_replace_marker(table)));
+            }
+            (Value::Table(table), Some(Value::Table(base))) => {
+                let table = diff(table, base, &key_path);
+                if !table.is_empty() {
+                    result.insert(key.clone(), Value::Table(table));
+                }
+            }
+            (value, Some(base)) if value == base => {}
+            (value, _) => {
+                result.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    result
+}
+
+// 上层中的值若没有被修改, 不写入用户配置, 用户配置中原有的值保持不变
+fn keep_previous(user: &mut Table, table: &Table, overrides: &Table, previous: Option<&Table>) {
+    for (key, value) in overrides {
+        let previous_value = previous.and_then(|previous| previous.get(key));
+        match (value, table.get(key)) {
+            (Value::Table(overrides), Some(Value::Table(table))) => {
+                let mut user_table = match user.remove(key) {
+                    Some(Value::Table(user_table)) => user_table,
+                    _ => Table::new(),
+                };
+                keep_previous(&mut user_table, table, overrides, previous_value.and_then(Value::as_table));
+                if !user_table.is_empty() {
+                    user.insert(key.clone(), Value::Table(user_table));
+                }
+            }
+            (value, Some(current)) if value == current => {
+                match previous_value {
+                    Some(previous_value) => user.insert(key.clone(), previous_value.clone()),
+                    None => user.remove(key),
+                };
+            }
+            _ => {}
+        }
+    }
+}
+
+fn insert_path(table: &mut Table, path: &[String], value: Value) {
+    match path {
+        [] => {}
+        [key] => {
+            table.insert(key.clone(), value);
+        }
+        [key, rest @ ..] => {
+            let child = table
+                .entry(key.clone())
+                .or_insert_with(|| Value::Table(Table::new()));
+            if !child.is_table() {
+                *child = Value::Table(Table::new());
+            }
+            if let Value::Table(child) = child {
+                insert_path(child, rest, value);
+            }
+        }
+    }
+}
+
+// 覆盖值按TOML值解析, 如 4, true, ["a", "b"], 解析失败时作为字符串
+fn parse_value(raw: &str) -> Value {
+    format!("value = {}", raw)
+        .parse::<Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(raw.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Table {
+        content.parse::<Table>().unwrap()
+    }
+
+    fn layers(base: &str) -> Layers {
+        Layers {
+            base: parse(base),
+            overrides: Table::new(),
+        }
+    }
+
+    const BASE: &str = r#"
+        [hint.grid]
+        rows = 4
+        columns = 5
+
+        [keybinding.modes.scroll]
+        enter = ["S"]
+
+        [keybinding.modes.select]
+        enter = ["V"]
+    "#;
+
+    #[test]
+    fn merges_tables_by_entry() {
+        let mut table = parse(BASE);
+        merge(&mut table, parse("[keybinding.modes.scroll]\nenter = [\"M\"]"));
+        assert_eq!(
+            table,
+            parse(&BASE.replace(r#"enter = ["S"]"#, r#"enter = ["M"]"#))
+        );
+    }
+
+    #[test]
+    fn replace_marker_removes_lower_entries() {
+        let mut table = parse(BASE);
+        merge(
+            &mut table,
+            parse("[keybinding.modes]\n_replace = true\n[keybinding.modes.scroll]\nenter = [\"M\"]"),
+        );
+        let modes = table["keybinding"]["modes"].as_table().unwrap();
+        assert_eq!(modes, &parse("[scroll]\nenter = [\"M\"]"));
+    }
+
+    // 每个可替换的表都能删除下层的条目, * 替换为具体的模式名
+    #[test]
+    fn replace_marker_removes_entries_of_every_replaceable_table() {
+        for pattern in REPLACEABLE_TABLES {
+            let path = pattern.replace('*', "scroll");
+            let mut table = parse(&format!("[{path}.kept]\nvalue = 1\n[{path}.removed]\nvalue = 2"));
+            merge(
+                &mut table,
+                parse(&format!("[{path}]\n_replace = true\n[{path}.kept]\nvalue = 1")),
+            );
+            let entries = path
+                .split('.')
+                .fold(&table, |table, key| table[key].as_table().unwrap());
+            assert_eq!(entries, &parse("[kept]\nvalue = 1"), "table: {}", path);
+        }
+    }
+
+    #[test]
+    fn user_diff_writes_whole_mode_bindings_when_entries_removed() {
+        let base = "[keybinding.modes.scroll.bindings]\nscroll_up = [\"K\"]\nexit = [\"Esc\"]";
+        let table = parse("[keybinding.modes.scroll.bindings]\nscroll_up = [\"K\"]");
+        let expected = parse(
+            "[keybinding.modes.scroll.bindings]\n_replace = true\nscroll_up = [\"K\"]",
+        );
+        assert_eq!(layers(base).user_diff(&table, None, ""), expected);
+    }
+
+    #[test]
+    fn replace_marker_only_applies_to_replaceable_tables() {
+        let mut table = parse(BASE);
+        merge(&mut table, parse("[hint.grid]\n_replace = true\nrows = 3"));
+        assert_eq!(table["hint"]["grid"]["columns"], Value::Integer(5));
+        assert_eq!(table["hint"]["grid"][REPLACE_MARKER], Value::Boolean(true));
+    }
+
+    #[test]
+    fn user_diff_writes_changed_entries() {
+        let mut table = parse(BASE);
+        merge(&mut table, parse("[keybinding.modes.scroll]\nenter = [\"M\"]"));
+        let diff = layers(BASE).user_diff(&table, None, "");
+        assert_eq!(diff, parse("[keybinding.modes.scroll]\nenter = [\"M\"]"));
+    }
+
+    #[test]
+    fn user_diff_writes_whole_table_when_entries_removed() {
+        let mut table = parse(BASE);
+        if let Some(Value::Table(modes)) = table
+            .get_mut("keybinding")
+            .and_then(|keybinding| keybinding.get_mut("modes"))
+        {
+            modes.remove("select");
+        }
+        let layers = layers(BASE);
+        let expected = parse(
+            "[keybinding.modes]\n_replace = true\n[keybinding.modes.scroll]\nenter = [\"S\"]",
+        );
+        assert_eq!(layers.user_diff(&table, None, ""), expected);
+        let view = layers.user_view(&table, None, "");
+        assert_eq!(view["keybinding"]["modes"], expected["keybinding"]["modes"]);
+
+        // 写入后再加载得到相同的配置
+        let mut reloaded = parse(BASE);
+        merge(&mut reloaded, expected);
+        assert_eq!(reloaded, table);
+    }
+}
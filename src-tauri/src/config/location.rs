@@ -0,0 +1,122 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::{error, info};
+use once_cell::sync::Lazy;
+
+use super::layer::BUILTIN_CONFIG;
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+const LOG_DIR_NAME: &str = "logs";
+const APP_DIR_NAME_WINDOWS: &str = "ScreenBuoy";
+const APP_DIR_NAME: &str = "screen-buoy";
+
+// 指定配置文件的命令行参数和环境变量, 命令行参数优先
+const CONFIG_FLAG: &str = "--config";
+const CONFIG_ENV: &str = "SCREEN_BUOY_CONFIG";
+// 指定日志目录的命令行参数和环境变量, 都没有时使用配置文件所在目录下的logs
+const LOG_DIR_FLAG: &str = "--log-dir";
+const LOG_DIR_ENV: &str = "SCREEN_BUOY_LOG_DIR";
+
+// 启动时确定的配置文件路径, 运行期间不变
+static CONFIG_FILE: Lazy<PathBuf> = Lazy::new(|| {
+    let path = resolve_config_file();
+    info!("[resolve_config_file] config file: {:?}", path);
+    path
+});
+
+fn get_arg(flag: &str) -> Option<String> {
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next();
+        }
+        if let Some(value) = arg.strip_prefix(flag).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+fn get_arg_or_env(flag: &str, env_name: &str) -> Option<PathBuf> {
+    get_arg(flag)
+        .or_else(|| env::var(env_name).ok())
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+// 每个用户自己的配置目录: Windows为%APPDATA%\ScreenBuoy, 其他系统为$XDG_CONFIG_HOME/screen-buoy或~/.config/screen-buoy
+pub fn get_user_config_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        let app_data = env::var_os("APPDATA")?;
+        return Some(PathBuf::from(app_data).join(APP_DIR_NAME_WINDOWS));
+    }
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join(APP_DIR_NAME))
+}
+
+// 与程序放在一起的配置文件, 用于免安装使用
+fn get_portable_config_file() -> Option<PathBuf> {
+    let exe_path = env::current_exe().ok()?;
+    Some(exe_path.parent()?.join(CONFIG_FILE_NAME))
+}
+
+// 依次使用命令行参数, 环境变量, 程序目录下已有的配置文件, 开发时的配置文件和用户配置目录
+fn resolve_config_file() -> PathBuf {
+    if let Some(path) = get_arg_or_env(CONFIG_FLAG, CONFIG_ENV) {
+        return path;
+    }
+    let mut candidates: Vec<PathBuf> = get_portable_config_file().into_iter().collect();
+    if cfg!(debug_assertions) {
+        candidates.extend(
+            ["config.toml", "src-tauri/config.toml", "../config.toml"]
+                .iter()
+                .map(PathBuf::from),
+        );
+    }
+    if let Some(path) = candidates.into_iter().find(|path| path.exists()) {
+        return path;
+    }
+    match get_user_config_dir() {
+        Some(dir) => dir.join(CONFIG_FILE_NAME),
+        None => {
+            error!("[resolve_config_file] user config directory not found, use the current directory");
+            PathBuf::from(CONFIG_FILE_NAME)
+        }
+    }
+}
+
+pub fn get_config_file() -> String {
+    CONFIG_FILE.to_string_lossy().to_string()
+}
+
+// 首次运行时把默认配置写入配置文件的位置, 写入失败时只使用默认配置
+pub fn ensure_config_file() {
+    let path = CONFIG_FILE.as_path();
+    if path.exists() {
+        return;
+    }
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        if let Err(e) = fs::create_dir_all(parent) {
+            error!("[ensure_config_file] failed to create {:?}: {}", parent, e);
+            return;
+        }
+    }
+    match fs::write(path, BUILTIN_CONFIG) {
+        Ok(()) => info!("[ensure_config_file] create default config: {:?}", path),
+        Err(e) => error!("[ensure_config_file] failed to create {:?}: {}", path, e),
+    }
+}
+
+// 日志目录, 依次使用命令行参数, 环境变量和配置文件所在目录下的logs
+pub fn get_log_dir() -> PathBuf {
+    if let Some(path) = get_arg_or_env(LOG_DIR_FLAG, LOG_DIR_ENV) {
+        return path;
+    }
+    let config_dir = CONFIG_FILE.parent().unwrap_or(Path::new(""));
+    config_dir.join(LOG_DIR_NAME)
+}
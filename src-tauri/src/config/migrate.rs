@@ -0,0 +1,521 @@
+use std::fs;
+use std::path::Path;
+
+use log::{error, info};
+use toml::{Table, Value};
+
+use super::{app, file, keybinding, profile, ConfigError};
+use crate::input::key::{win32, Key};
+
+// 当前的配置格式版本, 修改配置格式时加一并在MIGRATIONS中添加升级步骤
+pub const CURRENT_CONFIG_VERSION: u32 = 2;
+pub const VERSION_KEY: &str = "config_version";
+
+// 将配置从from版本升级到from + 1版本, 返回修改说明
+struct Migration {
+    from: u32,
+    apply: fn(&mut Table) -> Vec<String>,
+}
+
+// 按版本顺序排列
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 0,
+        apply: migrate_v0,
+    },
+    Migration {
+        from: 1,
+        apply: migrate_v1,
+    },
+];
+
+// 版本0是加入config_version之前的配置, 格式与版本1相同
+fn migrate_v0(_table: &mut Table) -> Vec<String> {
+    Vec::new()
+}
+
+// 版本1内置配置中keyboard.available_key的按键, 版本1中各层的表会合并, 这些按键总是可用
+const V1_AVAILABLE_KEYS: &[&str] = &[
+    "Backspace", "Tab", "Enter", "CapsLock", "Esc", "Space", "PageUp", "PageDown", "End", "Home",
+    "Left", "Up", "Right", "Down", "Insert", "Delete", "LWin", "RWin", "Num0", "Num1", "Num2",
+    "Num3", "Num4", "Num5", "Num6", "Num7", "Num8", "Num9", "NumMultiply", "NumAdd",
+    "NumSubtract", "NumDecimal", "NumDivide", "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8",
+    "F9", "F10", "F11", "F12", "LShift", "RShift", "LCtrl", "RCtrl", "LAlt", "RAlt",
+];
+
+// 版本2中keyboard.available_key从 按键名 -> Windows虚拟键码 改为按键名列表
+// 自定义的按键名按虚拟键码改为标准按键名, 并替换配置中对它的引用
+fn migrate_v1(table: &mut Table) -> Vec<String> {
+    let mut changes = Vec::new();
+    let mut renames = Vec::new();
+    convert_available_key(table, "", &mut renames, &mut changes);
+    if let Some(Value::Table(profiles)) = table.get_mut(profile::PROFILES_KEY) {
+        for (name, value) in profiles.iter_mut() {
+            if let Value::Table(profile_table) = value {
+                let prefix = format!("{}.{}.", profile::PROFILES_KEY, name);
+                convert_available_key(profile_table, &prefix, &mut renames, &mut changes);
+            }
+        }
+    }
+    for (from, to) in renames.iter() {
+        changes.push(format!("rename key `{}` to `{}`", from, to));
+    }
+    if !renames.is_empty() {
+        rename_keys(table, &renames);
+    }
+    changes
+}
+
+fn convert_available_key(
+    table: &mut Table,
+    prefix: &str,
+    renames: &mut Vec<(String, String)>,
+    changes: &mut Vec<String>,
+) {
+    let keyboard = match table.get_mut("keyboard") {
+        Some(Value::Table(keyboard)) => keyboard,
+        _ => return,
+    };
+    let available_key = match keyboard.get("available_key") {
+        Some(Value::Table(available_key)) => available_key.clone(),
+        _ => return,
+    };
+    let mut names = V1_AVAILABLE_KEYS
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    for (name, vk) in available_key.iter() {
+        let key = vk
+            .as_integer()
+            .and_then(|vk| u16::try_from(vk).ok())
+            .and_then(win32::from_virtual_key);
+        let key = match key {
+            Some(key) => key,
+            None => {
+                changes.push(format!(
+                    "drop {}keyboard.available_key.{}: unknown virtual key {}",
+                    prefix, name, vk
+                ));
+                continue;
+            }
+        };
+        if key.name() != name && !renames.iter().any(|(from, _)| from == name) {
+            renames.push((name.clone(), key.name().to_string()));
+        }
+        // 字符键总是可用, 不需要列出
+        if !key.is_char() && !names.iter().any(|n| n == key.name()) {
+            names.push(key.name().to_string());
+        }
+    }
+    names.sort_by_key(|name| Key::from_name(name).map_or(u16::MAX, |key| key.scancode()));
+    keyboard.insert(
+        "available_key".to_string(),
+        Value::Array(names.into_iter().map(Value::String).collect()),
+    );
+    changes.push(format!(
+        "convert {}keyboard.available_key to a list of key names",
+        prefix
+    ));
+}
+
+// 键名为组合键或点击描述的绑定表, 如 send_keys 中的 LCtrl+C
+const COMBO_BINDINGS: &[&str] = &[
+    keybinding::SEND_KEYS_CMD,
+    keybinding::CLICK_SEND_KEYS_CMD,
+    keybinding::CLICK_CMD,
+];
+
+// 只替换引用按键名的位置: 按键绑定, 组合键, 鼠标步长和流水线步骤中的修饰键,
+// keyboard.map_left_right 和 propagation_modifier, 不改动流水线, 文本片段, 模式和配置方案的名称
+// 各配置方案和 [[app]] 中的覆盖与顶层结构相同
+fn rename_keys(table: &mut Table, renames: &[(String, String)]) {
+    rename_layer_keys(table, renames);
+    if let Some(Value::Table(profiles)) = table.get_mut(profile::PROFILES_KEY) {
+        for value in profiles.values_mut() {
+            if let Value::Table(profile_table) = value {
+                rename_layer_keys(profile_table, renames);
+            }
+        }
+    }
+    if let Some(Value::Array(apps)) = table.get_mut("app") {
+        for value in apps.iter_mut() {
+            if let Value::Table(app) = value {
+                rename_layer_keys(app, renames);
+            }
+        }
+    }
+}
+
+fn rename_layer_keys(table: &mut Table, renames: &[(String, String)]) {
+    if let Some(Value::Table(keybinding)) = table.get_mut("keybinding") {
+        for section in ["global", app::AT_HINT_KEY] {
+            if let Some(Value::Table(bindings)) = keybinding.get_mut(section) {
+                rename_bindings(bindings, renames);
+            }
+        }
+        if let Some(Value::Table(modes)) = keybinding.get_mut("modes") {
+            for value in modes.values_mut() {
+                if let Value::Table(mode) = value {
+                    rename_mode(mode, renames);
+                }
+            }
+        }
+    }
+    if let Some(Value::Table(keyboard)) = table.get_mut("keyboard") {
+        rename_array(keyboard.get_mut("propagation_modifier"), renames);
+        if let Some(Value::Table(map_left_right)) = keyboard.get_mut("map_left_right") {
+            *map_left_right = std::mem::take(map_left_right)
+                .into_iter()
+                .map(|(key, mut value)| {
+                    if let Value::Table(sides) = &mut value {
+                        for side in sides.values_mut() {
+                            if let Value::String(name) = side {
+                                *name = rename_key(name, renames);
+                            }
+                        }
+                    }
+                    (rename_key(&key, renames), value)
+                })
+                .collect();
+        }
+    }
+    if let Some(Value::Table(steps)) = table
+        .get_mut("mouse")
+        .and_then(|mouse| mouse.get_mut("step"))
+    {
+        for value in steps.values_mut() {
+            if let Value::Array(steps) = value {
+                for step in steps.iter_mut() {
+                    rename_array(step.get_mut("modifier"), renames);
+                }
+            }
+        }
+    }
+    if let Some(Value::Table(pipelines)) = table
+        .get_mut("action")
+        .and_then(|action| action.get_mut("pipelines"))
+    {
+        for value in pipelines.values_mut() {
+            if let Value::Array(steps) = value {
+                for step in steps.iter_mut() {
+                    rename_array(step.get_mut("keys"), renames);
+                    rename_array(step.get_mut("modifiers"), renames);
+                }
+            }
+        }
+    }
+}
+
+// keybinding.global 和 keybinding.at_hint: 命令 -> 按键, 或 名称 -> 按键 的表
+fn rename_bindings(bindings: &mut Table, renames: &[(String, String)]) {
+    for (name, value) in bindings.iter_mut() {
+        match value {
+            Value::Array(_) => rename_array(Some(value), renames),
+            Value::Table(table) => {
+                let rename_names = COMBO_BINDINGS.contains(&name.as_str());
+                *table = std::mem::take(table)
+                    .into_iter()
+                    .map(|(key, mut keys)| {
+                        rename_array(Some(&mut keys), renames);
+                        let key = if rename_names { rename_combo(&key, renames) } else { key };
+                        (key, keys)
+                    })
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+}
+
+// 模式的bindings中带参数的命令, 如 send_keys:LCtrl+C, 只替换组合键部分
+fn rename_mode(mode: &mut Table, renames: &[(String, String)]) {
+    rename_array(mode.get_mut("enter"), renames);
+    if let Some(Value::Table(bindings)) = mode.get_mut("bindings") {
+        *bindings = std::mem::take(bindings)
+            .into_iter()
+            .map(|(command, mut keys)| {
+                rename_array(Some(&mut keys), renames);
+                let command = match command.split_once(':') {
+                    Some((name, combo)) if COMBO_BINDINGS.contains(&name) => {
+                        format!("{}:{}", name, rename_combo(combo, renames))
+                    }
+                    _ => command,
+                };
+                (command, keys)
+            })
+            .collect();
+    }
+}
+
+fn rename_array(value: Option<&mut Value>, renames: &[(String, String)]) {
+    if let Some(Value::Array(array)) = value {
+        for item in array.iter_mut() {
+            if let Value::String(name) = item {
+                *name = rename_key(name, renames);
+            }
+        }
+    }
+}
+
+fn rename_combo(combo: &str, renames: &[(String, String)]) -> String {
+    combo
+        .split('+')
+        .map(|part| rename_key(part, renames))
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+fn rename_key(name: &str, renames: &[(String, String)]) -> String {
+    renames
+        .iter()
+        .find(|(from, _)| from == name)
+        .map_or(name.to_string(), |(_, to)| to.clone())
+}
+
+// 没有config_version的配置视为版本0
+fn get_version(table: &Table, path: &str) -> Result<u32, ConfigError> {
+    match table.get(VERSION_KEY) {
+        None => Ok(0),
+        Some(Value::Integer(version)) if *version >= 0 => Ok(*version as u32),
+        Some(value) => Err(ConfigError::Invalid {
+            path: path.to_string(),
+            field: VERSION_KEY.to_string(),
+            message: format!("expected a non-negative integer, found: {}", value),
+        }),
+    }
+}
+
+// 将config_version设为当前版本并放在最前面
+pub fn set_current_version(table: &mut Table) {
+    let mut versioned = Table::new();
+    versioned.insert(
+        VERSION_KEY.to_string(),
+        Value::Integer(CURRENT_CONFIG_VERSION as i64),
+    );
+    for (key, value) in std::mem::take(table) {
+        if key != VERSION_KEY {
+            versioned.insert(key, value);
+        }
+    }
+    *table = versioned;
+}
+
+// 在内存中将配置逐版本升级到当前版本, 返回所有修改说明, 版本比程序新时返回错误
+pub fn migrate(table: &mut Table, path: &str) -> Result<Vec<String>, ConfigError> {
+    let version = get_version(table, path)?;
+    if version > CURRENT_CONFIG_VERSION {
+        return Err(ConfigError::Invalid {
+            path: path.to_string(),
+            field: VERSION_KEY.to_string(),
+            message: format!(
+                "version {} is newer than the supported version {}",
+                version, CURRENT_CONFIG_VERSION
+            ),
+        });
+    }
+    if version == CURRENT_CONFIG_VERSION {
+        return Ok(Vec::new());
+    }
+    let mut changes = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.from >= version) {
+        for change in (migration.apply)(table) {
+            changes.push(format!("v{} -> v{}: {}", migration.from, migration.from + 1, change));
+        }
+    }
+    set_current_version(table);
+    changes.push(format!(
+        "set {} from {} to {}",
+        VERSION_KEY, version, CURRENT_CONFIG_VERSION
+    ));
+    Ok(changes)
+}
+
+// 配置文件版本较旧时备份原文件为 <文件名>.v<版本>.bak, 然后写入升级后的配置
+pub fn migrate_file(path: &str) -> Result<(), ConfigError> {
+    let content = fs::read_to_string(path).map_err(|e| ConfigError::Read {
+        path: path.to_string(),
+        message: e.to_string(),
+    })?;
+    let mut table = content
+        .parse::<Table>()
+        .map_err(|e| ConfigError::from_toml(path, &content, &e))?;
+    let version = get_version(&table, path)?;
+    if version >= CURRENT_CONFIG_VERSION {
+        return Ok(());
+    }
+    let changes = migrate(&mut table, path)?;
+
+    let file_name = Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let backup_path = Path::new(path).with_file_name(format!("{}.v{}.bak", file_name, version));
+    if let Err(e) = fs::copy(path, &backup_path) {
+        // 没有备份时不改写原文件
+        error!("[migrate_file] failed to back up {} to {:?}: {}", path, backup_path, e);
+        return Err(ConfigError::Read {
+            path: path.to_string(),
+            message: format!("failed to back up before migration: {}", e),
+        });
+    }
+    // 只改动升级涉及的值, 保留原文件的注释和格式
+    let new_content = file::update_document(&content, &table, &table);
+    file::write_atomic(path, &new_content).map_err(|e| ConfigError::Read {
+        path: path.to_string(),
+        message: e.to_string(),
+    })?;
+    info!(
+        "[migrate_file] migrated {} from v{} to v{}, backup: {:?}",
+        path, version, CURRENT_CONFIG_VERSION, backup_path
+    );
+    for change in changes.iter() {
+        info!("[migrate_file] {}", change);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    // 每个升级步骤的输入和期望输出: (from, 输入, 输出)
+    const STEP_FIXTURES: &[(u32, &str, &str)] = &[
+        (
+            0,
+            include_str!("../../tests/fixtures/migrate/v0.in.toml"),
+            include_str!("../../tests/fixtures/migrate/v0.out.toml"),
+        ),
+        (
+            1,
+            include_str!("../../tests/fixtures/migrate/v1.in.toml"),
+            include_str!("../../tests/fixtures/migrate/v1.out.toml"),
+        ),
+    ];
+
+    // 从旧版本升级到当前版本的输入和期望输出
+    const MIGRATE_FIXTURES: &[(&str, &str)] = &[
+        (
+            include_str!("../../tests/fixtures/migrate/v0.in.toml"),
+            include_str!("../../tests/fixtures/migrate/v0.migrated.toml"),
+        ),
+        (
+            include_str!("../../tests/fixtures/migrate/v1.in.toml"),
+            include_str!("../../tests/fixtures/migrate/v1.migrated.toml"),
+        ),
+    ];
+
+    fn parse(content: &str) -> Table {
+        content.parse::<Table>().unwrap()
+    }
+
+    // 每个测试使用单独的临时目录
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "screen-buoy-migrate-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn every_migration_has_fixture() {
+        for migration in MIGRATIONS.iter() {
+            assert!(
+                STEP_FIXTURES.iter().any(|(from, _, _)| *from == migration.from),
+                "missing fixture for v{} -> v{}",
+                migration.from,
+                migration.from + 1
+            );
+        }
+    }
+
+    #[test]
+    fn migration_steps_match_fixtures() {
+        for (from, input, expected) in STEP_FIXTURES.iter() {
+            let migration = MIGRATIONS.iter().find(|m| m.from == *from).unwrap();
+            let mut table = parse(input);
+            (migration.apply)(&mut table);
+            assert_eq!(table, parse(expected), "v{} -> v{}", from, from + 1);
+        }
+    }
+
+    #[test]
+    fn migrate_v1_reports_renamed_and_dropped_keys() {
+        let mut table = parse(include_str!("../../tests/fixtures/migrate/v1.in.toml"));
+        let changes = migrate_v1(&mut table);
+        assert!(changes.contains(&"rename key `Ctrl_R` to `RCtrl`".to_string()));
+        assert!(changes.contains(&"rename key `Semi` to `;`".to_string()));
+        assert!(changes
+            .iter()
+            .any(|change| change.starts_with("drop keyboard.available_key.Bogus")));
+    }
+
+    #[test]
+    fn migrate_to_current_version() {
+        for (input, expected) in MIGRATE_FIXTURES.iter() {
+            let mut table = parse(input);
+            let changes = migrate(&mut table, "config.toml").unwrap();
+            assert_eq!(table, parse(expected));
+            assert!(!changes.is_empty());
+        }
+    }
+
+    #[test]
+    fn current_version_is_unchanged() {
+        let content = include_str!("../../tests/fixtures/migrate/v0.migrated.toml");
+        let mut table = parse(content);
+        assert!(migrate(&mut table, "config.toml").unwrap().is_empty());
+        assert_eq!(table, parse(content));
+    }
+
+    #[test]
+    fn rejects_newer_version() {
+        let mut table = parse(&format!("{} = {}", VERSION_KEY, CURRENT_CONFIG_VERSION + 1));
+        match migrate(&mut table, "config.toml") {
+            Err(ConfigError::Invalid { field, message, .. }) => {
+                assert_eq!(field, VERSION_KEY);
+                assert!(message.contains("newer"), "{}", message);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn migrate_file_backs_up_old_version() {
+        let dir = temp_dir("backup");
+        let path = dir.join("config.toml");
+        let input = include_str!("../../tests/fixtures/migrate/v0.in.toml");
+        fs::write(&path, input).unwrap();
+        migrate_file(path.to_str().unwrap()).unwrap();
+
+        let backup = fs::read_to_string(dir.join("config.toml.v0.bak")).unwrap();
+        assert_eq!(backup, input);
+        let content = fs::read_to_string(&path).unwrap();
+        // 保留原文件的注释
+        assert!(content.contains("# 加入config_version之前的配置"));
+        assert_eq!(
+            parse(&content),
+            parse(include_str!("../../tests/fixtures/migrate/v0.migrated.toml"))
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn migrate_file_skips_current_version() {
+        let dir = temp_dir("current");
+        let path = dir.join("config.toml");
+        let input = include_str!("../../tests/fixtures/migrate/v0.migrated.toml");
+        fs::write(&path, input).unwrap();
+        migrate_file(path.to_str().unwrap()).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), input);
+        let backups = fs::read_dir(&dir).unwrap().count();
+        assert_eq!(backups, 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
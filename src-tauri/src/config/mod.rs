@@ -1,53 +1,285 @@
+pub mod action;
+pub mod app;
+pub mod derived;
+pub mod error;
+pub mod file;
 pub mod hint;
 pub mod keybinding;
 pub mod keyboard;
+pub mod layer;
+pub mod location;
+pub mod migrate;
 pub mod mouse;
+pub mod profile;
+pub mod schema;
 pub mod system;
 pub mod ui_automation;
 
+pub use action::ActionConfig;
+pub use app::AppOverride;
+pub use derived::{get_derived_tables, reload_derived_tables};
+pub use error::ConfigError;
 pub use hint::{get_hint_types_styles, HintConfig};
 pub use keybinding::KeybindingConfig;
 pub use keyboard::KeyboardConfig;
 use log::{debug, error, info};
 pub use mouse::MouseConfig;
+pub use profile::ProfileConfig;
 pub use system::SystemConfig;
 pub use ui_automation::UiAutomationConfig;
 
+use indexmap::IndexMap;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
+use std::sync::mpsc;
+use tauri::{Emitter, Manager};
 use std::path::Path;
+use std::time::{Duration, SystemTime};
 use toml;
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 pub struct Config {
+    // 配置格式版本, 旧版本的配置文件在加载时升级
+    #[serde(default)]
+    pub config_version: u32,
     pub hint: HintConfig,
     pub keybinding: KeybindingConfig,
     pub mouse: MouseConfig,
     pub keyboard: KeyboardConfig,
     pub system: SystemConfig,
     pub ui_automation: UiAutomationConfig,
+    #[serde(default)]
+    pub action: ActionConfig,
+    #[serde(default)]
+    pub profile: ProfileConfig,
+    // profile名 -> 对基础配置的部分覆盖, 只需写出与基础配置不同的值
+    #[serde(default)]
+    #[schemars(with = "IndexMap<String, serde_json::Map<String, serde_json::Value>>")]
+    pub profiles: IndexMap<String, toml::Table>,
+    // 按前台应用覆盖hint和keybinding.at_hint, 使用第一个匹配的
+    #[serde(default)]
+    pub app: Vec<AppOverride>,
 }
 
+// 最近一次成功加载的配置, 配置文件出错时优先使用
+const LAST_GOOD_FILE_NAME: &str = "config.last-good.toml";
+
+// 配置文件存在时返回其路径, 位置见location::get_config_file
 pub fn get_config_path() -> Option<String> {
-    let config_paths = vec!["config.toml", "src-tauri/config.toml", "../config.toml"];
-    for path in config_paths {
-        if Path::new(path).exists() {
-            return Some(path.to_string());
+    let path = location::get_config_file();
+    if Path::new(&path).exists() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+fn get_last_good_path(path: &str) -> String {
+    Path::new(path)
+        .with_file_name(LAST_GOOD_FILE_NAME)
+        .to_string_lossy()
+        .to_string()
+}
+
+// 在内置默认配置和系统配置之上合并用户配置文件及覆盖值, 解析并校验, path为None时不使用用户配置文件
+fn resolve_config(path: Option<&str>) -> Result<Config, Vec<ConfigError>> {
+    let layers = layer::load_layers().map_err(|e| vec![e])?;
+    let user = match path {
+        Some(path) => Some(layer::read_layer(path).map_err(|e| vec![e])?),
+        None => None,
+    };
+    let source = path.unwrap_or(layer::BUILTIN_CONFIG_NAME);
+    // 先输出为文本再解析, 出错时可以从文本中找到字段路径
+    let rendered = toml::to_string(&layers.resolve(user)).map_err(|e| {
+        vec![ConfigError::Read {
+            path: source.to_string(),
+            message: e.to_string(),
+        }]
+    })?;
+    let config: Config = toml::from_str(&rendered)
+        .map_err(|e| vec![ConfigError::from_merged(source, &rendered, &e)])?;
+    let errors = error::validate_config(&config, source);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(config)
+}
+
+// 加载配置, 用户配置文件出错时依次退回到上次可用的配置和不含用户配置文件的配置, 同时返回遇到的错误
+pub fn load_config() -> (Config, Vec<ConfigError>) {
+    let path = match get_config_path() {
+        Some(path) => path,
+        None => {
+            info!("[load_config] no user config file, use defaults");
+            return load_default_config(Vec::new());
+        }
+    };
+    if let Err(e) = migrate::migrate_file(&path) {
+        error!("[load_config] failed to migrate {}: {}", path, e);
+    }
+    record_config_modified(&path);
+    let errors = match resolve_config(Some(&path)) {
+        Ok(config) => {
+            info!("[load_config] load config from{} : {:?}", path, config);
+            save_last_good_config(&path);
+            return (config, Vec::new());
+        }
+        Err(errors) => errors,
+    };
+    for e in errors.iter() {
+        error!("[load_config] {}", e);
+    }
+    let last_good_path = get_last_good_path(&path);
+    if Path::new(&last_good_path).exists() {
+        match resolve_config(Some(&last_good_path)) {
+            Ok(config) => {
+                info!("[load_config] fall back to last good config: {}", last_good_path);
+                return (config, errors);
+            }
+            Err(last_good_errors) => {
+                error!("[load_config] last good config is not usable: {:?}", last_good_errors);
+            }
         }
     }
-    None
+    load_default_config(errors)
 }
 
-pub fn load_config() -> Config {
+// 只读取配置而不升级, 备份或创建任何文件, 用户配置文件出错时使用不含它的配置
+pub fn read_config() -> Config {
     if let Some(path) = get_config_path() {
-        let config_str = fs::read_to_string(&path)
-            .expect(format!("[load_config] Failed to read config file: {}", path).as_str());
-        let config: Config = toml::from_str(&config_str)
-            .expect(format!("[load_config] Failed to parse config file: {}", path).as_str());
-        info!("[load_config] load config from{} : {:?}", path, config);
-        return config;
+        if let Ok(config) = resolve_config(Some(&path)) {
+            return config;
+        }
+    }
+    load_default_config(Vec::new()).0
+}
+
+// 不使用用户配置文件, 系统配置或覆盖值也不可用时只使用内置默认配置
+fn load_default_config(mut errors: Vec<ConfigError>) -> (Config, Vec<ConfigError>) {
+    match resolve_config(None) {
+        Ok(config) => (config, errors),
+        Err(default_errors) => {
+            for e in default_errors.iter() {
+                error!("[load_default_config] {}", e);
+            }
+            errors.extend(default_errors);
+            info!("[load_default_config] fall back to built-in config");
+            let config = toml::from_str(layer::BUILTIN_CONFIG)
+                .expect("[load_default_config] built-in config is invalid");
+            (config, errors)
+        }
+    }
+}
+
+// 配置文件可以正常加载时复制一份, 之后出错时用来启动
+fn save_last_good_config(path: &str) {
+    let last_good_path = get_last_good_path(path);
+    if let Err(e) = fs::copy(path, &last_good_path) {
+        error!("[save_last_good_config] failed to copy {} to {}: {}", path, last_good_path, e);
+    }
+}
+
+// 最近一次读取或写入时各配置文件的修改时间, 用于区分外部修改和自己的写入
+static CONFIG_MODIFIED: Lazy<Mutex<HashMap<String, Option<SystemTime>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+const CONFIG_WATCH_INTERVAL: Duration = Duration::from_millis(1000);
+
+fn get_modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn record_config_modified(path: &str) {
+    CONFIG_MODIFIED
+        .lock()
+        .unwrap()
+        .insert(path.to_string(), get_modified(path));
+}
+
+// 记录文件当前的修改时间, 与上一次记录不同时返回true, 文件被删除或新建也算修改
+fn update_config_modified(path: &str) -> bool {
+    let modified = get_modified(path);
+    let mut last_modified = CONFIG_MODIFIED.lock().unwrap();
+    last_modified.insert(path.to_string(), modified) != Some(modified)
+}
+
+// 配置文件的监听线程, stop或被丢弃后立即退出
+pub struct ConfigWatcher {
+    stop_sender: mpsc::Sender<()>,
+    thread: std::thread::JoinHandle<()>,
+}
+
+impl ConfigWatcher {
+    // 停止监听并等待线程退出
+    pub fn stop(self) {
+        drop(self.stop_sender);
+        if self.thread.join().is_err() {
+            error!("[ConfigWatcher::stop] config watcher thread panicked");
+        }
+    }
+}
+
+// 轮询系统配置文件和用户配置文件的修改时间, 外部修改后升级并重新读取配置, 更新内存中的配置
+// 出错时保留当前配置并返回错误
+pub fn watch_config_file<F>(on_change: F) -> ConfigWatcher
+where
+    F: Fn(Result<Config, Vec<ConfigError>>) + Send + 'static,
+{
+    let system_path = layer::get_system_config_path();
+    record_config_modified(&system_path);
+    let (stop_sender, stop_receiver) = mpsc::channel::<()>();
+    let thread = std::thread::spawn(move || loop {
+        // 发送端被丢弃时停止
+        match stop_receiver.recv_timeout(CONFIG_WATCH_INTERVAL) {
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            _ => {
+                info!("[watch_config_file] config watcher stopped");
+                break;
+            }
+        }
+        let path = get_config_path();
+        // 两个文件都要更新记录, 不能短路
+        let system_changed = update_config_modified(&system_path);
+        let user_changed = path.as_deref().is_some_and(update_config_modified);
+        if !system_changed && !user_changed {
+            continue;
+        }
+        if let Some(path) = path.as_deref() {
+            if let Err(e) = migrate::migrate_file(path) {
+                error!("[watch_config_file] failed to migrate {}: {}", path, e);
+            }
+            // 升级会改写文件, 不需要再次重新加载
+            record_config_modified(path);
+        }
+        match resolve_config(path.as_deref()) {
+            Ok(config) => {
+                info!(
+                    "[watch_config_file] config changed, reload from: {}, system: {}",
+                    path.as_deref().unwrap_or(layer::BUILTIN_CONFIG_NAME),
+                    system_path
+                );
+                *CONFIG.lock().unwrap() = Some(config.clone());
+                CONFIG_ERRORS.lock().unwrap().clear();
+                if let Some(path) = path.as_deref() {
+                    save_last_good_config(path);
+                }
+                on_change(Ok(config));
+            }
+            Err(errors) => {
+                for e in errors.iter() {
+                    error!("[watch_config_file] keep current config: {}", e);
+                }
+                *CONFIG_ERRORS.lock().unwrap() = errors.clone();
+                on_change(Err(errors));
+            }
+        }
+    });
+    ConfigWatcher {
+        stop_sender,
+        thread,
     }
-    panic!("please check the config file: config.toml exists");
 }
 
 // 全局配置实例
@@ -56,43 +288,193 @@ use std::sync::Mutex;
 
 pub static CONFIG: Lazy<Mutex<Option<Config>>> = Lazy::new(|| Mutex::new(None));
 
+// 合并了前台应用覆盖的配置, 在show_hints开始时按前台窗口选择, 为None时使用CONFIG
+static APP_CONFIG: Lazy<Mutex<Option<Config>>> = Lazy::new(|| Mutex::new(None));
+
+// 最近一次加载配置文件遇到的错误, 为空表示正在使用配置文件中的配置
+pub static CONFIG_ERRORS: Lazy<Mutex<Vec<ConfigError>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
 // 初始化配置
 pub fn init_config() -> Config {
-    let config = load_config();
+    location::ensure_config_file();
+    let (config, errors) = load_config();
+    *CONFIG_ERRORS.lock().unwrap() = errors;
     let mut config_guard = CONFIG.lock().unwrap();
     *config_guard = Some(config.clone());
     config
 }
 
-// 获取配置
+// 加载之后才发现的配置问题, 如快捷键无法注册, 与加载错误一起在设置界面显示
+pub fn add_config_error(error: ConfigError) {
+    CONFIG_ERRORS.lock().unwrap().push(error);
+}
+
+pub fn get_config_errors() -> Vec<ConfigError> {
+    CONFIG_ERRORS.lock().unwrap().clone()
+}
+
+// 获取配置, 前台应用匹配到[[app]]时包含其覆盖
 pub fn get_config() -> Option<Config> {
+    let app_config = APP_CONFIG.lock().unwrap().clone();
+    app_config.or_else(get_base_config)
+}
+
+// 获取不含前台应用覆盖的配置, 用于设置界面和保存
+pub fn get_base_config() -> Option<Config> {
     CONFIG.lock().unwrap().clone()
 }
 
+// 按前台窗口选择[[app]]覆盖, 返回使用的配置是否有变化, 有变化时需要重建派生表
+pub fn select_app_override(window: Option<&crate::window::window::AppWindow>) -> bool {
+    let config = match get_base_config() {
+        Some(config) => config,
+        None => return false,
+    };
+    let app_config = window.and_then(|window| app::resolve(&config, window));
+    let mut current = APP_CONFIG.lock().unwrap();
+    let changed = app_config.is_some() || current.is_some();
+    *current = app_config;
+    changed
+}
+
+// 会话结束或配置重新加载后丢弃合并的覆盖, 返回之前是否有覆盖
+pub fn clear_app_override() -> bool {
+    APP_CONFIG.lock().unwrap().take().is_some()
+}
+
 // 为前端提供的配置获取命令
 #[tauri::command]
 pub fn get_config_for_frontend() -> Config {
-    get_config().unwrap_or_else(|| {
-        let config = load_config();
-        let mut config_guard = CONFIG.lock().unwrap();
-        *config_guard = Some(config.clone());
-        config
-    })
+    get_base_config().unwrap_or_else(init_config)
 }
 
-// 为前端提供的配置保存命令
+// 为前端提供的配置错误获取命令
 #[tauri::command]
-pub fn save_config_for_frontend(config: Config) {
-    // 重排序 keyboard.available_key
-    let mut config = config;
+pub fn get_config_errors_for_frontend() -> Vec<ConfigError> {
+    get_config_errors()
+}
+
+// 为前端提供的配置保存命令, 保存后立即生效, 校验失败时不保存并返回错误
+#[tauri::command]
+pub fn save_config_for_frontend(
+    app_handle: tauri::AppHandle,
+    config: Config,
+) -> Result<(), Vec<ConfigError>> {
     debug!("[save_config_for_frontend] save config: {:?}", config);
-    let mut available_keys_vec = config
-        .keyboard
-        .available_key
-        .into_iter()
-        .collect::<Vec<_>>();
-    available_keys_vec.sort_by_key(|k| k.1);
-    config.keyboard.available_key = available_keys_vec.into_iter().collect();
+    let path = location::get_config_file();
+    let errors = error::validate_config(&config, &path);
+    if !errors.is_empty() {
+        error!("[save_config_for_frontend] invalid config: {:?}", errors);
+        return Err(errors);
+    }
+    save_config(config.clone()).map_err(|e| vec![e])?;
+    CONFIG_ERRORS.lock().unwrap().clear();
+    crate::apply_config(&app_handle, &config);
+    Ok(())
+}
+
+// 为前端提供的配置备份列表
+#[tauri::command]
+pub fn get_config_backups_for_frontend() -> Vec<file::ConfigBackup> {
+    match get_config_path() {
+        Some(path) => file::list_backups(&path),
+        None => Vec::new(),
+    }
+}
+
+// 从备份恢复配置文件并立即生效, 备份无法使用时不恢复并返回错误
+#[tauri::command]
+pub fn restore_config_backup_for_frontend(
+    app_handle: tauri::AppHandle,
+    index: usize,
+) -> Result<(), Vec<ConfigError>> {
+    let path = location::get_config_file();
+    let backup_path = file::get_backup_path(&path, index)
+        .to_string_lossy()
+        .to_string();
+    let config = resolve_config(Some(&backup_path))?;
+    let read_error = |e: std::io::Error| {
+        vec![ConfigError::Read {
+            path: backup_path.clone(),
+            message: e.to_string(),
+        }]
+    };
+    let content = fs::read_to_string(&backup_path).map_err(read_error)?;
+    // 恢复前的文件也会被备份, 恢复操作可以撤销
+    file::write_with_backup(&path, &content).map_err(read_error)?;
+    info!("[restore_config_backup_for_frontend] restore {} from {}", path, backup_path);
+    record_config_modified(&path);
+    save_last_good_config(&path);
+    *CONFIG.lock().unwrap() = Some(config.clone());
+    CONFIG_ERRORS.lock().unwrap().clear();
+    crate::apply_config(&app_handle, &config);
+    if let Some(window) = app_handle.get_webview_window("main") {
+        if let Err(e) = window.emit("config-reloaded", config) {
+            error!("[restore_config_backup_for_frontend] config-reloaded failed: {}", e);
+        }
+    }
+    Ok(())
+}
+
+// 切换到指定的profile并重新加载配置, 空字符串为基础配置, 失败时保持原来的profile
+pub fn switch_profile(name: &str) -> Result<Config, Vec<ConfigError>> {
+    let previous = profile::set_active_profile(Some(name.to_string()));
+    let source = get_config_path();
+    let result = resolve_config(source.as_deref()).and_then(|config| {
+        if name.is_empty() || config.profiles.contains_key(name) {
+            return Ok(config);
+        }
+        Err(vec![ConfigError::Invalid {
+            path: source.clone().unwrap_or_else(|| layer::BUILTIN_CONFIG_NAME.to_string()),
+            field: profile::PROFILES_KEY.to_string(),
+            message: format!("unknown profile `{}`", name),
+        }])
+    });
+    match result {
+        Ok(config) => {
+            info!("[switch_profile] switch to profile: {}", name);
+            *CONFIG.lock().unwrap() = Some(config.clone());
+            CONFIG_ERRORS.lock().unwrap().clear();
+            Ok(config)
+        }
+        Err(errors) => {
+            error!("[switch_profile] failed to switch to {}: {:?}", name, errors);
+            profile::set_active_profile(previous);
+            Err(errors)
+        }
+    }
+}
+
+// 为前端提供的当前profile, 空字符串为基础配置
+#[tauri::command]
+pub fn get_active_profile_for_frontend() -> String {
+    get_base_config()
+        .map(|config| profile::get_active_profile(&config))
+        .unwrap_or_default()
+}
+
+// 为前端提供的profile切换命令, 切换后立即生效
+#[tauri::command]
+pub fn switch_profile_for_frontend(
+    app_handle: tauri::AppHandle,
+    name: String,
+) -> Result<(), Vec<ConfigError>> {
+    crate::switch_profile(&app_handle, &name)
+}
+
+// 为前端提供的配置JSON Schema, hint类型和profile名取自当前配置
+#[tauri::command]
+pub fn get_config_schema_for_frontend() -> serde_json::Value {
+    schema::generate_schema(&get_base_config().unwrap_or_else(init_config))
+}
+
+// 更新内存中的配置并写入配置文件, 写入失败时返回错误, 内存中的配置仍会更新
+pub fn save_config(config: Config) -> Result<(), ConfigError> {
+    // 按扫描码重排序 keyboard.available_key, 未知的按键名放在最后
+    let mut config = config;
+    config.keyboard.available_key.sort_by_key(|name| {
+        crate::input::key::Key::from_name(name).map_or(u16::MAX, |key| key.scancode())
+    });
 
     // 更新内存中的配置
     {
@@ -100,41 +482,46 @@ pub fn save_config_for_frontend(config: Config) {
         *config_guard = Some(config.clone());
     }
 
-    // 获取当前配置文件路径，如果不存在则使用默认路径
-    let config_path = get_config_path().unwrap_or_else(|| {
-        if cfg!(debug_assertions) {
-            "src-tauri/config.toml".to_string()
-        } else {
-            "config.toml".to_string()
+    let config_path = location::get_config_file();
+    let write_error = |message: String| {
+        error!("[save_config] failed to write {}: {}", config_path, message);
+        ConfigError::Read {
+            path: config_path.clone(),
+            message,
         }
-    });
+    };
 
     // 确保目标目录存在
     if let Some(parent) = Path::new(&config_path).parent() {
         if !parent.exists() {
-            if let Err(e) = std::fs::create_dir_all(parent) {
-                error!(
-                    "[save_config_for_frontend] Failed to create config directory: {}",
-                    e
-                );
-            }
+            std::fs::create_dir_all(parent).map_err(|e| write_error(e.to_string()))?;
         }
     }
 
-    match toml::to_string_pretty(&config) {
-        Ok(config_str) => {
-            if let Err(e) = fs::write(&config_path, config_str) {
-                error!(
-                    "[save_config_for_frontend] Failed to write config file: {}",
-                    e
-                );
-            }
-        }
-        Err(e) => {
-            error!(
-                "[save_config_for_frontend] Failed to serialize config: {}",
-                e
-            );
-        }
-    }
+    let config_str = get_user_config_content(&config, &config_path).map_err(write_error)?;
+    file::write_with_backup(&config_path, &config_str).map_err(|e| write_error(e.to_string()))?;
+    // 自己写入的修改不需要再由监听线程重新加载
+    record_config_modified(&config_path);
+    save_last_good_config(&config_path);
+    Ok(())
+}
+
+// 在原有用户配置文件上更新有变化的值, 新增的键只写入与内置默认配置和系统配置不同的值
+// 当前profile覆盖的值被修改时写入该profile
+fn get_user_config_content(config: &Config, config_path: &str) -> Result<String, String> {
+    let layers = layer::load_layers().map_err(|e| e.to_string())?;
+    let mut table = match toml::Value::try_from(config).map_err(|e| e.to_string())? {
+        toml::Value::Table(table) => table,
+        _ => return Err("config is not a table".to_string()),
+    };
+    let active_profile = profile::get_active_profile(config);
+    profile::absorb_changes(&mut table, &active_profile);
+    let previous_user = layer::read_layer(config_path).ok();
+    let mut view = layers.user_view(&table, previous_user.as_ref(), &active_profile);
+    let mut additions = layers.user_diff(&table, previous_user.as_ref(), &active_profile);
+    // 版本号总是写入, 否则下次加载会被当作旧版本配置
+    migrate::set_current_version(&mut view);
+    migrate::set_current_version(&mut additions);
+    let content = fs::read_to_string(config_path).unwrap_or_default();
+    Ok(file::update_document(&content, &view, &additions))
 }
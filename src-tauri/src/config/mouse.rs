@@ -1,20 +1,84 @@
 use std::collections::HashSet;
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 pub struct MouseConfig {
     pub step: MouseStepConfig,
+    #[serde(default)]
+    pub motion: MotionConfig,
+    #[serde(default)]
+    pub scroll: ScrollConfig,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+// 滚动方式
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(default)]
+pub struct ScrollConfig {
+    // 将滚动距离拆分为多次高精度滚动, 关闭时一次发送全部距离
+    pub smooth: bool,
+    // 平滑滚动的时长, 单位毫秒
+    pub duration: u64,
+    // 平滑滚动的间隔, 单位毫秒
+    pub tick: u64,
+    // 停止输入后惯性滚动的时长, 单位毫秒, 0表示没有惯性
+    pub inertia: u64,
+    // 用 Shift+垂直滚轮 代替水平滚轮, 用于不响应水平滚轮的程序
+    pub horizontal_fallback: bool,
+    // 直接向hint下的窗口发送滚动消息, 不需要移动光标
+    pub target_window: bool,
+}
+
+impl Default for ScrollConfig {
+    fn default() -> Self {
+        Self {
+            smooth: false,
+            duration: 150,
+            tick: 10,
+            inertia: 0,
+            horizontal_fallback: false,
+            target_window: false,
+        }
+    }
+}
+
+// 按住方向键时的连续移动, 速度单位为每秒移动的step数
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(default)]
+pub struct MotionConfig {
+    // 关闭时每次按键事件移动一个step
+    pub enabled: bool,
+    // 移动间隔, 单位毫秒
+    pub tick: u64,
+    // 起始速度
+    pub initial_rate: f64,
+    // 最大速度
+    pub max_rate: f64,
+    // 加速度, 每秒增加的速度
+    pub acceleration: f64,
+}
+
+impl Default for MotionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tick: 10,
+            initial_rate: 15.0,
+            max_rate: 60.0,
+            acceleration: 60.0,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 pub struct MouseStepConfig {
     pub translate: Vec<MouseStep>,
     pub scroll: Vec<MouseStep>,
     pub drag: Vec<MouseStep>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 pub struct MouseStep {
     pub x: i32,
     pub y: i32,
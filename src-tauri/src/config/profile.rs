@@ -0,0 +1,230 @@
+use log::{debug, info};
+use once_cell::sync::Lazy;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use toml::{Table, Value};
+
+use super::Config;
+use crate::monitor::MonitorInfo;
+
+pub const PROFILE_KEY: &str = "profile";
+pub const PROFILES_KEY: &str = "profiles";
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+pub struct ProfileConfig {
+    // 启动时使用的profile, 为空则只使用基础配置
+    #[serde(default)]
+    pub default: String,
+    // 依次切换到下一个profile的全局快捷键, 为空则不注册
+    #[serde(default)]
+    pub hotkey: String,
+    // 启动时按显示器布局选择profile, 使用第一条匹配的规则
+    #[serde(default)]
+    pub auto_select: Vec<ProfileRule>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct ProfileRule {
+    pub profile: String,
+    // 显示器数量, 0表示不限
+    #[serde(default)]
+    pub monitors: usize,
+    // 按显示器顺序(从上到下, 从左到右)排列的分辨率, 如 "2560x1440", 为空表示不限
+    #[serde(default)]
+    pub resolutions: Vec<String>,
+}
+
+// 运行时切换到的profile, None表示还没有切换过, 使用profile.default
+static ACTIVE_PROFILE: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+// 设置当前profile, 返回之前的值, 切换失败时用来还原
+pub fn set_active_profile(name: Option<String>) -> Option<String> {
+    info!("[set_active_profile] active profile: {:?}", name);
+    std::mem::replace(&mut *ACTIVE_PROFILE.lock().unwrap(), name)
+}
+
+// 当前使用的profile名, 未切换过时使用profile.default, profile不存在时为空
+fn resolve_name(default: &str, exists: impl Fn(&str) -> bool) -> String {
+    let name = ACTIVE_PROFILE
+        .lock()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| default.to_string());
+    if name.is_empty() || exists(&name) {
+        return name;
+    }
+    debug!("[resolve_name] profile not found, use base config: {}", name);
+    String::new()
+}
+
+pub fn get_active_profile(config: &Config) -> String {
+    resolve_name(&config.profile.default, |name| {
+        config.profiles.contains_key(name)
+    })
+}
+
+// 合并各层后的配置中当前使用的profile名
+pub fn get_active_profile_in_table(table: &Table) -> String {
+    let default = table
+        .get(PROFILE_KEY)
+        .and_then(|profile| profile.get("default"))
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    resolve_name(default, |name| get_profile(table, name).is_some())
+}
+
+// profile对基础配置的部分覆盖, 其中的profile和profiles不生效
+pub fn get_profile(table: &Table, name: &str) -> Option<Table> {
+    if name.is_empty() {
+        return None;
+    }
+    let mut profile = table.get(PROFILES_KEY)?.get(name)?.as_table()?.clone();
+    profile.remove(PROFILE_KEY);
+    profile.remove(PROFILES_KEY);
+    Some(profile)
+}
+
+// 当前profile覆盖的值被修改时, 修改写入该profile而不是基础配置
+pub fn absorb_changes(table: &mut Table, name: &str) {
+    let mut profile = match get_profile(table, name) {
+        Some(profile) => profile,
+        None => return,
+    };
+    update_from(&mut profile, table);
+    if let Some(Value::Table(profiles)) = table.get_mut(PROFILES_KEY) {
+        if let Some(Value::Table(target)) = profiles.get_mut(name) {
+            for (key, value) in profile {
+                target.insert(key, value);
+            }
+        }
+    }
+}
+
+fn update_from(profile: &mut Table, current: &Table) {
+    for (key, value) in profile.iter_mut() {
+        match (value, current.get(key)) {
+            (Value::Table(profile), Some(Value::Table(current))) => update_from(profile, current),
+            (value, Some(current)) if value != current => {
+                debug!("[update_from] profile value changed: {}", key);
+                *value = current.clone();
+            }
+            _ => {}
+        }
+    }
+}
+
+// 可切换的profile, 空字符串为基础配置
+pub fn get_profile_names(config: &Config) -> Vec<String> {
+    std::iter::once(String::new())
+        .chain(config.profiles.keys().cloned())
+        .collect()
+}
+
+// 依次切换时的下一个profile, 最后一个之后回到基础配置
+pub fn get_next_profile(config: &Config) -> String {
+    let names = get_profile_names(config);
+    let active = get_active_profile(config);
+    let index = names.iter().position(|name| *name == active).unwrap_or(0);
+    names[(index + 1) % names.len()].clone()
+}
+
+// 按显示器布局选择profile, 没有匹配的规则时返回None
+pub fn select_by_monitors(config: &Config, monitors: &[MonitorInfo]) -> Option<String> {
+    let resolutions = monitors
+        .iter()
+        .map(|monitor| format!("{}x{}", monitor.width, monitor.height))
+        .collect::<Vec<_>>();
+    config
+        .profile
+        .auto_select
+        .iter()
+        .find(|rule| {
+            (rule.monitors == 0 || rule.monitors == monitors.len())
+                && (rule.resolutions.is_empty()
+                    || rule
+                        .resolutions
+                        .iter()
+                        .map(|resolution| resolution.to_lowercase())
+                        .eq(resolutions.iter().cloned()))
+        })
+        .map(|rule| rule.profile.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::layer::{self, merge, Layers};
+
+    // 当前profile是全局的, 测试需要依次执行
+    static TEST_LOCK: Mutex<()> = Mutex::new(());
+
+    fn parse(content: &str) -> Table {
+        content.parse::<Table>().unwrap()
+    }
+
+    fn with_active_profile<T>(name: &str, test: impl FnOnce() -> T) -> T {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let previous = set_active_profile(Some(name.to_string()));
+        let result = test();
+        set_active_profile(previous);
+        result
+    }
+
+    const BASE: &str = "[hint.grid]\nrows = 4\ncolumns = 5";
+    const USER: &str = "[hint.grid]\nrows = 5\n[profiles.work.hint.grid]\nrows = 3";
+
+    fn layers() -> Layers {
+        Layers {
+            base: parse(BASE),
+            overrides: Table::new(),
+        }
+    }
+
+    fn rows(table: &Table) -> &Value {
+        &table["hint"]["grid"]["rows"]
+    }
+
+    // 在work下于设置界面把rows改为6, 按保存的流程得到用户配置文件的内容
+    fn save_in_work_profile() -> Table {
+        let layers = layers();
+        let mut table = layers.resolve(Some(parse(USER)));
+        assert_eq!(rows(&table), &Value::Integer(3));
+        merge(&mut table, parse("[hint.grid]\nrows = 6"));
+        absorb_changes(&mut table, "work");
+        layers.user_view(&table, Some(&parse(USER)), "work")
+    }
+
+    #[test]
+    fn save_with_profile_leaves_base_keys_untouched() {
+        with_active_profile("work", || {
+            let user = save_in_work_profile();
+            assert_eq!(rows(&user), &Value::Integer(5));
+            assert_eq!(user["profiles"]["work"]["hint"]["grid"]["rows"], Value::Integer(6));
+            assert_eq!(rows(&layers().resolve(Some(user))), &Value::Integer(6));
+        });
+    }
+
+    #[test]
+    fn switching_back_to_base_restores_base_keys() {
+        let user = with_active_profile("work", save_in_work_profile);
+        with_active_profile("", || {
+            let table = layers().resolve(Some(user));
+            assert_eq!(get_active_profile_in_table(&table), "");
+            assert_eq!(rows(&table), &Value::Integer(5));
+        });
+    }
+
+    #[test]
+    fn next_profile_wraps_around() {
+        let mut config: Config = toml::from_str(layer::BUILTIN_CONFIG).unwrap();
+        config.profiles.insert("work".to_string(), Table::new());
+        config.profiles.insert("game".to_string(), Table::new());
+        // 不存在的profile视为基础配置
+        for (active, next) in [("", "work"), ("work", "game"), ("game", ""), ("missing", "work")] {
+            with_active_profile(active, || {
+                assert_eq!(get_next_profile(&config), next, "active: {}", active);
+            });
+        }
+    }
+}
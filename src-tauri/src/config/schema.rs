@@ -0,0 +1,398 @@
+use std::fs;
+
+use schemars::gen::SchemaSettings;
+use serde_json::{json, Value};
+use windows::Win32::System::Console::{
+    AttachConsole, GetStdHandle, ATTACH_PARENT_PROCESS, STD_OUTPUT_HANDLE,
+};
+
+use super::{keybinding, layer, profile, Config};
+use crate::input::key;
+
+// 输出JSON Schema的命令行子命令, 如 ScreenBuoy.exe schema config.schema.json
+pub const SCHEMA_COMMAND: &str = "schema";
+
+// UI Automation控件类型id和名称, 用于hint.types.*.element_control_types
+const CONTROL_TYPES: &[(i32, &str)] = &[
+    (50000, "Button"),
+    (50001, "Calendar"),
+    (50002, "CheckBox"),
+    (50003, "ComboBox"),
+    (50004, "Edit"),
+    (50005, "Hyperlink"),
+    (50006, "Image"),
+    (50007, "ListItem"),
+    (50008, "List"),
+    (50009, "Menu"),
+    (50010, "MenuBar"),
+    (50011, "MenuItem"),
+    (50012, "ProgressBar"),
+    (50013, "RadioButton"),
+    (50014, "ScrollBar"),
+    (50015, "Slider"),
+    (50016, "Spinner"),
+    (50017, "StatusBar"),
+    (50018, "Tab"),
+    (50019, "TabItem"),
+    (50020, "Text"),
+    (50021, "ToolBar"),
+    (50022, "ToolTip"),
+    (50023, "Tree"),
+    (50024, "TreeItem"),
+    (50025, "Custom"),
+    (50026, "Group"),
+    (50027, "Thumb"),
+    (50028, "DataGrid"),
+    (50029, "DataItem"),
+    (50030, "Document"),
+    (50031, "SplitButton"),
+    (50032, "Window"),
+    (50033, "Pane"),
+    (50034, "Header"),
+    (50035, "HeaderItem"),
+    (50036, "Table"),
+    (50037, "TitleBar"),
+    (50038, "Separator"),
+    (50039, "SemanticZoom"),
+    (50040, "AppBar"),
+];
+
+// 字段说明, 路径中*表示映射的值, []表示数组的元素
+const DESCRIPTIONS: &[(&str, &str)] = &[
+    ("config_version", "Config format version. Older files are migrated when loaded."),
+    ("hint", "Hint labels: characters, styles, types and the grid."),
+    ("hint.charsets", "Characters for each position of a label. The number of charsets is the label length."),
+    ("hint.charset_extra", "Leading characters for one more position when the charsets run out of labels."),
+    ("hint.style", "CSS applied to every label."),
+    ("hint.types", "Hint types by name. Each element gets the type that lists its control type."),
+    ("hint.types.*.style", "CSS added to labels of this type."),
+    ("hint.types.*.z_index", "Stacking order of labels of this type. Higher is drawn on top."),
+    ("hint.types.*.element_control_types", "UI Automation control type IDs that get this type."),
+    ("hint.grid", "Labels at fixed cells of each monitor, for places without UI elements."),
+    ("hint.grid.rows", "Number of rows the monitor is divided into."),
+    ("hint.grid.columns", "Number of columns the monitor is divided into."),
+    ("hint.grid.show_at_rows", "Rows that show labels, starting from 1."),
+    ("hint.grid.show_at_columns", "Columns that show labels, starting from 1."),
+    ("hint.grid.hint_type", "Hint type of the grid labels."),
+    ("hint.sub_grid", "Grid opened at a hint to refine the target point."),
+    ("hint.sub_grid.chars", "Labels of the cells, assigned row by row."),
+    ("hint.sub_grid.use_bounds", "Cover the element bounds. Otherwise, or without bounds, use radius."),
+    ("hint.sub_grid.radius", "Distance from the center to the edge in logical pixels."),
+    ("hint.pins", "Labels at fixed positions, relative to a monitor or a matched window."),
+    ("hint.pins[].monitor", "Monitor index used when no window is given."),
+    ("hint.pins[].window_class", "Window class, exact match."),
+    ("hint.pins[].window_title", "Part of the window title."),
+    ("hint.pins[].label", "Fixed label text. Empty to assign one automatically."),
+    ("hint.pins[].hint_type", "Hint type. Empty to use hint.grid.hint_type."),
+    ("keybinding", "Key bindings. Each command lists the keys that trigger it."),
+    ("keybinding.hotkey_buoy", "Global hotkey that shows the hints, e.g. Alt+H."),
+    ("keybinding.hotkey_repeat", "Global hotkey that repeats the last action without hints. Empty to disable."),
+    ("keybinding.global", "Bindings active while the hints are shown."),
+    ("keybinding.at_hint", "Bindings active after a hint is selected."),
+    ("keybinding.global.pipelines", "Pipeline name to keys."),
+    ("keybinding.global.send_keys", "Key combination, e.g. LCtrl+C, to keys."),
+    ("keybinding.at_hint.pipelines", "Pipeline name to keys."),
+    ("keybinding.at_hint.type_text", "Snippet name to keys."),
+    ("keybinding.at_hint.send_keys", "Key combination, e.g. LCtrl+C, to keys."),
+    ("keybinding.at_hint.click_send_keys", "Key combination sent after clicking the hint, to keys."),
+    ("keybinding.at_hint.click", "Click description, e.g. LCtrl+left or left*3, to keys."),
+    ("keybinding.modes", "Modes by name, with their own bindings."),
+    ("keybinding.modes.*.enter", "Keys that enter the mode during a hint session."),
+    ("keybinding.modes.*.hotkey", "Global hotkey that enters the mode without hints. Empty to disable."),
+    ("keybinding.modes.*.bindings", "Command to keys."),
+    ("keybinding.modes.*.indicator", "Text shown in the overlay while the mode is active. Empty to hide."),
+    ("mouse.step", "Distances moved per key press. A step with modifier is used while one of its keys is held."),
+    ("mouse.motion", "Continuous movement while a direction key is held, in steps per second."),
+    ("mouse.scroll", "Scrolling behaviour."),
+    ("keyboard.available_key", "Named keys handled by ScreenBuoy. Letter, digit and symbol keys are always handled."),
+    ("keyboard.propagation_modifier", "Modifier keys passed on to other programs."),
+    ("keyboard.map_left_right", "Keys on the left and right of each key, used for HintLeftKey and HintRightKey."),
+    ("system.logging_level", "Logging level: debug, info, warn, error or none."),
+    ("system.debug_mode", "Show the overlay windows for debugging."),
+    ("ui_automation.collect_interval", "Time between UI element scans in milliseconds."),
+    ("ui_automation.cache_ttl", "Time cached elements of covered windows are kept in milliseconds."),
+    ("action.repeat_target", "Target of a repeated action: the same point, or the same element found again."),
+    ("action.pipelines", "Named sequences of steps, bound with pipeline:<name>."),
+    ("action.snippets", "Named texts, bound with type_text:<name>."),
+    ("action.type_interval", "Delay between typed characters in milliseconds."),
+    ("action.click_press_duration", "Time a mouse button is held for a click in milliseconds."),
+    ("profile.default", "Profile used at startup. Empty for the base config."),
+    ("profile.hotkey", "Global hotkey that switches to the next profile. Empty to disable."),
+    ("profile.auto_select", "Rules that pick a profile from the monitor layout at startup. The first match wins."),
+    ("profile.auto_select[].monitors", "Number of monitors. 0 for any."),
+    ("profile.auto_select[].resolutions", "Resolutions in monitor order, e.g. 2560x1440. Empty for any."),
+    ("profiles", "Named profiles. Each lists only the values it changes."),
+    ("app", "Overrides for the foreground application. The first match is used for a hint session."),
+    ("app[].process_name", "Executable name, case-insensitive, e.g. Code.exe."),
+    ("app[].class_name", "Window class, exact match."),
+    ("app[].title", "Regular expression on the window title."),
+    ("app[].elements", "Show element hints. When false only grid and pinned hints are shown."),
+    ("app[].hint", "Values of hint to change."),
+    ("app[].keybinding", "Values of keybinding.at_hint to change."),
+];
+
+// 取值范围, 路径写法与DESCRIPTIONS相同
+const RANGES: &[(&str, Option<i64>, Option<i64>)] = &[
+    ("hint.grid.rows", Some(1), None),
+    ("hint.grid.columns", Some(1), None),
+    ("hint.grid.show_at_rows[]", Some(1), None),
+    ("hint.grid.show_at_columns[]", Some(1), None),
+    ("hint.sub_grid.rows", Some(1), None),
+    ("hint.sub_grid.columns", Some(1), None),
+    ("hint.sub_grid.radius", Some(0), None),
+    ("ui_automation.collect_interval", Some(1), None),
+    ("mouse.motion.tick", Some(1), None),
+    ("mouse.scroll.tick", Some(1), None),
+];
+
+// 至少需要一个元素的数组
+const NON_EMPTY: &[&str] = &[
+    "hint.charsets",
+    "hint.charsets[]",
+    "mouse.step.translate",
+    "mouse.step.scroll",
+    "mouse.step.drag",
+];
+
+// 按路径找到字段的schema, 路径中*表示映射的值, []表示数组的元素
+fn find_mut<'a>(schema: &'a mut Value, path: &str) -> Option<&'a mut Value> {
+    let mut node = schema;
+    for part in path.split('.') {
+        let (name, is_items) = match part.strip_suffix("[]") {
+            Some(name) => (name, true),
+            None => (part, false),
+        };
+        node = if name == "*" {
+            node.get_mut("additionalProperties")?
+        } else {
+            node.get_mut("properties")?.get_mut(name)?
+        };
+        if is_items {
+            node = node.get_mut("items")?;
+        }
+    }
+    Some(node)
+}
+
+fn set(schema: &mut Value, path: &str, key: &str, value: Value) {
+    match find_mut(schema, path).and_then(Value::as_object_mut) {
+        Some(node) => {
+            node.insert(key.to_string(), value);
+        }
+        None => log::error!("[set] schema path not found: {}", path),
+    }
+}
+
+// 按键名: 所有按键和hint相关的按键
+fn get_key_names() -> Vec<&'static str> {
+    let mut keys = key::get_key_names();
+    keys.extend([
+        super::keyboard::HINT_KEY,
+        super::keyboard::HINT_LEFT_KEY,
+        super::keyboard::HINT_RIGHT_KEY,
+    ]);
+    keys
+}
+
+// 将字符串数组的元素改为引用按键名, 包括映射的值和枚举的各个变体
+fn set_key_items(node: &mut Value) {
+    match node.get_mut("items") {
+        Some(items) if items.get("type") == Some(&json!("string")) => {
+            *items = json!({ "$ref": "#/definitions/key" });
+        }
+        Some(Value::Array(items)) => items.iter_mut().for_each(set_key_items),
+        Some(items) => set_key_items(items),
+        None => {}
+    }
+    if let Some(Value::Object(properties)) = node.get_mut("properties") {
+        properties.values_mut().for_each(set_key_items);
+    }
+    if let Some(additional) = node.get_mut("additionalProperties").filter(|v| v.is_object()) {
+        set_key_items(additional);
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(Value::Array(variants)) = node.get_mut(key) {
+            variants.iter_mut().for_each(set_key_items);
+        }
+    }
+}
+
+// 去掉所有required, 使字段都可以省略
+fn remove_required(node: &mut Value) {
+    match node {
+        Value::Object(object) => {
+            object.remove("required");
+            object.values_mut().for_each(remove_required);
+        }
+        Value::Array(array) => array.iter_mut().for_each(remove_required),
+        _ => {}
+    }
+}
+
+// 由Config生成JSON Schema, hint类型和profile名取自当前配置
+pub fn generate_schema(config: &Config) -> Value {
+    let generator = SchemaSettings::draft07()
+        .with(|settings| settings.inline_subschemas = true)
+        .into_generator();
+    let mut schema = serde_json::to_value(generator.into_root_schema_for::<Config>())
+        .unwrap_or_else(|_| json!({}));
+    // 配置文件只需写出与默认配置不同的值, 所有字段都可以省略
+    remove_required(&mut schema);
+
+    schema["title"] = json!("ScreenBuoy config");
+    schema["definitions"] = json!({
+        "key": {
+            "type": "string",
+            "enum": get_key_names(),
+        },
+        "control_type": {
+            "type": "integer",
+            "oneOf": CONTROL_TYPES
+                .iter()
+                .map(|(id, name)| json!({ "const": id, "description": name }))
+                .collect::<Vec<_>>(),
+        },
+    });
+
+    for (path, description) in DESCRIPTIONS {
+        set(&mut schema, path, "description", json!(description));
+    }
+    for (path, minimum, maximum) in RANGES {
+        if let Some(minimum) = minimum {
+            set(&mut schema, path, "minimum", json!(minimum));
+        }
+        if let Some(maximum) = maximum {
+            set(&mut schema, path, "maximum", json!(maximum));
+        }
+    }
+    for path in NON_EMPTY {
+        set(&mut schema, path, "minItems", json!(1));
+    }
+
+    for path in ["keybinding", "keyboard.propagation_modifier", "mouse.step", "action.pipelines"] {
+        if let Some(node) = find_mut(&mut schema, path) {
+            set_key_items(node);
+        }
+    }
+    set(
+        &mut schema,
+        "keyboard.available_key",
+        "items",
+        json!({ "type": "string", "enum": key::get_named_keys() }),
+    );
+    set(
+        &mut schema,
+        "hint.types.*.element_control_types",
+        "items",
+        json!({ "$ref": "#/definitions/control_type" }),
+    );
+    set(
+        &mut schema,
+        "keybinding.modes.*.bindings",
+        "propertyNames",
+        json!({
+            "anyOf": [
+                { "enum": keybinding::COMMANDS },
+                { "pattern": format!("^({}):.+$", keybinding::PARAMETERIZED_COMMANDS.join("|")) },
+            ]
+        }),
+    );
+
+    for path in layer::REPLACEABLE_TABLES {
+        let mut properties = serde_json::Map::new();
+        properties.insert(
+            layer::REPLACE_MARKER.to_string(),
+            json!({
+                "type": "boolean",
+                "description": "Replace the whole table of the lower layers instead of merging entries, so entries can be removed.",
+            }),
+        );
+        set(&mut schema, path, "properties", Value::Object(properties));
+    }
+
+    let hint_types = config.hint.types.keys().cloned().collect::<Vec<_>>();
+    set(&mut schema, "hint.grid.hint_type", "enum", json!(hint_types));
+    let mut pin_hint_types = vec![String::new()];
+    pin_hint_types.extend(hint_types);
+    set(&mut schema, "hint.pins[].hint_type", "enum", json!(pin_hint_types));
+
+    let profiles = config.profiles.keys().cloned().collect::<Vec<_>>();
+    set(&mut schema, "profile.auto_select[].profile", "enum", json!(profiles));
+    set(
+        &mut schema,
+        "profile.default",
+        "enum",
+        json!(profile::get_profile_names(config)),
+    );
+    set(
+        &mut schema,
+        "profile.auto_select[].resolutions[]",
+        "pattern",
+        json!("^[0-9]+[xX][0-9]+$"),
+    );
+    set(
+        &mut schema,
+        "action.repeat_target",
+        "enum",
+        json!([super::action::REPEAT_TARGET_POINT, super::action::REPEAT_TARGET_ELEMENT]),
+    );
+    if let Some(Value::Array(steps)) = find_mut(&mut schema, "action.pipelines.*[]")
+        .and_then(|node| node.get_mut("oneOf"))
+    {
+        for step in steps.iter_mut() {
+            if let Some(button) = step.pointer_mut("/properties/button") {
+                button["enum"] = json!([
+                    super::action::BUTTON_LEFT,
+                    super::action::BUTTON_RIGHT,
+                    super::action::BUTTON_MIDDLE,
+                    super::action::BUTTON_X1,
+                    super::action::BUTTON_X2,
+                ]);
+            }
+        }
+    }
+
+    // 覆盖中的字段与对应的配置相同
+    let hint = schema.pointer("/properties/hint").cloned();
+    let at_hint = schema.pointer("/properties/keybinding/properties/at_hint").cloned();
+    if let Some(hint) = hint {
+        set(&mut schema, "app[].hint", "properties", hint["properties"].clone());
+    }
+    if let Some(at_hint) = at_hint {
+        set(&mut schema, "app[].keybinding", "properties", json!({ "at_hint": at_hint }));
+        set(&mut schema, "app[].keybinding", "additionalProperties", json!(false));
+    }
+    let mut profile_properties = schema["properties"].clone();
+    if let Some(properties) = profile_properties.as_object_mut() {
+        for key in [profile::PROFILE_KEY, profile::PROFILES_KEY, "app", "config_version"] {
+            properties.remove(key);
+        }
+    }
+    set(&mut schema, "profiles.*", "properties", profile_properties);
+
+    schema
+}
+
+// 发布版本没有控制台, 标准输出未重定向时连接到启动它的命令行窗口
+pub fn attach_parent_console() {
+    unsafe {
+        let redirected = GetStdHandle(STD_OUTPUT_HANDLE)
+            .map(|handle| !handle.is_invalid() && !handle.0.is_null())
+            .unwrap_or(false);
+        if !redirected {
+            let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+        }
+    }
+}
+
+// 将JSON Schema写入文件, path为None时输出到标准输出
+pub fn write_schema(config: &Config, path: Option<&str>) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(&generate_schema(config)).map_err(|e| e.to_string())?;
+    match path {
+        Some(path) => fs::write(path, content).map_err(|e| format!("{}: {}", path, e)),
+        None => {
+            println!("{}", content);
+            Ok(())
+        }
+    }
+}
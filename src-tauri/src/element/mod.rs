@@ -9,9 +9,13 @@ use element::collect_ui_elements;
 use std::time::Duration;
 
 pub fn setup_ui_collection(config: &config::Config) {
-    let collect_interval = config.ui_automation.collect_interval;
+    let mut collect_interval = config.ui_automation.collect_interval;
     std::thread::spawn(move || loop {
         std::thread::sleep(Duration::from_millis(collect_interval));
         collect_ui_elements();
+        // 每轮重新读取间隔, 配置重新加载后无需重启线程
+        if let Some(config) = config::get_config() {
+            collect_interval = config.ui_automation.collect_interval;
+        }
     });
 }
@@ -11,6 +11,8 @@ use crate::window::WindowElement;
 pub struct UIAutomationRequest {
     pub automation: IUIAutomation,
     pub condition: IUIAutomationCondition,
+    // 一次取回整棵子树及所需属性, 遍历时不再跨进程调用
+    pub cache_request: IUIAutomationCacheRequest,
 }
 
 #[derive(Clone)]
@@ -25,6 +27,8 @@ pub struct UIElement {
     pub control_type: i32,
     // element_type: 0-default, 1-window, 2-pane, 3-tab, 4-button, 5-scrollbar
     pub element_type: usize,
+    // 最近的已收集祖先元素在本窗口元素列表中的下标, 顶层元素为None
+    pub parent: Option<usize>,
 }
 
 static ELEMENTS_CACHE_WITH_EXPIRATION: Lazy<Mutex<HashMap<i64, (Vec<UIElement>, u128)>>> = 
@@ -37,8 +41,24 @@ impl UIAutomationRequest {
                 .expect("Failed to create UI Automation");
             let condition = automation.CreateTrueCondition()
                 .expect("Failed to create condition");
+            let cache_request = automation.CreateCacheRequest()
+                .expect("Failed to create cache request");
+            for property_id in [
+                UIA_IsEnabledPropertyId,
+                UIA_IsOffscreenPropertyId,
+                UIA_ControlTypePropertyId,
+                UIA_BoundingRectanglePropertyId,
+            ] {
+                cache_request.AddProperty(property_id)
+                    .expect("Failed to add cache property");
+            }
+            cache_request.SetTreeScope(TreeScope_Subtree)
+                .expect("Failed to set cache tree scope");
+            // 与原始视图一致, 不过滤任何元素
+            cache_request.SetTreeFilter(&condition)
+                .expect("Failed to set cache tree filter");
             UIAutomationRequest {
-                automation, condition
+                automation, condition, cache_request
             }
         }
     }
@@ -46,17 +66,17 @@ impl UIAutomationRequest {
     pub fn get_elements_for_window(&self, window: &WindowElement) -> Option<Vec<UIElement>> {
         unsafe {
             let root_element = self.automation.ElementFromHandle(HWND(window.window_handle as *mut _)).ok()?;
-            match root_element.FindAll(TreeScope_Subtree, &self.condition) {
-                Ok(elements) => {
+            match root_element.BuildUpdatedCache(&self.cache_request) {
+                Ok(root_element) => {
                 let expire_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() + config::get_config().unwrap().ui_automation.cache_ttl as u128;
                 let window_handle = window.window_handle;
-                let elements = convert_ui_automation(elements, window_handle);
+                let elements = convert_ui_automation(&root_element, window_handle);
                 ELEMENTS_CACHE_WITH_EXPIRATION.lock().unwrap().insert(window_handle, (elements.clone(), expire_at));
                 debug!("[get_elements_for_window] get {} elements for window: {}", elements.len(), window_handle);
                 Some(elements)
             }
                 Err(e) => {
-                    error!("[get_elements_for_window] find all elements failed: {}", e);
+                    error!("[get_elements_for_window] build element cache failed: {}", e);
                     None
                 }
             }
@@ -92,84 +112,140 @@ pub fn get_cached_elements_for_window(window: &WindowElement) -> Option<Vec<UIEl
     request.get_cached_elements_for_window(window)
 }
 
-unsafe fn convert_ui_automation(all_elements: IUIAutomationElementArray, window_handle: i64) -> Vec<UIElement> {
-    let mut position_map: HashMap<(i32, i32), UIElement> = HashMap::new();
-    let count = match all_elements.Length() {
-        Ok(len) => len,
-        Err(_) => {
-            error!("[collect_ui_elements_for_window] get all elements length failed");
-            return Vec::new();
-        }
-    };
-
-    for i in 0..count {
-        let element = match all_elements.GetElement(i) {
-            Ok(e) => e,
-            Err(_) => continue
+// 深度优先先序遍历缓存的元素树, 保留元素之间的父子关系
+unsafe fn convert_ui_automation(root_element: &IUIAutomationElement, window_handle: i64) -> Vec<UIElement> {
+    let mut elements = Vec::new();
+    // 栈中保存待访问的元素及其最近的已收集祖先的下标
+    let mut stack = vec![(root_element.clone(), None)];
+    while let Some((element, parent)) = stack.pop() {
+        let parent = match convert_element(&element, window_handle, parent) {
+            Some(ui_element) => {
+                elements.push(ui_element);
+                Some(elements.len() - 1)
+            }
+            None => parent,
         };
-
-        // 检查可见性和启用状态
-        let (is_enabled, is_offscreen) = match (
-            element.CurrentIsEnabled(),
-            element.CurrentIsOffscreen(),
-        ) {
-            (Ok(e), Ok(o)) => (
-                e.as_bool(),
-                o.as_bool(),
-            ),
-            _ => continue
+        // 没有子元素时返回错误
+        let children = match element.GetCachedChildren() {
+            Ok(children) => children,
+            Err(_) => continue,
         };
-        if !is_enabled || is_offscreen {
-            continue;
+        let count = children.Length().unwrap_or(0);
+        // 逆序入栈, 保证兄弟元素按原顺序出栈
+        for i in (0..count).rev() {
+            if let Ok(child) = children.GetElement(i) {
+                stack.push((child, parent));
+            }
         }
+    }
+    merge_same_position(elements)
+}
 
-        let control_type_id = match element.CurrentControlType() {
-            Ok(id) => id,
-            Err(_) => continue,
-        };
-        // 获取元素类型和z_index
-        let (element_type, z_index) =
-            match config::hint::HINT_CONTROL_TYPES_ID_Z_MAP.get(&control_type_id.0) {
-                Some((element_type, z_index)) => (element_type, z_index),
-                None => continue,
-            };
+unsafe fn convert_element(element: &IUIAutomationElement, window_handle: i64, parent: Option<usize>) -> Option<UIElement> {
+    // 检查可见性和启用状态
+    let (is_enabled, is_offscreen) = match (
+        element.CachedIsEnabled(),
+        element.CachedIsOffscreen(),
+    ) {
+        (Ok(e), Ok(o)) => (
+            e.as_bool(),
+            o.as_bool(),
+        ),
+        _ => return None,
+    };
+    if !is_enabled || is_offscreen {
+        return None;
+    }
 
-        let rect = match element.CurrentBoundingRectangle() {
-            Ok(r) => r,
-            Err(_) => continue,
-        };
+    let control_type_id = element.CachedControlType().ok()?;
+    // 收集hint.types和[[app]]中用到的控件类型, hint类型在生成hints时按当前配置确定
+    let tables = config::get_derived_tables();
+    if !tables.collected_control_types.contains(&control_type_id.0) {
+        return None;
+    }
+    // 获取元素类型和z_index, 只在[[app]]中用到的控件类型为默认值
+    let (element_type, z_index) = tables
+        .hint_control_types_id_z_map
+        .get(&control_type_id.0)
+        .copied()
+        .unwrap_or_default();
 
-        let x = (rect.right + rect.left) / 2;
-        let y = (rect.bottom + rect.top) / 2;
-        debug!("[convert_ui_automation] get element:({},{}) with control_type: {}, element_type: {}, z_index: {}, rect: {:?}, window_handle: {}", 
-            x, y, control_type_id.0, element_type, z_index, rect, window_handle);
-        let ui_element = UIElement {
-            text: "".to_string(),
-            x,
-            y,
-            z: *z_index,
-            width: rect.right - rect.left,
-            height: rect.bottom - rect.top,
-            window_handle: window_handle,
-            control_type: control_type_id.0,
-            element_type: *element_type,
-        };
+    let rect = element.CachedBoundingRectangle().ok()?;
+
+    let x = (rect.right + rect.left) / 2;
+    let y = (rect.bottom + rect.top) / 2;
+    debug!("[convert_element] get element:({},{}) with control_type: {}, element_type: {}, z_index: {}, rect: {:?}, window_handle: {}", 
+        x, y, control_type_id.0, element_type, z_index, rect, window_handle);
+    Some(UIElement {
+        text: "".to_string(),
+        x,
+        y,
+        z: z_index,
+        width: rect.right - rect.left,
+        height: rect.bottom - rect.top,
+        window_handle: window_handle,
+        control_type: control_type_id.0,
+        element_type,
+        parent,
+    })
+}
 
-        let position = (x, y);
+// 同一位置只保留z_index最大的元素, 被合并元素的子元素改为挂在保留的元素下
+fn merge_same_position(elements: Vec<UIElement>) -> Vec<UIElement> {
+    // kept[i]指向合并后保留的元素, 可能需要多次跳转
+    let mut kept: Vec<usize> = Vec::with_capacity(elements.len());
+    let mut position_map: HashMap<(i32, i32), usize> = HashMap::new();
+    for (index, element) in elements.iter().enumerate() {
+        kept.push(index);
+        let position = (element.x, element.y);
         match position_map.get(&position) {
-            Some(old_element) => {
-                if old_element.z < *z_index as i32 {
-                    debug!("[convert_ui_automation] overwrite element at z:{} from window:{} with z_index:{}", 
-                        old_element.z, old_element.window_handle, *z_index);
-                    position_map.insert(position, ui_element);
-                }
+            Some(&old_index) if elements[old_index].z >= element.z => {
+                kept[index] = old_index;
+            }
+            Some(&old_index) => {
+                debug!("[merge_same_position] overwrite element at z:{} from window:{} with z_index:{}", 
+                    elements[old_index].z, elements[old_index].window_handle, element.z);
+                kept[old_index] = index;
+                position_map.insert(position, index);
             }
             None => {
-                position_map.insert(position, ui_element);
+                position_map.insert(position, index);
+            }
+        }
+    }
+    let resolve = |mut index: usize| {
+        while kept[index] != index {
+            index = kept[index];
+        }
+        index
+    };
+
+    let mut new_index = vec![None; elements.len()];
+    let mut count = 0;
+    for index in 0..elements.len() {
+        if resolve(index) == index {
+            new_index[index] = Some(count);
+            count += 1;
+        }
+    }
+    let mut merged = Vec::with_capacity(count);
+    for (index, element) in elements.iter().enumerate() {
+        if new_index[index].is_none() {
+            continue;
+        }
+        // 向上查找第一个未合并到自身的祖先
+        let mut parent = element.parent;
+        while let Some(parent_index) = parent {
+            if resolve(parent_index) != index {
+                break;
             }
+            parent = elements[parent_index].parent;
         }
+        let mut element = element.clone();
+        element.parent = parent.and_then(|parent_index| new_index[resolve(parent_index)]);
+        merged.push(element);
     }
-    position_map.values().cloned().collect()
+    merged
 }
 
 // 定时清理过期key的方法
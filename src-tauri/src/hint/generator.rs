@@ -1,12 +1,12 @@
 use crate::{
-    config::{self, hint::HINT_TYPE_ID_MAP},
+    config::{self, hint::PinConfig},
     element::{UIElement, WINDOWS_UI_ELEMENTS_MAP_STORAGE},
     monitor::{MonitorInfo, MONITORS_STORAGE},
     utils::Rect,
     window::{window::calculate_covered_areas, WindowElement},
 };
 use indexmap::IndexMap;
-use log::{debug, info};
+use log::{debug, error, info};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -23,6 +23,15 @@ pub struct Hint {
     pub scale: f64,
     // hint_type: 0-default, 1-window, 2-pane, 3-tab, 4-button, 5-scrollbar
     pub hint_type: usize,
+    // 来源元素所在窗口句柄, grid hint为0
+    pub window_handle: i64,
+    pub control_type: i32,
+    // 来源元素的全局物理坐标边界, grid hint为None
+    pub bounds: Option<Rect>,
+    // 来源元素在窗口元素列表中的先序位置, 用于兄弟元素排序, grid hint为0
+    pub order: usize,
+    // 最近的带有hint的祖先元素的hint文本, 顶层元素和grid hint为None
+    pub parent: Option<String>,
 }
 
 static HINT_TEXT_LIST_STORAGE: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(Vec::new()));
@@ -33,7 +42,7 @@ pub fn init_hint_text_list_storage() {
     let mut list = Vec::new();
     generate_n_digit_hints(&hint_config.charsets, String::new(), 0, &mut list);
     // 如果有额外字符集，生成n+1位hints
-    if *config::hint::HAS_EXTRA_CHARSET {
+    if config::get_derived_tables().has_extra_charset {
         for &extra_char in hint_config.charset_extra.iter() {
             let mut prefix = String::new();
             prefix.push(extra_char);
@@ -68,6 +77,8 @@ pub struct HintsGenerator {
     uncovered_windows: HashSet<WindowElement>,
     windows_covered_areas: IndexMap<WindowElement, Vec<Rect>>,
     ui_elements: HashMap<WindowElement, Vec<UIElement>>,
+    // 固定hint占用的文本, 自动分配时跳过
+    reserved_texts: HashSet<String>,
 }
 
 impl HintsGenerator {
@@ -75,13 +86,156 @@ impl HintsGenerator {
         let monitors = MONITORS_STORAGE.lock().unwrap().clone();
         let (uncovered_windows, windows_covered_areas) = calculate_covered_areas();
         let ui_elements = WINDOWS_UI_ELEMENTS_MAP_STORAGE.lock().unwrap().clone();
+        let hint_text_list = HINT_TEXT_LIST_STORAGE.lock().unwrap();
+        let reserved_texts = config::get_config()
+            .unwrap()
+            .hint
+            .pins
+            .iter()
+            .filter(|pin| !pin.label.is_empty())
+            .filter(|pin| {
+                let valid = hint_text_list.contains(&pin.label);
+                if !valid {
+                    error!("[HintsGenerator::new] pin label: {} is not a valid hint text", pin.label);
+                }
+                valid
+            })
+            .map(|pin| pin.label.clone())
+            .collect();
 
         Self {
             monitors,
             uncovered_windows,
             windows_covered_areas,
             ui_elements,
+            reserved_texts,
+        }
+    }
+
+    // 取下一个未被固定hint占用的文本, 文本用完时返回None
+    fn next_hint_text(&self, hints_count: &mut i32) -> Option<String> {
+        let hint_text_list = HINT_TEXT_LIST_STORAGE.lock().unwrap();
+        while (*hints_count as usize) < hint_text_list.len() {
+            let text = &hint_text_list[*hints_count as usize];
+            *hints_count += 1;
+            if !self.reserved_texts.contains(text) {
+                return Some(text.clone());
+            }
         }
+        None
+    }
+
+    // 生成配置中的固定hint, 相对于显示器或匹配到的窗口
+    pub fn generate_hints_pins(
+        &self,
+        position_set: &mut HashSet<(i32, i32)>,
+        hints_count: &mut i32,
+    ) -> HashMap<String, Vec<Hint>> {
+        let mut monitor_hints: HashMap<String, Vec<Hint>> = HashMap::new();
+        let config = config::get_config().unwrap();
+        for pin in config.hint.pins.iter() {
+            let (window_handle, global_x, global_y) = match self.locate_pin(pin) {
+                Some(position) => position,
+                None => continue,
+            };
+            let index = match self.monitors.iter().position(|monitor| {
+                global_x >= monitor.x
+                    && global_x < monitor.x + monitor.width
+                    && global_y >= monitor.y
+                    && global_y < monitor.y + monitor.height
+            }) {
+                Some(index) => index,
+                None => {
+                    debug!("[generate_hints_pins] skip pin: {:?} out of monitors", pin);
+                    continue;
+                }
+            };
+            if !position_set.insert((global_x, global_y)) {
+                continue;
+            }
+            let text = if self.reserved_texts.contains(&pin.label) {
+                pin.label.clone()
+            } else {
+                match self.next_hint_text(hints_count) {
+                    Some(text) => text,
+                    None => break,
+                }
+            };
+            let hint_type = if pin.hint_type.is_empty() {
+                &config.hint.grid.hint_type
+            } else {
+                &pin.hint_type
+            };
+            let (hint_type_id, hint_z) = match (
+                config::get_derived_tables().hint_type_id_map.get(hint_type).copied(),
+                config.hint.types.get(hint_type),
+            ) {
+                (Some(hint_type_id), Some(hint_type)) => (hint_type_id, hint_type.z_index),
+                _ => {
+                    error!("[generate_hints_pins] unknown hint type: {} of pin: {:?}", hint_type, pin);
+                    continue;
+                }
+            };
+            let monitor = &self.monitors[index];
+            let hint = Hint {
+                text,
+                x: ((global_x - monitor.x) as f64 / monitor.scale_factor) as i32,
+                y: ((global_y - monitor.y) as f64 / monitor.scale_factor) as i32,
+                z: hint_z,
+                scale: monitor.scale_factor,
+                hint_type: hint_type_id,
+                window_handle,
+                control_type: 0,
+                bounds: None,
+                order: 0,
+                parent: None,
+            };
+            debug!("[generate_hints_pins] add pin hint: {:?} to monitor: {}", hint, monitor.id);
+            monitor_hints
+                .entry(format!("{}{}", OVERLAY_WINDOW_PREFIX, index))
+                .or_default()
+                .push(hint);
+        }
+        monitor_hints
+    }
+
+    // 计算固定hint的全局物理坐标, 窗口被遮挡或不存在时返回None
+    fn locate_pin(&self, pin: &PinConfig) -> Option<(i64, i32, i32)> {
+        if pin.window_class.is_empty() && pin.window_title.is_empty() {
+            let monitor = self.monitors.get(pin.monitor)?;
+            return Some((
+                0,
+                monitor.x + (pin.x as f64 * monitor.scale_factor) as i32,
+                monitor.y + (pin.y as f64 * monitor.scale_factor) as i32,
+            ));
+        }
+        // 多个窗口匹配时使用最上层的窗口
+        let window = self
+            .uncovered_windows
+            .iter()
+            .chain(self.windows_covered_areas.keys())
+            .filter(|window| pin.matches_window(&window.class_name, &window.title))
+            .max_by_key(|window| window.z_index)?;
+        // 按窗口左上角所在显示器的缩放比例换算逻辑坐标
+        let scale_factor = self
+            .monitors
+            .iter()
+            .find(|monitor| {
+                window.x >= monitor.x
+                    && window.x < monitor.x + monitor.width
+                    && window.y >= monitor.y
+                    && window.y < monitor.y + monitor.height
+            })
+            .map_or(1.0, |monitor| monitor.scale_factor);
+        let x = window.x + (pin.x as f64 * scale_factor) as i32;
+        let y = window.y + (pin.y as f64 * scale_factor) as i32;
+        if let Some(areas) = self.windows_covered_areas.get(window) {
+            if areas.iter().any(|area| area.contains_point(x, y)) {
+                debug!("[locate_pin] skip pin: {:?} covered in window: {}", pin, window.title);
+                return None;
+            }
+        }
+        Some((window.window_handle, x, y))
     }
 
     pub fn generate_hints_grid(
@@ -95,25 +249,39 @@ impl HintsGenerator {
         let show_at_row = config.hint.grid.show_at_rows;
         let show_at_column = config.hint.grid.show_at_columns;
         let hint_type = config.hint.grid.hint_type;
-        let hint_z = config.hint.types.get(&hint_type).unwrap().z_index;
+        let (hint_type_id, hint_z) = match (
+            config::get_derived_tables().hint_type_id_map.get(&hint_type).copied(),
+            config.hint.types.get(&hint_type),
+        ) {
+            (Some(hint_type_id), Some(hint_type)) => (hint_type_id, hint_type.z_index),
+            _ => {
+                error!("[generate_hints_grid] unknown grid hint type: {}", hint_type);
+                return monitor_hints;
+            }
+        };
         for (index, monitor) in self.monitors.iter().enumerate() {
             let mut hints = Vec::new();
             for row in show_at_row.iter() {
                 for column in show_at_column.iter() {
                     // 检查是否超出范围
-                    if *hints_count >= HINT_TEXT_LIST_STORAGE.lock().unwrap().len() as i32 {
-                        return monitor_hints;
-                    }
+                    let text = match self.next_hint_text(hints_count) {
+                        Some(text) => text,
+                        None => return monitor_hints,
+                    };
                     let hint = Hint {
-                        text: HINT_TEXT_LIST_STORAGE.lock().unwrap()[*hints_count as usize].clone(),
+                        text,
                         x: (((*column as f64) - 0.5) * monitor.width as f64 / grid_column as f64 / monitor.scale_factor) as i32,
                         y: (((*row as f64) - 0.5) * monitor.height as f64 / grid_row as f64 / monitor.scale_factor) as i32,
                         z: hint_z,
                         scale: monitor.scale_factor,
-                        hint_type: HINT_TYPE_ID_MAP.get(&hint_type).unwrap().clone(),
+                        hint_type: hint_type_id,
+                        window_handle: 0,
+                        control_type: 0,
+                        bounds: None,
+                        order: 0,
+                        parent: None,
                     };
                     hints.push(hint);
-                    *hints_count += 1;
                 }
             }
             monitor_hints.insert(format!("{}{}", OVERLAY_WINDOW_PREFIX, index), hints);
@@ -176,7 +344,15 @@ impl HintsGenerator {
         position_set: &mut HashSet<(i32, i32)>,
         hints_count: &mut i32,
     ) {
-        for hint in ui_elements {
+        // 元素下标对应的hint文本及其在monitor_hints中的位置
+        let mut element_hints: HashMap<usize, (String, String, usize)> = HashMap::new();
+        // 按当前配置(可能带有[[app]]覆盖)确定hint类型, 不在hint.types中的控件类型不显示
+        let tables = config::get_derived_tables();
+        for (element_index, hint) in ui_elements.iter().enumerate() {
+            let (hint_type, hint_z) = match tables.hint_control_types_id_z_map.get(&hint.control_type) {
+                Some(type_z) => *type_z,
+                None => continue,
+            };
             if !position_set.insert((hint.x, hint.y)) {
                 continue;
             }
@@ -204,40 +380,85 @@ impl HintsGenerator {
 
                 if !is_covered {
                     // 检查是否超出范围
-                    if *hints_count >= HINT_TEXT_LIST_STORAGE.lock().unwrap().len() as i32 {
-                        debug!("[generator] skip hint:{}:({},{}) due to hint text is out of use",
-                            hint.text, hint.x, hint.y);
-                        return;
-                    }
+                    let hint_letter = match self.next_hint_text(hints_count) {
+                        Some(hint_letter) => hint_letter,
+                        None => {
+                            debug!("[generator] skip hint:{}:({},{}) due to hint text is out of use",
+                                hint.text, hint.x, hint.y);
+                            self.link_parent_hints(monitor_hints, ui_elements, &element_hints);
+                            return;
+                        }
+                    };
 
+                    let bounds = Rect::new(
+                        hint.x - hint.width / 2,
+                        hint.y - hint.height / 2,
+                        hint.width,
+                        hint.height,
+                    );
                     // 转换为相对于显示器的坐标
                     let mut hint = hint.clone();
                     hint.x -= monitor.x;
                     hint.y -= monitor.y;
                     hint.x = (hint.x as f64 / monitor.scale_factor) as i32;
                     hint.y = (hint.y as f64 / monitor.scale_factor) as i32;
-                    let hint_letter =
-                        HINT_TEXT_LIST_STORAGE.lock().unwrap()[*hints_count as usize].clone();
-                    let hint_type = hint.element_type;
                     debug!("[generator] add window:{}, NO.{}hint:{},type:{},ctrl_type:{},pos:({},{}):{} to monitor:{}",
                         window_element.title, *hints_count, hint_letter, hint_type, hint.control_type, hint.x, hint.y, hint.text, monitor.id);
                     let hint = Hint {
                         text: hint_letter,
                         x: hint.x,
                         y: hint.y,
-                        z: hint.z,
+                        z: hint_z,
                         scale: monitor.scale_factor,
                         hint_type,
+                        window_handle: hint.window_handle,
+                        control_type: hint.control_type,
+                        bounds: Some(bounds),
+                        order: element_index,
+                        parent: None,
                     };
                     let window_label = format!("{}{}", OVERLAY_WINDOW_PREFIX, index);
-                    monitor_hints
-                        .entry(window_label.clone())
-                        .or_default()
-                        .push(hint);
-                    *hints_count += 1;
+                    let monitor_hint_list = monitor_hints.entry(window_label.clone()).or_default();
+                    element_hints.insert(
+                        element_index,
+                        (hint.text.clone(), window_label, monitor_hint_list.len()),
+                    );
+                    monitor_hint_list.push(hint);
                 }
                 break;
             }
         }
+        self.link_parent_hints(monitor_hints, ui_elements, &element_hints);
+    }
+
+    // 为每个hint找到最近的带有hint的祖先, 被遮挡或重叠而没有hint的祖先会被跳过
+    fn link_parent_hints(
+        &self,
+        monitor_hints: &mut HashMap<String, Vec<Hint>>,
+        ui_elements: &Vec<UIElement>,
+        element_hints: &HashMap<usize, (String, String, usize)>,
+    ) {
+        for (element_index, (_, window_label, position)) in element_hints {
+            let mut parent = ui_elements[*element_index].parent;
+            // 限制查找步数, 防止合并重叠元素后出现环
+            let mut steps = 0;
+            let parent_text = loop {
+                let parent_index = match parent {
+                    Some(parent_index) if steps < ui_elements.len() => parent_index,
+                    _ => break None,
+                };
+                if let Some((text, _, _)) = element_hints.get(&parent_index) {
+                    break Some(text.clone());
+                }
+                parent = ui_elements[parent_index].parent;
+                steps += 1;
+            };
+            if let Some(hint) = monitor_hints
+                .get_mut(window_label)
+                .and_then(|hints| hints.get_mut(*position))
+            {
+                hint.parent = parent_text;
+            }
+        }
     }
 }
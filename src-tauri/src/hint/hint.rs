@@ -1,7 +1,12 @@
+use crate::config::hint::{PinConfig, SubGridConfig};
 use crate::hint::generator::Hint;
-use crate::hint::overlay::get_overlay_monitor_id;
+use crate::hint::overlay::{get_overlay_monitor_id, get_overlay_window_label};
+use crate::monitor::MONITORS_STORAGE;
+use crate::utils::rect::Rect;
+use crate::window::window::get_foreground_window;
 use log::{debug, error, info};
 use once_cell::sync::Lazy;
+use serde::Serialize;
 use std::collections::HashMap;
 use std::sync::Mutex;
 
@@ -9,6 +14,26 @@ use std::sync::Mutex;
 pub static ACTIVE_HINTS_STORAGE: Lazy<Mutex<HashMap<String, HashMap<String, Hint>>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+// 当前打开的细分网格
+pub static SUB_GRID_STORAGE: Lazy<Mutex<Option<SubGrid>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubGrid {
+    pub hint_text: String,
+    pub window_label: String,
+    // 网格区域, 相对于overlay窗口的逻辑坐标, 含偏移量
+    pub bounds: Rect,
+    pub cells: Vec<SubGridCell>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubGridCell {
+    pub text: String,
+    pub x: i32,
+    pub y: i32,
+}
+
 // 存储 hints 的移动偏移量
 pub static HINTS_OFFSET_STORAGE: Lazy<Mutex<(i32, i32)>> = Lazy::new(|| Mutex::new((0, 0)));
 
@@ -40,6 +65,276 @@ pub fn get_hint_position_by_text(hint_text: &str) -> Option<(usize, i32, i32)> {
     None
 }
 
+// 根据完整的 hint 文本获取 hint 本身, 坐标不含偏移量
+pub fn get_hint_by_text(hint_text: &str) -> Option<Hint> {
+    if let Ok(hints_map) = ACTIVE_HINTS_STORAGE.lock() {
+        for hints in hints_map.values() {
+            if let Some(hint) = hints.get(hint_text) {
+                return Some(hint.clone());
+            }
+        }
+    } else {
+        error!("[get_hint_by_text] failed to get ACTIVE_HINTS_STORAGE lock");
+    }
+    None
+}
+
+// 在方向(dx, dy)上查找离当前hint最近的元素hint
+// 优先选择与当前元素在垂直于方向的轴上有重叠的元素(光束), 其次选择45度锥形范围内的元素
+pub fn find_hint_in_direction(hint_text: &str, direction: (i32, i32)) -> Option<String> {
+    let hints_map = match ACTIVE_HINTS_STORAGE.lock() {
+        Ok(hints_map) => hints_map,
+        Err(_) => {
+            error!("[find_hint_in_direction] failed to get ACTIVE_HINTS_STORAGE lock");
+            return None;
+        }
+    };
+    let current = hints_map
+        .values()
+        .find_map(|hints| hints.get(hint_text))
+        .and_then(|hint| hint.bounds.clone())?;
+    let (dir_x, dir_y) = (direction.0.signum() as f64, direction.1.signum() as f64);
+    let length = (dir_x * dir_x + dir_y * dir_y).sqrt();
+    if length == 0.0 {
+        return None;
+    }
+    let (dir_x, dir_y) = (dir_x / length, dir_y / length);
+    let center = |rect: &Rect| {
+        (
+            rect.x as f64 + rect.width as f64 / 2.0,
+            rect.y as f64 + rect.height as f64 / 2.0,
+        )
+    };
+    let (cx, cy) = center(&current);
+    let mut best: Option<(f64, String)> = None;
+    for hint in hints_map.values().flat_map(|hints| hints.values()) {
+        let bounds = match &hint.bounds {
+            Some(bounds) if hint.text != hint_text => bounds,
+            _ => continue,
+        };
+        let (px, py) = center(bounds);
+        let (vx, vy) = (px - cx, py - cy);
+        let along = vx * dir_x + vy * dir_y;
+        if along <= 0.0 {
+            continue;
+        }
+        let across = (vx * dir_y - vy * dir_x).abs();
+        // 水平移动时比较纵向重叠, 垂直移动时比较横向重叠
+        let in_beam = if dir_y == 0.0 {
+            bounds.y < current.y + current.height && current.y < bounds.y + bounds.height
+        } else if dir_x == 0.0 {
+            bounds.x < current.x + current.width && current.x < bounds.x + bounds.width
+        } else {
+            false
+        };
+        if !in_beam && across > along {
+            continue;
+        }
+        let score = if in_beam { along } else { along + across * 2.0 };
+        if best.as_ref().map_or(true, |(best_score, _)| score < *best_score) {
+            best = Some((score, hint.text.clone()));
+        }
+    }
+    debug!("[find_hint_in_direction] from: {}, direction: {:?}, found: {:?}", hint_text, direction, best);
+    best.map(|(_, text)| text)
+}
+
+// 元素树中相对于当前hint的位置
+#[derive(Debug, Clone, Copy)]
+pub enum HintRelation {
+    Parent,
+    FirstChild,
+    NextSibling,
+    PrevSibling,
+}
+
+// 按元素树关系查找hint, 只在同一窗口的元素hint之间查找
+pub fn find_related_hint(hint_text: &str, relation: HintRelation) -> Option<String> {
+    let hints_map = match ACTIVE_HINTS_STORAGE.lock() {
+        Ok(hints_map) => hints_map,
+        Err(_) => {
+            error!("[find_related_hint] failed to get ACTIVE_HINTS_STORAGE lock");
+            return None;
+        }
+    };
+    let current = hints_map
+        .values()
+        .find_map(|hints| hints.get(hint_text))
+        .filter(|hint| hint.bounds.is_some())?;
+    let same_window = || {
+        hints_map
+            .values()
+            .flat_map(|hints| hints.values())
+            .filter(|hint| hint.bounds.is_some() && hint.window_handle == current.window_handle)
+    };
+    let found = match relation {
+        HintRelation::Parent => current.parent.clone(),
+        HintRelation::FirstChild => same_window()
+            .filter(|hint| hint.parent.as_deref() == Some(hint_text))
+            .min_by_key(|hint| hint.order)
+            .map(|hint| hint.text.clone()),
+        HintRelation::NextSibling => same_window()
+            .filter(|hint| hint.parent == current.parent && hint.order > current.order)
+            .min_by_key(|hint| hint.order)
+            .map(|hint| hint.text.clone()),
+        HintRelation::PrevSibling => same_window()
+            .filter(|hint| hint.parent == current.parent && hint.order < current.order)
+            .max_by_key(|hint| hint.order)
+            .map(|hint| hint.text.clone()),
+    };
+    debug!("[find_related_hint] from: {}, relation: {:?}, found: {:?}", hint_text, relation, found);
+    found
+}
+
+// 获取hint来源元素的边界, 转换为相对于所在overlay窗口的逻辑坐标
+pub fn get_hint_bounds_by_text(hint_text: &str) -> Option<(String, Rect)> {
+    let (window_label, bounds) = {
+        let hints_map = match ACTIVE_HINTS_STORAGE.lock() {
+            Ok(hints_map) => hints_map,
+            Err(_) => {
+                error!("[get_hint_bounds_by_text] failed to get ACTIVE_HINTS_STORAGE lock");
+                return None;
+            }
+        };
+        hints_map.iter().find_map(|(window_label, hints)| {
+            let bounds = hints.get(hint_text)?.bounds.clone()?;
+            Some((window_label.clone(), bounds))
+        })?
+    };
+    let monitor = MONITORS_STORAGE
+        .lock()
+        .ok()?
+        .get(get_overlay_monitor_id(&window_label))?
+        .clone();
+    let scale = monitor.scale_factor;
+    Some((
+        window_label,
+        Rect::new(
+            ((bounds.x - monitor.x) as f64 / scale) as i32,
+            ((bounds.y - monitor.y) as f64 / scale) as i32,
+            (bounds.width as f64 / scale) as i32,
+            (bounds.height as f64 / scale) as i32,
+        ),
+    ))
+}
+
+// 以hint当前的目标点为中心生成细分网格并保存
+pub fn create_sub_grid(hint_text: &str, config: &SubGridConfig) -> Option<SubGrid> {
+    let (monitor_id, x, y) = get_hint_position_by_text(hint_text)?;
+    let (width, height) = match get_hint_bounds_by_text(hint_text) {
+        Some((_, bounds)) if config.use_bounds => (bounds.width, bounds.height),
+        _ => (config.radius * 2, config.radius * 2),
+    };
+    if config.rows <= 0 || config.columns <= 0 || width <= 0 || height <= 0 {
+        error!("[create_sub_grid] invalid sub grid: {}x{} with size: {}x{}", config.rows, config.columns, width, height);
+        return None;
+    }
+    let bounds = Rect::new(x - width / 2, y - height / 2, width, height);
+    let cell_width = width as f64 / config.columns as f64;
+    let cell_height = height as f64 / config.rows as f64;
+    let mut cells = Vec::new();
+    let mut chars = config.chars.iter();
+    'rows: for row in 0..config.rows {
+        for column in 0..config.columns {
+            // 标签不够时剩余的格子不显示
+            let c = match chars.next() {
+                Some(c) => c,
+                None => break 'rows,
+            };
+            cells.push(SubGridCell {
+                text: c.to_string(),
+                x: bounds.x + ((column as f64 + 0.5) * cell_width) as i32,
+                y: bounds.y + ((row as f64 + 0.5) * cell_height) as i32,
+            });
+        }
+    }
+    let sub_grid = SubGrid {
+        hint_text: hint_text.to_string(),
+        window_label: get_overlay_window_label(monitor_id),
+        bounds,
+        cells,
+    };
+    debug!("[create_sub_grid] create sub grid: {:?}", sub_grid);
+    if let Ok(mut storage) = SUB_GRID_STORAGE.lock() {
+        *storage = Some(sub_grid.clone());
+    } else {
+        error!("[create_sub_grid] failed to get SUB_GRID_STORAGE lock");
+    }
+    Some(sub_grid)
+}
+
+// 细分网格是否为指定hint打开
+pub fn is_sub_grid_open(hint_text: &str) -> bool {
+    SUB_GRID_STORAGE
+        .lock()
+        .map(|storage| storage.as_ref().map_or(false, |sub_grid| sub_grid.hint_text == hint_text))
+        .unwrap_or(false)
+}
+
+// 查找指定hint的细分网格中标签对应的格子
+pub fn get_sub_grid_cell(hint_text: &str, label: &str) -> Option<SubGridCell> {
+    let storage = SUB_GRID_STORAGE.lock().ok()?;
+    let sub_grid = storage.as_ref().filter(|sub_grid| sub_grid.hint_text == hint_text)?;
+    sub_grid.cells.iter().find(|cell| cell.text == label).cloned()
+}
+
+pub fn clear_sub_grid() {
+    if let Ok(mut storage) = SUB_GRID_STORAGE.lock() {
+        *storage = None;
+    } else {
+        error!("[clear_sub_grid] failed to get SUB_GRID_STORAGE lock");
+    }
+}
+
+// 将hint的目标点移动到(x, y), 坐标含偏移量, 之后的动作都作用于新的目标点
+pub fn refine_hint_position(hint_text: &str, x: i32, y: i32) -> Option<(i32, i32)> {
+    let offset = HINTS_OFFSET_STORAGE.lock().map(|offset| *offset).unwrap_or((0, 0));
+    let mut hints_map = match ACTIVE_HINTS_STORAGE.lock() {
+        Ok(hints_map) => hints_map,
+        Err(_) => {
+            error!("[refine_hint_position] failed to get ACTIVE_HINTS_STORAGE lock");
+            return None;
+        }
+    };
+    let hint = hints_map.values_mut().find_map(|hints| hints.get_mut(hint_text))?;
+    debug!("[refine_hint_position] refine hint: {} from ({},{}) to ({},{})", hint_text, hint.x, hint.y, x - offset.0, y - offset.1);
+    hint.x = x - offset.0;
+    hint.y = y - offset.1;
+    Some((hint.x, hint.y))
+}
+
+// 为全局物理坐标创建固定hint, 相对于前台窗口, 没有前台窗口时相对于所在显示器
+pub fn create_pin_at(x: i32, y: i32) -> Option<PinConfig> {
+    let monitors = MONITORS_STORAGE.lock().ok()?.clone();
+    let monitor_at = |px: i32, py: i32| {
+        monitors.iter().position(|monitor| {
+            px >= monitor.x
+                && px < monitor.x + monitor.width
+                && py >= monitor.y
+                && py < monitor.y + monitor.height
+        })
+    };
+    if let Some(window) = get_foreground_window() {
+        // 与生成时一致, 按窗口左上角所在显示器的缩放比例换算
+        let scale_factor = monitor_at(window.rect.x, window.rect.y)
+            .map_or(1.0, |index| monitors[index].scale_factor);
+        return Some(PinConfig {
+            x: ((x - window.rect.x) as f64 / scale_factor) as i32,
+            y: ((y - window.rect.y) as f64 / scale_factor) as i32,
+            window_class: window.class_name,
+            ..Default::default()
+        });
+    }
+    let index = monitor_at(x, y)?;
+    let monitor = &monitors[index];
+    Some(PinConfig {
+        x: ((x - monitor.x) as f64 / monitor.scale_factor) as i32,
+        y: ((y - monitor.y) as f64 / monitor.scale_factor) as i32,
+        monitor: index,
+        ..Default::default()
+    })
+}
+
 // 更新 hints 的偏移量
 pub fn update_hints_offset(dx: i32, dy: i32) {
     if let Ok(mut offset) = HINTS_OFFSET_STORAGE.lock() {
@@ -92,4 +387,5 @@ pub fn clear_hints() {
         error!("[clear_hints] failed to get ACTIVE_HINTS_STORAGE lock");
     }
     reset_hints_offset();
+    clear_sub_grid();
 }
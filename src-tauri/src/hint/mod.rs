@@ -10,8 +10,11 @@ use overlay::ensure_all_overlays_topmost;
 use serde_json::json;
 use std::collections::HashSet;
 use hint::clear_hints;
+use hint::get_hint_bounds_by_text;
+use hint::SubGrid;
 use hint::save_hints;
 use hint::update_hints_offset;
+use crate::window::window::get_foreground_window;
 use tauri::Emitter;
 use tauri::{Manager, WebviewWindow};
 
@@ -23,22 +26,44 @@ pub async fn show_hints(window: WebviewWindow) {
     // 清空之前的 hints 数据
     clear_hints();
 
+    // 按前台窗口使用[[app]]中的覆盖, 配置有变化时重建派生表和hint文本列表
+    if config::select_app_override(get_foreground_window().as_ref()) {
+        if let Some(config) = config::get_config() {
+            config::reload_derived_tables(&config);
+            init_hint_text_list_storage();
+        }
+    }
+
     // 发送hints到对应的overlay窗口
     let app_handle = window.app_handle();
     let hints_generator = HintsGenerator::new();
     let mut position_set = HashSet::new();
     let mut hints_count = 0;
 
+    // hint字符在当前键盘布局上的标签, overlay按标签显示
+    let hint_config = config::get_config().unwrap().hint;
+    let labels = input::key::get_layout_labels(
+        hint_config
+            .charsets
+            .iter()
+            .flatten()
+            .chain(hint_config.charset_extra.iter())
+            .chain(hint_config.sub_grid.chars.iter())
+            .copied(),
+    );
+
     // 设置键盘状态为监听
     input::keyboard::switch_keyboard_ctrl(true, Some(&app_handle));
     debug!("[show_hints] switch keyboard ctrl to true");
     let monitor_grid_hints = hints_generator.generate_hints_grid(&mut hints_count);
+    // 固定hint先于元素生成, 与元素位置重叠时保留固定hint
+    let monitor_pin_hints = hints_generator.generate_hints_pins(&mut position_set, &mut hints_count);
     let mut monitor_hints = hints_generator.generate_hints_batch1(&mut position_set, &mut hints_count);
-    for (window_label, grid_hints) in &monitor_grid_hints {
+    for (window_label, extra_hints) in monitor_grid_hints.iter().chain(monitor_pin_hints.iter()) {
         if let Some(hints) = monitor_hints.get_mut(window_label) {
-            hints.extend(grid_hints.clone());
+            hints.extend(extra_hints.clone());
         } else {
-            monitor_hints.insert(window_label.clone(), grid_hints.clone());
+            monitor_hints.insert(window_label.clone(), extra_hints.clone());
         }
     }
     for (window_label, hints) in &monitor_hints {
@@ -47,7 +72,8 @@ pub async fn show_hints(window: WebviewWindow) {
                 "show-hints",
                 json!({
                     "windowLabel": window_label,
-                    "hints": hints
+                    "hints": hints,
+                    "labels": labels
                 }),
             ) {
                 error!("[show_hints] show-hints failed: {}", e);
@@ -95,6 +121,14 @@ pub async fn hide_hints(app_handle: tauri::AppHandle) {
     debug!("[hide_hints] switch keyboard ctrl to false");
     // 清空 hints 数据
     clear_hints();
+
+    // 会话结束后恢复不含[[app]]覆盖的配置
+    if config::clear_app_override() {
+        if let Some(config) = config::get_base_config() {
+            config::reload_derived_tables(&config);
+            init_hint_text_list_storage();
+        }
+    }
 }
 
 pub async fn move_hints(app_handle: tauri::AppHandle, move_direction: (i32, i32)) {
@@ -115,3 +149,83 @@ pub async fn filter_hints(app_handle: tauri::AppHandle, letters: String) {
         error!("[filter_hints] filter-hints failed: {}", e);
     }
 }
+
+// 恢复所有被过滤的hints, 用于重新选择hint
+pub async fn reset_hints(app_handle: tauri::AppHandle) {
+    let window = app_handle.get_webview_window("main").unwrap();
+    if let Err(e) = window.emit("reset-hints", ()) {
+        error!("[reset_hints] reset-hints failed: {}", e);
+    }
+}
+
+// 只显示并突出指定的hint
+pub async fn select_hint(app_handle: tauri::AppHandle, hint_text: String) {
+    let window = app_handle.get_webview_window("main").unwrap();
+    if let Err(e) = window.emit("select-hint", hint_text) {
+        error!("[select_hint] select-hint failed: {}", e);
+    }
+}
+
+// 在hint所在的overlay窗口上框出来源元素的边界, 其余overlay窗口清除边框
+pub async fn highlight_hint(app_handle: tauri::AppHandle, hint_text: String) {
+    let json = match get_hint_bounds_by_text(&hint_text) {
+        Some((window_label, bounds)) => json!({
+            "windowLabel": window_label,
+            "bounds": bounds
+        }),
+        None => json!({
+            "windowLabel": null,
+            "bounds": null
+        }),
+    };
+    let window = app_handle.get_webview_window("main").unwrap();
+    if let Err(e) = window.emit("highlight-bounds", json) {
+        error!("[highlight_hint] highlight-bounds failed: {}", e);
+    }
+}
+
+// 在hint所在的overlay窗口上显示细分网格
+pub async fn show_sub_grid(app_handle: tauri::AppHandle, sub_grid: SubGrid) {
+    let window = app_handle.get_webview_window("main").unwrap();
+    if let Err(e) = window.emit("show-sub-grid", sub_grid) {
+        error!("[show_sub_grid] show-sub-grid failed: {}", e);
+    }
+}
+
+pub async fn hide_sub_grid(app_handle: tauri::AppHandle) {
+    let window = app_handle.get_webview_window("main").unwrap();
+    if let Err(e) = window.emit("hide-sub-grid", ()) {
+        error!("[hide_sub_grid] hide-sub-grid failed: {}", e);
+    }
+}
+
+// 将hint标签移动到微调后的目标点, 坐标不含偏移量
+pub async fn refine_hint(app_handle: tauri::AppHandle, hint_text: String, x: i32, y: i32) {
+    let window = app_handle.get_webview_window("main").unwrap();
+    let json = json!({
+        "text": hint_text,
+        "x": x,
+        "y": y
+    });
+    if let Err(e) = window.emit("refine-hint", json) {
+        error!("[refine_hint] refine-hint failed: {}", e);
+    }
+}
+
+pub async fn show_mode(app_handle: tauri::AppHandle, mode: String, indicator: String) {
+    let window = app_handle.get_webview_window("main").unwrap();
+    let json = json!({
+        "mode": mode,
+        "indicator": indicator
+    });
+    if let Err(e) = window.emit("show-mode", json) {
+        error!("[show_mode] show-mode failed: {}", e);
+    }
+}
+
+pub async fn hide_mode(app_handle: tauri::AppHandle) {
+    let window = app_handle.get_webview_window("main").unwrap();
+    if let Err(e) = window.emit("hide-mode", ()) {
+        error!("[hide_mode] hide-mode failed: {}", e);
+    }
+}
@@ -2,10 +2,23 @@ use log::{debug, error, info};
 
 use crate::{
     config,
-    hint::{filter_hints, hide_hints, move_hints, hint::get_hint_position_by_text},
+    hint::{
+        filter_hints, hide_hints, hide_mode, hint::get_hint_by_text, hint::get_hint_position_by_text, move_hints,
+        hint::find_hint_in_direction, hint::find_related_hint, hint::HintRelation, highlight_hint,
+        hint::{clear_sub_grid, create_pin_at, create_sub_grid, get_sub_grid_cell, is_sub_grid_open, refine_hint_position},
+        hide_sub_grid, init_hint_text_list_storage, overlay::ensure_all_overlays_topmost, refine_hint,
+        reset_hints, select_hint, show_mode, show_sub_grid,
+    },
 };
 
-use super::{keyboard::KeyboardState, mouse};
+use super::{
+    keyboard::KeyboardState,
+    inject,
+    marks::{self, PendingMark},
+    motion::{self, MotionKind},
+    mouse, pipeline, prompt, queue, scroll,
+    repeat::{self, RepeatableAction},
+};
 
 pub struct Executor<'a> {
     app_handle: &'a tauri::AppHandle,
@@ -27,6 +40,9 @@ impl<'a> Executor<'a> {
     }
 
     pub fn execute(&mut self, cmd: Option<&str>) -> bool {
+        if let Some(handled) = cmd.and_then(|cmd| self.execute_parameterized(cmd)) {
+            return handled;
+        }
         match cmd {
             Some(config::keybinding::MOVE_TO_HINT_CMD) => self.execute_move_to_hint(false),
             Some(config::keybinding::MOVE_TO_HINT_EXIT_CMD) => self.execute_move_to_hint(true),
@@ -34,6 +50,13 @@ impl<'a> Executor<'a> {
             Some(config::keybinding::LEFT_CLICK_EXIT_CMD) => self.execute_quick_click(true),
             Some(config::keybinding::HOLD_AT_HINT_CMD) => self.execute_hold_at_hint(),
             Some(config::keybinding::EXIT_CMD) => self.execute_exit(),
+            Some(config::keybinding::REPEAT_CMD) => self.execute_repeat(),
+            Some(config::keybinding::BUTTON_UP_CMD) => self.execute_button_up(),
+            Some(config::keybinding::SET_MARK_CMD) => self.execute_set_mark(false),
+            Some(config::keybinding::JUMP_MARK_CMD) => self.execute_jump_mark(),
+            Some(config::keybinding::JUMP_BACK_CMD) => self.execute_jump(true),
+            Some(config::keybinding::JUMP_FORWARD_CMD) => self.execute_jump(false),
+            Some(config::keybinding::CAPTURE_PIN_CMD) => self.execute_capture_pin(),
             Some(config::keybinding::TRANSLATE_UP_CMD) => {
                 self.execute_move_hints(&self.config.keybinding.global.translate)
             }
@@ -51,6 +74,9 @@ impl<'a> Executor<'a> {
     }
 
     pub fn execute_at_hint(&mut self, cmd: Option<&str>) -> bool {
+        if let Some(handled) = cmd.and_then(|cmd| self.execute_parameterized(cmd)) {
+            return handled;
+        }
         match cmd {
             Some(config::keybinding::HOLD_AT_HINT_CMD) => {
                 // 拦截hint_key, 保持按住final_hint_key, 不传播按键
@@ -65,6 +91,22 @@ impl<'a> Executor<'a> {
             Some(config::keybinding::MIDDLE_CLICK_CMD) => self.execute_middle_click(false),
             Some(config::keybinding::MIDDLE_CLICK_EXIT_CMD) => self.execute_middle_click(true),
             Some(config::keybinding::EXIT_CMD) => self.execute_exit(),
+            Some(config::keybinding::REPEAT_CMD) => self.execute_repeat(),
+            Some(config::keybinding::BUTTON_DOWN_CMD) => self.execute_button_down(),
+            Some(config::keybinding::BUTTON_UP_CMD) => self.execute_button_up(),
+            Some(config::keybinding::NAVIGATE_UP_CMD)
+            | Some(config::keybinding::NAVIGATE_DOWN_CMD)
+            | Some(config::keybinding::NAVIGATE_LEFT_CMD)
+            | Some(config::keybinding::NAVIGATE_RIGHT_CMD) => {
+                self.execute_navigate(&self.config.keybinding.at_hint.navigate)
+            }
+            Some(config::keybinding::SELECT_PARENT_CMD) => self.execute_select_related(HintRelation::Parent),
+            Some(config::keybinding::SELECT_CHILD_CMD) => self.execute_select_related(HintRelation::FirstChild),
+            Some(config::keybinding::SELECT_NEXT_CMD) => self.execute_select_related(HintRelation::NextSibling),
+            Some(config::keybinding::SELECT_PREV_CMD) => self.execute_select_related(HintRelation::PrevSibling),
+            Some(config::keybinding::SUB_GRID_CMD) => self.execute_sub_grid(),
+            Some(config::keybinding::SET_MARK_CMD) => self.execute_set_mark(true),
+            Some(config::keybinding::PROMPT_TEXT_CMD) => self.execute_prompt_text(),
             Some(config::keybinding::TRANSLATE_UP_CMD) => {
                 self.execute_move_hints(&self.config.keybinding.at_hint.translate)
             }
@@ -105,13 +147,302 @@ impl<'a> Executor<'a> {
         }
     }
 
+    pub fn execute_in_mode(&mut self, cmd: Option<&str>) -> bool {
+        let config = self.config;
+        let mode = match self
+            .state
+            .active_mode
+            .as_ref()
+            .and_then(|mode_name| config.keybinding.modes.get(mode_name))
+        {
+            Some(mode) => mode,
+            None => return false,
+        };
+        if let Some(handled) = cmd.and_then(|cmd| self.execute_parameterized(cmd)) {
+            return handled;
+        }
+        match cmd {
+            Some(config::keybinding::LEAVE_MODE_CMD) => self.execute_leave_mode(),
+            Some(config::keybinding::TRANSLATE_UP_CMD)
+            | Some(config::keybinding::TRANSLATE_DOWN_CMD)
+            | Some(config::keybinding::TRANSLATE_LEFT_CMD)
+            | Some(config::keybinding::TRANSLATE_RIGHT_CMD) => {
+                self.execute_move_cursor(&mode.get_direction_keybindings(
+                    config::keybinding::TRANSLATE_UP_CMD,
+                    config::keybinding::TRANSLATE_DOWN_CMD,
+                    config::keybinding::TRANSLATE_LEFT_CMD,
+                    config::keybinding::TRANSLATE_RIGHT_CMD,
+                ))
+            }
+            Some(config::keybinding::SCROLL_UP_CMD)
+            | Some(config::keybinding::SCROLL_DOWN_CMD)
+            | Some(config::keybinding::SCROLL_LEFT_CMD)
+            | Some(config::keybinding::SCROLL_RIGHT_CMD) => {
+                self.execute_scroll_hints(&mode.get_direction_keybindings(
+                    config::keybinding::SCROLL_UP_CMD,
+                    config::keybinding::SCROLL_DOWN_CMD,
+                    config::keybinding::SCROLL_LEFT_CMD,
+                    config::keybinding::SCROLL_RIGHT_CMD,
+                ))
+            }
+            Some(config::keybinding::DRAG_UP_CMD)
+            | Some(config::keybinding::DRAG_DOWN_CMD)
+            | Some(config::keybinding::DRAG_LEFT_CMD)
+            | Some(config::keybinding::DRAG_RIGHT_CMD) => {
+                self.execute_drag_hints(&mode.get_direction_keybindings(
+                    config::keybinding::DRAG_UP_CMD,
+                    config::keybinding::DRAG_DOWN_CMD,
+                    config::keybinding::DRAG_LEFT_CMD,
+                    config::keybinding::DRAG_RIGHT_CMD,
+                ))
+            }
+            // 其余命令与at_hint状态下一致, 没有选中hint时作用于光标位置
+            _ => self.execute_at_hint(cmd),
+        }
+    }
+
+    // 处理带参数的命令, 如 enter_mode:<name>, pipeline:<name>
+    fn execute_parameterized(&mut self, cmd: &str) -> Option<bool> {
+        if let Some(mode_name) = config::keybinding::parse_enter_mode_cmd(cmd) {
+            return Some(self.execute_enter_mode(mode_name));
+        }
+        if let Some(pipeline_name) = config::keybinding::parse_pipeline_cmd(cmd) {
+            return Some(self.execute_pipeline(pipeline_name));
+        }
+        if let Some(snippet) = config::keybinding::parse_type_text_cmd(cmd) {
+            return Some(self.execute_type_text(snippet));
+        }
+        if let Some(combo) = config::keybinding::parse_click_send_keys_cmd(cmd) {
+            return Some(self.execute_send_keys(combo, true));
+        }
+        if let Some(combo) = config::keybinding::parse_send_keys_cmd(cmd) {
+            return Some(self.execute_send_keys(combo, false));
+        }
+        if let Some(spec) = config::keybinding::parse_click_cmd(cmd) {
+            return Some(self.execute_click(spec));
+        }
+        None
+    }
+
+    fn execute_click(&self, spec: &str) -> bool {
+        let click = match config::action::ClickSpec::parse(spec) {
+            Some(click) => click,
+            None => {
+                error!("[execute_click] invalid click: {}", spec);
+                return false;
+            }
+        };
+        let pressed_hint_keys = self.state.pressed_hint_keys.clone().unwrap_or_default();
+        let is_dragging = self.state.is_dragging;
+        let press_duration = self.config.action.click_press_duration;
+        debug!("[execute_click] click: {:?}", click);
+        queue::enqueue("click", async move {
+            if is_dragging {
+                mouse::mouse_drag_end().await;
+            }
+            if let Some((monitor_id, x, y)) = get_hint_position_by_text(&pressed_hint_keys) {
+                marks::record_cursor();
+                mouse::mouse_move(monitor_id, x, y).await;
+            }
+            mouse::mouse_click(&click, press_duration).await;
+            repeat::record_action(RepeatableAction::Click(click), &pressed_hint_keys);
+        });
+        true
+    }
+
+    fn execute_send_keys(&self, combo: &str, click: bool) -> bool {
+        let keys = config::keybinding::split_combo(combo);
+        if keys.is_empty() {
+            error!("[execute_send_keys] empty combo: {}", combo);
+            return false;
+        }
+        let pressed_hint_keys = self.state.pressed_hint_keys.clone().unwrap_or_default();
+        let is_dragging = self.state.is_dragging;
+        let combo = combo.to_string();
+        debug!("[execute_send_keys] combo: {}, click: {}", combo, click);
+        queue::enqueue("send_keys", async move {
+            if is_dragging {
+                mouse::mouse_drag_end().await;
+            }
+            if click {
+                if let Some((monitor_id, x, y)) = get_hint_position_by_text(&pressed_hint_keys) {
+                    marks::record_cursor();
+                    mouse::mouse_move(monitor_id, x, y).await;
+                }
+                mouse::mouse_click_left().await;
+            }
+            if inject::key_combo(&keys).await {
+                let action = RepeatableAction::SendKeys { combo, click };
+                // 不点击时作用于当前焦点, 不需要取hint处的元素
+                let hint_text = if click { pressed_hint_keys.as_str() } else { "" };
+                repeat::record_action(action, hint_text);
+            }
+        });
+        true
+    }
+
+    fn execute_type_text(&self, snippet: &str) -> bool {
+        let text = match self.config.action.snippets.get(snippet) {
+            Some(text) => text.clone(),
+            None => {
+                error!("[execute_type_text] snippet not found: {}", snippet);
+                return false;
+            }
+        };
+        let pressed_hint_keys = self.state.pressed_hint_keys.clone().unwrap_or_default();
+        // hide_hints会清空hints, 提前取得目标位置
+        let position = get_hint_position_by_text(&pressed_hint_keys);
+        let target = self.type_text_target(&pressed_hint_keys, position);
+        let is_dragging = self.state.is_dragging;
+        let app_handle_clone = self.app_handle.clone();
+        let type_interval = self.config.action.type_interval;
+        let timeout = queue::ACTION_TIMEOUT + inject::type_duration(&text, type_interval);
+        queue::enqueue_with_timeout("type_text", timeout, async move {
+            if is_dragging {
+                mouse::mouse_drag_end().await;
+            }
+            // 先隐藏hints再点击目标元素使其获得焦点, 避免overlay影响输入
+            hide_hints(app_handle_clone).await;
+            prompt::click_and_type(position, &text, type_interval).await;
+            let action = RepeatableAction::TypeText {
+                text,
+                click: position.is_some(),
+            };
+            match target {
+                Some(target) => repeat::record_target(action, target),
+                None => repeat::record_action(action, ""),
+            }
+        });
+        true
+    }
+
+    // 结束会话并在hint处打开输入框, 提交后点击hint并输入文本
+    fn execute_prompt_text(&self) -> bool {
+        let pressed_hint_keys = self.state.pressed_hint_keys.clone().unwrap_or_default();
+        let position = get_hint_position_by_text(&pressed_hint_keys);
+        let target = self.type_text_target(&pressed_hint_keys, position);
+        let is_dragging = self.state.is_dragging;
+        let app_handle_clone = self.app_handle.clone();
+        queue::enqueue("prompt_text", async move {
+            if is_dragging {
+                mouse::mouse_drag_end().await;
+            }
+            hide_hints(app_handle_clone.clone()).await;
+            prompt::open_prompt(&app_handle_clone, position, target);
+        });
+        true
+    }
+
+    // 输入文本动作的重复目标, 取hint处的元素
+    fn type_text_target(
+        &self,
+        hint_text: &str,
+        position: Option<(usize, i32, i32)>,
+    ) -> Option<repeat::ActionTarget> {
+        position
+            .and_then(|(monitor_id, x, y)| mouse::to_global_point(monitor_id, x, y))
+            .map(|point| repeat::capture_target(hint_text, point))
+    }
+
+    fn execute_pipeline(&self, pipeline_name: &str) -> bool {
+        let steps = match self.config.action.pipelines.get(pipeline_name) {
+            Some(steps) => steps.clone(),
+            None => {
+                error!("[execute_pipeline] pipeline not found: {}", pipeline_name);
+                return false;
+            }
+        };
+        let pressed_hint_keys = self.state.pressed_hint_keys.clone().unwrap_or_default();
+        let is_dragging = self.state.is_dragging;
+        let app_handle_clone = self.app_handle.clone();
+        let type_interval = self.config.action.type_interval;
+        let press_duration = self.config.action.click_press_duration;
+        let timeout = queue::ACTION_TIMEOUT + pipeline::total_wait(&steps, type_interval);
+        let pipeline_name = pipeline_name.to_string();
+        queue::enqueue_with_timeout("pipeline", timeout, async move {
+            if is_dragging {
+                mouse::mouse_drag_end().await;
+            }
+            marks::record_cursor();
+            // 流水线的步骤不单独记录, 重复时按名称重新执行整个流水线
+            let origin = get_hint_position_by_text(&pressed_hint_keys);
+            let target = origin
+                .and_then(|(monitor_id, x, y)| mouse::to_global_point(monitor_id, x, y))
+                .or_else(mouse::get_cursor_position)
+                .map(|point| repeat::capture_target(&pressed_hint_keys, point));
+            pipeline::run_pipeline(
+                app_handle_clone,
+                pipeline_name.clone(),
+                steps,
+                origin,
+                type_interval,
+                press_duration,
+            )
+            .await;
+            if let Some(target) = target {
+                repeat::record_target(RepeatableAction::Pipeline(pipeline_name), target);
+            }
+        });
+        true
+    }
+
+    fn execute_enter_mode(&mut self, mode_name: &str) -> bool {
+        let mode = match self.config.keybinding.modes.get(mode_name) {
+            Some(mode) => mode,
+            None => {
+                error!("[execute_enter_mode] mode not found: {}", mode_name);
+                return false;
+            }
+        };
+        debug!("[execute_enter_mode] enter mode: {}, from mode: {:?}", mode_name, self.state.active_mode);
+        self.state.active_mode = Some(mode_name.to_string());
+        let app_handle_clone = self.app_handle.clone();
+        let mode_name = mode_name.to_string();
+        let indicator = mode.indicator.clone();
+        let debug_mode = self.config.system.debug_mode;
+        queue::enqueue("enter_mode", async move {
+            mouse::show_cursor().await;
+            show_mode(app_handle_clone, mode_name, indicator).await;
+            // 通过全局快捷键进入模式时没有显示hints, overlay窗口可能被其他窗口遮挡
+            if !debug_mode {
+                ensure_all_overlays_topmost();
+            }
+        });
+        true
+    }
+
+    fn execute_leave_mode(&mut self) -> bool {
+        let mode_ends_session = self.state.mode_ends_session;
+        debug!("[execute_leave_mode] leave mode: {:?}, end session: {}", self.state.active_mode, mode_ends_session);
+        self.state.active_mode = None;
+        self.state.hold_keys.clear();
+        let is_dragging = self.state.is_dragging;
+        self.state.is_dragging = false;
+        if mode_ends_session {
+            queue::cancel();
+        }
+        let app_handle_clone = self.app_handle.clone();
+        queue::enqueue("leave_mode", async move {
+            if is_dragging {
+                mouse::mouse_drag_end().await;
+            }
+            if mode_ends_session {
+                hide_hints(app_handle_clone).await;
+            } else {
+                hide_mode(app_handle_clone).await;
+            }
+        });
+        true
+    }
+
     fn execute_move_to_hint(&self, exit: bool) -> bool {
         let app_handle_clone = self.app_handle.clone();
-        if let Some((monitor_id, x, y)) =
-            get_hint_position_by_text(&self.state.pressed_hint_keys.clone().unwrap())
-        {
-            tauri::async_runtime::spawn(async move {
+        let pressed_hint_keys = self.state.pressed_hint_keys.clone().unwrap();
+        if let Some((monitor_id, x, y)) = get_hint_position_by_text(&pressed_hint_keys) {
+            queue::enqueue("move_to_hint", async move {
+                marks::record_cursor();
                 mouse::mouse_move(monitor_id, x, y).await;
+                repeat::record_action(RepeatableAction::MoveTo, &pressed_hint_keys);
                 mouse::show_cursor().await;
                 if exit {
                     hide_hints(app_handle_clone).await;
@@ -132,8 +463,10 @@ impl<'a> Executor<'a> {
         let app_handle_clone = self.app_handle.clone();
         if hold_duration < 300 {
             debug!("[execute_quick_click] execute quick click since hold_duration: {} < 300", hold_duration);
-            tauri::async_runtime::spawn(async move {
+            let pressed_hint_keys = self.state.pressed_hint_keys.clone().unwrap();
+            queue::enqueue("quick_click", async move {
                 mouse::mouse_click_left().await;
+                repeat::record_action(RepeatableAction::LeftClick, &pressed_hint_keys);
                 if exit {
                     hide_hints(app_handle_clone).await;
                 }
@@ -155,7 +488,7 @@ impl<'a> Executor<'a> {
             if self.state.final_hint_key.clone().unwrap().is_empty() {
                 // 未找到末位hint, 提前进入hold状态
                 debug!("[execute_hold_at_hint] no final hint, directly to hold state, filter hints");
-                tauri::async_runtime::spawn(async move {
+                queue::enqueue("hold_at_hint", async move {
                     mouse::show_cursor().await;
                     filter_hints(app_handle_clone, "_removeAllHints".to_string()).await;
                 });
@@ -163,8 +496,9 @@ impl<'a> Executor<'a> {
         } else {
             let is_dragging = self.state.is_dragging;
             debug!("[execute_hold_at_hint] already in hold state, hide hints, currently is_dragging: {}", is_dragging);
+            queue::cancel();
             let app_handle_clone = self.app_handle.clone();
-            tauri::async_runtime::spawn(async move {
+            queue::enqueue("hold_at_hint", async move {
                 if is_dragging {
                     mouse::mouse_drag_end().await;
                 }
@@ -179,14 +513,16 @@ impl<'a> Executor<'a> {
         let pressed_hint_keys = self.state.pressed_hint_keys.clone().unwrap();
         let app_handle_clone = self.app_handle.clone();
         let is_dragging = self.state.is_dragging;
-        tauri::async_runtime::spawn(async move {
+        queue::enqueue("left_click", async move {
             if is_dragging {
                 mouse::mouse_drag_end().await;
             }
             if let Some((monitor_id, x, y)) = get_hint_position_by_text(&pressed_hint_keys) {
+                marks::record_cursor();
                 mouse::mouse_move(monitor_id, x, y).await;
             }
             mouse::mouse_click_left().await;
+            repeat::record_action(RepeatableAction::LeftClick, &pressed_hint_keys);
             if exit {
                 hide_hints(app_handle_clone).await;
             }
@@ -199,14 +535,16 @@ impl<'a> Executor<'a> {
         let pressed_hint_keys = self.state.pressed_hint_keys.clone().unwrap();
         let is_dragging = self.state.is_dragging;
         let app_handle_clone = self.app_handle.clone();
-        tauri::async_runtime::spawn(async move {
+        queue::enqueue("right_click", async move {
             if is_dragging {
                 mouse::mouse_drag_end().await;
             }
             if let Some((monitor_id, x, y)) = get_hint_position_by_text(&pressed_hint_keys) {
+                marks::record_cursor();
                 mouse::mouse_move(monitor_id, x, y).await;
             }
             mouse::mouse_click_right().await;
+            repeat::record_action(RepeatableAction::RightClick, &pressed_hint_keys);
             if exit {
                 hide_hints(app_handle_clone).await;
             }
@@ -219,14 +557,16 @@ impl<'a> Executor<'a> {
         let pressed_hint_keys = self.state.pressed_hint_keys.clone().unwrap();
         let is_dragging = self.state.is_dragging;
         let app_handle_clone = self.app_handle.clone();
-        tauri::async_runtime::spawn(async move {
+        queue::enqueue("middle_click", async move {
             if is_dragging {
                 let _ = mouse::mouse_drag_end().await;
             }
             if let Some((monitor_id, x, y)) = get_hint_position_by_text(&pressed_hint_keys) {
+                marks::record_cursor();
                 mouse::mouse_move(monitor_id, x, y).await;
             }
             mouse::mouse_click_middle().await;
+            repeat::record_action(RepeatableAction::MiddleClick, &pressed_hint_keys);
             if exit {
                 hide_hints(app_handle_clone).await;
             }
@@ -240,14 +580,16 @@ impl<'a> Executor<'a> {
         let is_dragging = self.state.is_dragging;
         let app_handle_clone = self.app_handle.clone();
         self.state.double_click_key_hold = true;
-        tauri::async_runtime::spawn(async move {
+        queue::enqueue("double_click", async move {
             if is_dragging {
                 mouse::mouse_drag_end().await;
             }
             if let Some((monitor_id, x, y)) = get_hint_position_by_text(&pressed_hint_keys) {
+                marks::record_cursor();
                 mouse::mouse_move(monitor_id, x, y).await;
             }
             mouse::mouse_double_click().await;
+            repeat::record_action(RepeatableAction::DoubleClick, &pressed_hint_keys);
             if exit {
                 hide_hints(app_handle_clone).await;
             }
@@ -256,9 +598,11 @@ impl<'a> Executor<'a> {
     }
 
     fn execute_exit(&self) -> bool {
+        // 丢弃尚未执行的动作, 退出动作不必排在它们之后
+        queue::cancel();
         let app_handle_clone = self.app_handle.clone();
         let is_dragging = self.state.is_dragging;
-        tauri::async_runtime::spawn(async move {
+        queue::enqueue("exit", async move {
             if is_dragging {
                 debug!("[execute_exit] end dragging and exit");
                 mouse::mouse_drag_end().await;
@@ -268,6 +612,329 @@ impl<'a> Executor<'a> {
         true
     }
 
+    // 等待下一个按键作为标记名, 在hint处保存hint的目标点, 否则保存光标位置
+    fn execute_set_mark(&mut self, at_hint: bool) -> bool {
+        let position = if at_hint {
+            let pressed_hint_keys = self.state.pressed_hint_keys.clone().unwrap();
+            get_hint_position_by_text(&pressed_hint_keys)
+                .and_then(|(monitor_id, x, y)| mouse::to_global_point(monitor_id, x, y))
+        } else {
+            mouse::get_cursor_position()
+        };
+        match position {
+            Some((x, y)) => {
+                debug!("[execute_set_mark] wait for mark name, position: ({},{})", x, y);
+                self.state.pending_mark = Some(PendingMark::Set(x, y));
+            }
+            None => error!("[execute_set_mark] failed to get position, at_hint: {}", at_hint),
+        }
+        true
+    }
+
+    fn execute_jump_mark(&mut self) -> bool {
+        debug!("[execute_jump_mark] wait for mark name");
+        self.state.pending_mark = Some(PendingMark::Jump);
+        true
+    }
+
+    // 以name保存或跳转到等待中的标记
+    pub fn execute_pending_mark(&mut self, pending_mark: PendingMark, name: &str) -> bool {
+        let marks_config = &self.config.action.marks;
+        match pending_mark {
+            PendingMark::Set(x, y) => marks::set_mark(name, x, y, marks_config),
+            PendingMark::Jump => match marks::get_mark(name, marks_config) {
+                Some((x, y)) => {
+                    queue::enqueue("jump_mark", async move {
+                        // 跳转到标记前记录当前位置, 可以用jump_back跳回
+                        marks::record_cursor();
+                        mouse::mouse_move_global(x, y).await;
+                    });
+                }
+                None => debug!("[execute_pending_mark] mark: {} not found", name),
+            },
+        }
+        true
+    }
+
+    // 在跳转列表中后退或前进
+    fn execute_jump(&mut self, back: bool) -> bool {
+        let position = if back {
+            mouse::get_cursor_position().and_then(marks::jump_back)
+        } else {
+            marks::jump_forward()
+        };
+        match position {
+            Some((x, y)) => {
+                debug!("[execute_jump] jump {} to: ({},{})", if back { "back" } else { "forward" }, x, y);
+                queue::enqueue("jump", async move {
+                    mouse::mouse_move_global(x, y).await;
+                });
+            }
+            None => debug!("[execute_jump] no position to jump, back: {}", back),
+        }
+        true
+    }
+
+    // 将光标位置保存为固定hint并写入配置文件, 下次显示hints时生效
+    fn execute_capture_pin(&self) -> bool {
+        let pin = match mouse::get_cursor_position().and_then(|(x, y)| create_pin_at(x, y)) {
+            Some(pin) => pin,
+            None => {
+                error!("[execute_capture_pin] failed to create pin at cursor");
+                return true;
+            }
+        };
+        // 会话中的配置可能包含[[app]]覆盖, 保存时只在基础配置上添加固定hint
+        let mut config = match config::get_base_config() {
+            Some(config) => config,
+            None => return true,
+        };
+        if config.hint.pins.contains(&pin) {
+            debug!("[execute_capture_pin] pin: {:?} already exists", pin);
+            return true;
+        }
+        info!("[execute_capture_pin] capture pin: {:?}", pin);
+        config.hint.pins.push(pin);
+        if let Err(e) = config::save_config(config) {
+            crate::report_config_error(self.app_handle, e);
+            return true;
+        }
+        // 只更新配置和hint文本列表, 不像apply_config那样结束当前会话
+        if let Some(config) = config::get_config() {
+            config::reload_derived_tables(&config);
+            init_hint_text_list_storage();
+        }
+        true
+    }
+
+    fn execute_repeat(&self) -> bool {
+        let app_handle_clone = self.app_handle.clone();
+        let is_dragging = self.state.is_dragging;
+        queue::enqueue_with_timeout("repeat", repeat::repeat_timeout(), async move {
+            if is_dragging {
+                mouse::mouse_drag_end().await;
+            }
+            hide_hints(app_handle_clone.clone()).await;
+            marks::record_cursor();
+            repeat::repeat_last_action(app_handle_clone).await;
+        });
+        true
+    }
+
+    // 重新开始选择hint, 已显示的hints全部恢复
+    fn reset_hint_selection(&mut self) {
+        self.state.pressed_hint_keys = Some("".to_string());
+        self.state.final_hint_key = Some("".to_string());
+        self.state.final_hint_key_hold = false;
+        self.state.hint_starts_with_extra = false;
+    }
+
+    fn execute_button_down(&mut self) -> bool {
+        let pressed_hint_keys = self.state.pressed_hint_keys.clone().unwrap();
+        let is_dragging = self.state.is_dragging;
+        debug!("[execute_button_down] press at hint: {}, already dragging: {}", pressed_hint_keys, is_dragging);
+        self.state.is_dragging = true;
+        self.state.drag_to_hint = true;
+        self.reset_hint_selection();
+        let app_handle_clone = self.app_handle.clone();
+        queue::enqueue("button_down", async move {
+            if !is_dragging {
+                if let Some((monitor_id, x, y)) = get_hint_position_by_text(&pressed_hint_keys) {
+                    marks::record_cursor();
+                    mouse::mouse_move(monitor_id, x, y).await;
+                }
+                mouse::mouse_drag_start().await;
+            }
+            reset_hints(app_handle_clone).await;
+        });
+        true
+    }
+
+    fn execute_button_up(&mut self) -> bool {
+        debug!("[execute_button_up] release at cursor, is_dragging: {}", self.state.is_dragging);
+        self.state.is_dragging = false;
+        self.state.drag_to_hint = false;
+        let app_handle_clone = self.app_handle.clone();
+        queue::enqueue("button_up", async move {
+            // 只在左键仍处于按下状态时抬起
+            mouse::mouse_release_held().await;
+            hide_hints(app_handle_clone).await;
+        });
+        true
+    }
+
+    // button_down 之后选中了hint: 移动到该hint, 作为放下位置或途经点
+    pub fn execute_drag_to_hint(&mut self) -> bool {
+        let pressed_hint_keys = self.state.pressed_hint_keys.clone().unwrap();
+        let target = get_hint_position_by_text(&pressed_hint_keys)
+            .and_then(|(monitor_id, x, y)| mouse::to_global_point(monitor_id, x, y));
+        let drag = self.config.action.drag.clone();
+        debug!("[execute_drag_to_hint] target hint: {}, position: {:?}, drop: {}", pressed_hint_keys, target, drag.drop_on_label);
+        if drag.drop_on_label {
+            self.state.is_dragging = false;
+            self.state.drag_to_hint = false;
+        }
+        self.reset_hint_selection();
+        let app_handle_clone = self.app_handle.clone();
+        // 匀速移动可能超过默认超时时间, 按移动距离延长
+        let move_duration = target
+            .map(|(x, y)| mouse::smooth_move_duration(x, y, drag.speed))
+            .unwrap_or_default();
+        let timeout = queue::ACTION_TIMEOUT + move_duration;
+        queue::enqueue_with_timeout("drag_to_hint", timeout, async move {
+            if let Some((x, y)) = target {
+                marks::record_cursor();
+                mouse::mouse_move_global_smooth(x, y, drag.speed).await;
+            }
+            if drag.drop_on_label {
+                mouse::mouse_drag_end().await;
+                hide_hints(app_handle_clone).await;
+            } else {
+                reset_hints(app_handle_clone).await;
+            }
+        });
+        true
+    }
+
+    // 跳转到方向上最近的元素, 光标和高亮的hint一起移动
+    fn execute_navigate(
+        &mut self,
+        key_binddings: &config::keybinding::DirectionKeybindingsConfig,
+    ) -> bool {
+        let direction = calculate_direction_delta(key_binddings, self.state, 1, 1);
+        if direction == (0, 0) {
+            return false;
+        }
+        let pressed_hint_keys = self.state.pressed_hint_keys.clone().unwrap();
+        let next_hint = match find_hint_in_direction(&pressed_hint_keys, direction) {
+            Some(next_hint) => next_hint,
+            None => {
+                debug!("[execute_navigate] no hint in direction: {:?} from: {}", direction, pressed_hint_keys);
+                // 没有可跳转的元素时也拦截按键
+                return true;
+            }
+        };
+        debug!("[execute_navigate] navigate from: {} to: {}", pressed_hint_keys, next_hint);
+        // 只更新当前hint, 按住的末位键不变
+        self.state.pressed_hint_keys = Some(next_hint.clone());
+        clear_sub_grid();
+        let app_handle_clone = self.app_handle.clone();
+        queue::enqueue("navigate", async move {
+            if let Some((monitor_id, x, y)) = get_hint_position_by_text(&next_hint) {
+                mouse::mouse_move(monitor_id, x, y).await;
+            }
+            select_hint(app_handle_clone, next_hint).await;
+        });
+        true
+    }
+
+    // 在元素树中跳转, 光标移动到新的hint并框出其元素边界
+    fn execute_select_related(&mut self, relation: HintRelation) -> bool {
+        let pressed_hint_keys = self.state.pressed_hint_keys.clone().unwrap();
+        let next_hint = match find_related_hint(&pressed_hint_keys, relation) {
+            Some(next_hint) => next_hint,
+            None => {
+                debug!("[execute_select_related] no {:?} hint from: {}", relation, pressed_hint_keys);
+                // 没有可跳转的元素时也拦截按键
+                return true;
+            }
+        };
+        debug!("[execute_select_related] select {:?} from: {} to: {}", relation, pressed_hint_keys, next_hint);
+        self.state.pressed_hint_keys = Some(next_hint.clone());
+        clear_sub_grid();
+        let app_handle_clone = self.app_handle.clone();
+        queue::enqueue("select_related", async move {
+            if let Some((monitor_id, x, y)) = get_hint_position_by_text(&next_hint) {
+                mouse::mouse_move(monitor_id, x, y).await;
+            }
+            select_hint(app_handle_clone.clone(), next_hint.clone()).await;
+            highlight_hint(app_handle_clone, next_hint).await;
+        });
+        true
+    }
+
+    // 在当前目标点周围打开细分网格, 已打开时关闭
+    fn execute_sub_grid(&mut self) -> bool {
+        let pressed_hint_keys = self.state.pressed_hint_keys.clone().unwrap();
+        let app_handle_clone = self.app_handle.clone();
+        if is_sub_grid_open(&pressed_hint_keys) {
+            debug!("[execute_sub_grid] close sub grid of: {}", pressed_hint_keys);
+            clear_sub_grid();
+            queue::enqueue("sub_grid", async move {
+                hide_sub_grid(app_handle_clone).await;
+            });
+            return true;
+        }
+        let sub_grid = match create_sub_grid(&pressed_hint_keys, &self.config.hint.sub_grid) {
+            Some(sub_grid) => sub_grid,
+            None => {
+                error!("[execute_sub_grid] failed to create sub grid for: {}", pressed_hint_keys);
+                return true;
+            }
+        };
+        queue::enqueue("sub_grid", async move {
+            show_sub_grid(app_handle_clone, sub_grid).await;
+        });
+        true
+    }
+
+    // 选择细分网格中的标签, 当前hint的目标点移动到该格子, 不是网格标签时返回false
+    pub fn execute_sub_grid_select(&mut self, key: &str) -> bool {
+        let pressed_hint_keys = match self.state.pressed_hint_keys.clone() {
+            Some(pressed_hint_keys) => pressed_hint_keys,
+            None => return false,
+        };
+        let cell = match get_sub_grid_cell(&pressed_hint_keys, key) {
+            Some(cell) => cell,
+            None => return false,
+        };
+        info!("[execute_sub_grid_select] refine hint: {} to cell: {:?}", pressed_hint_keys, cell);
+        clear_sub_grid();
+        let (hint_x, hint_y) = match refine_hint_position(&pressed_hint_keys, cell.x, cell.y) {
+            Some(position) => position,
+            None => return true,
+        };
+        let app_handle_clone = self.app_handle.clone();
+        queue::enqueue("sub_grid_select", async move {
+            if let Some((monitor_id, x, y)) = get_hint_position_by_text(&pressed_hint_keys) {
+                mouse::mouse_move(monitor_id, x, y).await;
+            }
+            hide_sub_grid(app_handle_clone.clone()).await;
+            refine_hint(app_handle_clone, pressed_hint_keys, hint_x, hint_y).await;
+        });
+        true
+    }
+
+    fn start_motion(
+        &self,
+        kind: MotionKind,
+        key_binddings: &config::keybinding::DirectionKeybindingsConfig,
+        target: Option<scroll::ScrollTarget>,
+    ) {
+        motion::start(
+            self.app_handle.clone(),
+            kind,
+            key_binddings.clone(),
+            self.config.mouse.clone(),
+            target,
+        );
+    }
+
+    // 滚动的目标: 直接向窗口发送滚动时取当前hint的位置和所在窗口
+    fn scroll_target(&self) -> Option<scroll::ScrollTarget> {
+        if !self.config.mouse.scroll.target_window {
+            return None;
+        }
+        let pressed_hint_keys = self.state.pressed_hint_keys.clone().unwrap_or_default();
+        let window_handle = get_hint_by_text(&pressed_hint_keys)
+            .map(|hint| hint.window_handle)
+            .unwrap_or(0);
+        get_hint_position_by_text(&pressed_hint_keys)
+            .and_then(|(monitor_id, x, y)| mouse::to_global_point(monitor_id, x, y))
+            .or_else(mouse::get_cursor_position)
+            .map(|(x, y)| scroll::ScrollTarget { x, y, window_handle })
+    }
+
     fn execute_move_hints(
         &self,
         key_binddings: &config::keybinding::DirectionKeybindingsConfig,
@@ -285,9 +952,13 @@ impl<'a> Executor<'a> {
         let (dx, dy) =
             calculate_direction_delta(key_binddings, self.state, mouse_step.x, mouse_step.y);
         if dx != 0 || dy != 0 {
+            if self.config.mouse.motion.enabled {
+                self.start_motion(MotionKind::MoveHints, key_binddings, None);
+                return true;
+            }
             let app_handle_clone = self.app_handle.clone();
             // 发送事件到前端更新显示
-            tauri::async_runtime::spawn(async move {
+            queue::enqueue("move_hints", async move {
                 move_hints(app_handle_clone, (dx, dy)).await;
             });
             return true;
@@ -295,6 +966,35 @@ impl<'a> Executor<'a> {
         false
     }
 
+    fn execute_move_cursor(
+        &self,
+        key_binddings: &config::keybinding::DirectionKeybindingsConfig,
+    ) -> bool {
+        let hold_keys_map = &self.state.hold_keys;
+        let mut hold_keys: Vec<String> = Vec::new();
+        if hold_keys_map.len() > 0 {
+            for (k, v) in hold_keys_map.iter() {
+                if *v {
+                    hold_keys.push(k.clone());
+                }
+            }
+        }
+        let mouse_step = self.config.mouse.get_translate_step(&hold_keys);
+        let (dx, dy) =
+            calculate_direction_delta(key_binddings, self.state, mouse_step.x, mouse_step.y);
+        if dx != 0 || dy != 0 {
+            if self.config.mouse.motion.enabled {
+                self.start_motion(MotionKind::MoveCursor, key_binddings, None);
+                return true;
+            }
+            queue::enqueue("move_cursor", async move {
+                mouse::mouse_move_relative(dx, dy).await;
+            });
+            return true;
+        }
+        false
+    }
+
     fn execute_scroll_hints(
         &self,
         key_binddings: &config::keybinding::DirectionKeybindingsConfig,
@@ -312,8 +1012,14 @@ impl<'a> Executor<'a> {
         let (dx, dy) =
             calculate_direction_delta(key_binddings, self.state, mouse_step.x, mouse_step.y);
         if dx != 0 || dy != 0 {
-            tauri::async_runtime::spawn(async move {
-                mouse::mouse_wheel_move(dx, dy).await;
+            let target = self.scroll_target();
+            if self.config.mouse.motion.enabled {
+                self.start_motion(MotionKind::Scroll, key_binddings, target);
+                return true;
+            }
+            let scroll_config = self.config.mouse.scroll.clone();
+            queue::enqueue(scroll::SCROLL_ACTION, async move {
+                scroll::scroll(dx, dy, target, &scroll_config).await;
             });
             return true;
         }
@@ -343,14 +1049,27 @@ impl<'a> Executor<'a> {
             if start_dragging {
                 self.state.is_dragging = true;
             }
-            tauri::async_runtime::spawn(async move {
+            let motion_enabled = self.config.mouse.motion.enabled;
+            if motion_enabled && !start_dragging {
+                self.start_motion(MotionKind::Drag, key_binddings, None);
+                return true;
+            }
+            let app_handle_clone = self.app_handle.clone();
+            let key_binddings = key_binddings.clone();
+            let mouse_config = self.config.mouse.clone();
+            queue::enqueue("drag_hints", async move {
                 if start_dragging {
                     if let Some((monitor_id, x, y)) = get_hint_position_by_text(&prefix_keys) {
                         mouse::mouse_move(monitor_id, x, y).await;
                     }
                     mouse::mouse_drag_start().await;
                 }
-                mouse::mouse_move_relative(dx, dy).await;
+                if motion_enabled {
+                    // 按下左键之后再开始连续移动
+                    motion::start(app_handle_clone, MotionKind::Drag, key_binddings, mouse_config, None);
+                } else {
+                    mouse::mouse_move_relative(dx, dy).await;
+                }
             });
             return true;
         }
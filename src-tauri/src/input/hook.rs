@@ -4,20 +4,15 @@ use log::{debug, error, info};
 use once_cell::sync::Lazy;
 use windows::Win32::{
     Foundation::{LPARAM, LRESULT, WPARAM},
-    UI::{
-        Input::KeyboardAndMouse::{
-            GetKeyboardState, ToUnicode, VK_OEM_1, VK_OEM_2, VK_OEM_3, VK_OEM_4, VK_OEM_5, VK_OEM_6, VK_OEM_7, VK_OEM_COMMA, VK_OEM_MINUS, VK_OEM_PERIOD, VK_OEM_PLUS
-        },
-        WindowsAndMessaging::{
-            CallNextHookEx, SetWindowsHookExW, UnhookWindowsHookEx, HHOOK, KBDLLHOOKSTRUCT,
-            WH_KEYBOARD_LL, WM_KEYDOWN,
-        },
+    UI::WindowsAndMessaging::{
+        CallNextHookEx, SetWindowsHookExW, UnhookWindowsHookEx, HHOOK, KBDLLHOOKSTRUCT,
+        WH_KEYBOARD_LL, WM_KEYDOWN,
     },
 };
 
 use crate::config;
 
-use super::keyboard::handle_keyboard_event;
+use super::{injector::INJECTED_EXTRA_INFO, key::win32::from_hook_event, keyboard::handle_keyboard_event};
 
 // 包装 HHOOK
 struct HookHandle(HHOOK);
@@ -34,74 +29,23 @@ pub unsafe extern "system" fn keyboard_hook_proc(
     }
 
     let key_info = *(lparam.0 as *const KBDLLHOOKSTRUCT);
+    if key_info.dwExtraInfo == INJECTED_EXTRA_INFO {
+        // 自己注入的按键, 直接放行
+        return CallNextHookEx(None, code, wparam, lparam);
+    }
     let is_down = wparam.0 == WM_KEYDOWN as usize;
-    let vk_code = key_info.vkCode as u16;
 
-    let key: String;
-    if let Some(key_config) = config::keyboard::VIRTUAL_KEY_MAP.lock().unwrap().get(&vk_code) {
-        debug!("[keyboard_hook_proc] read keyboard config key: {}, vk_code: {}", key_config, vk_code);
-        key = key_config.to_string();
-    } else {
-        // 转换虚拟键码到字符串
-        key = match vk_code {
-            // 数字键 (0-9)
-            0x30..=0x39 => {
-                let mut key_state = [0u8; 256];
-                let _ = GetKeyboardState(&mut key_state);
-                let mut result = [0u16; 2];
-                let chars = ToUnicode(
-                    vk_code as u32,
-                    key_info.scanCode as u32,
-                    Some(&key_state),
-                    &mut result,
-                    0,
-                );
-                if chars > 0 {
-                    if let Some(c) = char::from_u32(result[0] as u32) {
-                        c.to_string()
-                    } else {
-                        return CallNextHookEx(None, code, wparam, lparam);
-                    }
-                } else {
-                    return CallNextHookEx(None, code, wparam, lparam);
-                }
-            }
-            // 字母键 (A-Z)
-            0x41..=0x5A => {
-                let mut key_state = [0u8; 256];
-                let _ = GetKeyboardState(&mut key_state);
-                let mut result = [0u16; 2];
-                let chars = ToUnicode(
-                    vk_code as u32,
-                    key_info.scanCode as u32,
-                    Some(&key_state),
-                    &mut result,
-                    0,
-                );
-                if chars > 0 {
-                    if let Some(c) = char::from_u32(result[0] as u32) {
-                        c.to_string().to_uppercase()
-                    } else {
-                        return CallNextHookEx(None, code, wparam, lparam);
-                    }
-                } else {
-                    return CallNextHookEx(None, code, wparam, lparam);
-                }
-            }
-            x if x == VK_OEM_PLUS.0 => "=".to_string(),
-            x if x == VK_OEM_MINUS.0 => "-".to_string(),
-            x if x == VK_OEM_COMMA.0 => ",".to_string(),
-            x if x == VK_OEM_PERIOD.0 => ".".to_string(),
-            x if x == VK_OEM_1.0 => ";".to_string(),
-            x if x == VK_OEM_2.0 => "/".to_string(),
-            x if x == VK_OEM_3.0 => "`".to_string(),
-            x if x == VK_OEM_4.0 => "[".to_string(),
-            x if x == VK_OEM_5.0 => "\\".to_string(),
-            x if x == VK_OEM_6.0 => "]".to_string(),
-            x if x == VK_OEM_7.0 => "'".to_string(),
-            _ => return CallNextHookEx(None, code, wparam, lparam),
-        };
+    // 按物理位置识别按键, 与键盘布局无关
+    let key = match from_hook_event(key_info.vkCode, key_info.scanCode, key_info.flags.0) {
+        Some(key) => key,
+        None => return CallNextHookEx(None, code, wparam, lparam),
+    };
+    // 非字符键需要在 keyboard.available_key 中列出
+    if !key.is_char() && !config::get_derived_tables().available_keys.contains(&key) {
+        return CallNextHookEx(None, code, wparam, lparam);
     }
+    let key = key.name().to_string();
+    debug!("[keyboard_hook_proc] key: {}, vk_code: {}, scan_code: {}", key, key_info.vkCode, key_info.scanCode);
     if let Ok(app_handle_lock) = APP_HANDLE.lock() {
         if let Some(app_handle) = app_handle_lock.as_ref() {
             if handle_keyboard_event(app_handle, &key, is_down) {
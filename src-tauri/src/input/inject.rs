@@ -0,0 +1,132 @@
+use std::sync::Mutex;
+
+use log::{debug, error, info};
+use once_cell::sync::Lazy;
+use windows::Win32::UI::Input::KeyboardAndMouse::{VK_RETURN, VK_TAB};
+
+use super::injector::{self, InputEvent};
+use super::key::{win32, Key};
+
+// 已按下尚未释放的按键, 动作被取消时需要释放
+static HELD_KEYS: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+// 将按键名转换为虚拟键码
+pub fn get_virtual_key(key: &str) -> Option<u16> {
+    Key::from_name(key).and_then(win32::to_virtual_key)
+}
+
+fn press_keys(keys: &[String]) -> windows::core::Result<()> {
+    let events = keys
+        .iter()
+        .filter_map(|key| get_virtual_key(key))
+        .map(InputEvent::KeyDown)
+        .collect::<Vec<_>>();
+    injector::send(&events)
+}
+
+fn release_keys(keys: &[String]) -> windows::core::Result<()> {
+    let events = keys
+        .iter()
+        .rev()
+        .filter_map(|key| get_virtual_key(key))
+        .map(InputEvent::KeyUp)
+        .collect::<Vec<_>>();
+    injector::send(&events)
+}
+
+// 输入一个字符, 换行和制表符使用对应按键, 其余字符以Unicode方式输入, 与当前键盘布局无关
+fn type_char(c: char) -> windows::core::Result<()> {
+    let vk = match c {
+        '\n' => Some(VK_RETURN.0),
+        '\t' => Some(VK_TAB.0),
+        _ => None,
+    };
+    if let Some(vk) = vk {
+        return injector::send(&[InputEvent::KeyDown(vk), InputEvent::KeyUp(vk)]);
+    }
+    let mut buffer = [0u16; 2];
+    let units = c.encode_utf16(&mut buffer);
+    // 代理对的两个UTF-16单元需要连续发送
+    let events = units
+        .iter()
+        .map(|unit| InputEvent::UnicodeDown(*unit))
+        .chain(units.iter().map(|unit| InputEvent::UnicodeUp(*unit)))
+        .collect::<Vec<_>>();
+    injector::send(&events)
+}
+
+// 按顺序按下按键, 与 key_release 配对使用, 有未知按键或发送失败时返回false
+pub async fn key_press(keys: &[String]) -> bool {
+    let mut success = true;
+    if let Some(key) = keys.iter().find(|key| get_virtual_key(key).is_none()) {
+        error!("[key_press] unknown key: {}", key);
+        success = false;
+    }
+    if let Err(e) = press_keys(keys) {
+        error!("[key_press] failed: {}", e);
+        success = false;
+    }
+    if let Ok(mut held_keys) = HELD_KEYS.lock() {
+        held_keys.extend(keys.iter().cloned());
+    }
+    success
+}
+
+// 逆序释放按键, 发送失败时返回false
+pub async fn key_release(keys: &[String]) -> bool {
+    let success = match release_keys(keys) {
+        Ok(()) => true,
+        Err(e) => {
+            error!("[key_release] failed: {}", e);
+            false
+        }
+    };
+    if let Ok(mut held_keys) = HELD_KEYS.lock() {
+        held_keys.retain(|key| !keys.contains(key));
+    }
+    success
+}
+
+// 释放所有已按下尚未释放的按键
+pub async fn key_release_held() {
+    let held_keys = match HELD_KEYS.lock() {
+        Ok(mut held_keys) => std::mem::take(&mut *held_keys),
+        Err(_) => {
+            error!("[key_release_held] failed to get HELD_KEYS lock");
+            return;
+        }
+    };
+    if !held_keys.is_empty() {
+        info!("[key_release_held] release held keys: {:?}", held_keys);
+        if let Err(e) = release_keys(&held_keys) {
+            error!("[key_release_held] failed: {}", e);
+        }
+    }
+}
+
+// 按下并释放组合键, 全部按键都成功发送时返回true
+pub async fn key_combo(keys: &[String]) -> bool {
+    debug!("[key_combo] keys: {:?}", keys);
+    let pressed = key_press(keys).await;
+    let released = key_release(keys).await;
+    pressed && released
+}
+
+// 逐字符输入文本, 字符之间间隔interval毫秒, 避免目标程序丢失输入
+pub async fn type_text(text: &str, interval: u64) {
+    debug!("[type_text] chars: {}, interval: {}", text.chars().count(), interval);
+    for c in text.chars().filter(|c| *c != '\r') {
+        if let Err(e) = type_char(c) {
+            error!("[type_text] failed: {}", e);
+            return;
+        }
+        if interval > 0 {
+            tokio::time::sleep(tokio::time::Duration::from_millis(interval)).await;
+        }
+    }
+}
+
+// 按字符间隔估算输入文本所需的时间
+pub fn type_duration(text: &str, interval: u64) -> std::time::Duration {
+    std::time::Duration::from_millis(text.chars().count() as u64 * interval)
+}
@@ -0,0 +1,175 @@
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::Lazy;
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    mouse_event, SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS,
+    KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, MOUSE_EVENT_FLAGS, MOUSEEVENTF_HWHEEL,
+    MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP, MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP,
+    MOUSEEVENTF_MOVE, MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, MOUSEEVENTF_WHEEL,
+    MOUSEEVENTF_XDOWN, MOUSEEVENTF_XUP, VIRTUAL_KEY,
+};
+use windows::Win32::UI::WindowsAndMessaging::{SetCursorPos, XBUTTON1, XBUTTON2};
+
+use crate::config::action::{BUTTON_LEFT, BUTTON_MIDDLE, BUTTON_RIGHT, BUTTON_X1, BUTTON_X2};
+
+// 注入事件的标记, 钩子遇到带此标记的事件直接放行, 避免处理自己注入的按键
+pub const INJECTED_EXTRA_INFO: usize = 0x4B48_4E54;
+
+// 注入的鼠标和键盘事件, 坐标为全局物理坐标
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputEvent {
+    MoveTo(i32, i32),
+    MoveRelative(i32, i32),
+    ButtonDown(String),
+    ButtonUp(String),
+    // 水平和垂直滚动量
+    Wheel(i32, i32),
+    KeyDown(u16),
+    KeyUp(u16),
+    // 以UTF-16单元输入字符, 与键盘布局无关
+    UnicodeDown(u16),
+    UnicodeUp(u16),
+}
+
+// 鼠标和键盘事件的发送方式, 测试中替换为记录事件的实现
+pub trait Injector: Send + Sync {
+    fn send(&self, events: &[InputEvent]) -> windows::core::Result<()>;
+}
+
+static INJECTOR: Lazy<RwLock<Arc<dyn Injector>>> =
+    Lazy::new(|| RwLock::new(Arc::new(Win32Injector)));
+
+// 按顺序发送事件
+pub fn send(events: &[InputEvent]) -> windows::core::Result<()> {
+    let injector = INJECTOR.read().unwrap().clone();
+    injector.send(events)
+}
+
+// 替换注入器, 返回之前的注入器
+#[cfg(test)]
+pub fn set_injector(injector: Arc<dyn Injector>) -> Arc<dyn Injector> {
+    std::mem::replace(&mut *INJECTOR.write().unwrap(), injector)
+}
+
+// 通过Win32 API发送事件, 连续的键盘事件用一次SendInput发送, 避免被其他输入打断
+pub struct Win32Injector;
+
+impl Injector for Win32Injector {
+    fn send(&self, events: &[InputEvent]) -> windows::core::Result<()> {
+        let mut keyboard_inputs = Vec::new();
+        for event in events {
+            match event {
+                InputEvent::KeyDown(vk) => {
+                    keyboard_inputs.push(keyboard_input(*vk, 0, KEYBD_EVENT_FLAGS(0)))
+                }
+                InputEvent::KeyUp(vk) => {
+                    keyboard_inputs.push(keyboard_input(*vk, 0, KEYEVENTF_KEYUP))
+                }
+                InputEvent::UnicodeDown(unit) => {
+                    keyboard_inputs.push(keyboard_input(0, *unit, KEYEVENTF_UNICODE))
+                }
+                InputEvent::UnicodeUp(unit) => keyboard_inputs.push(keyboard_input(
+                    0,
+                    *unit,
+                    KEYEVENTF_UNICODE | KEYEVENTF_KEYUP,
+                )),
+                event => {
+                    send_inputs(&std::mem::take(&mut keyboard_inputs))?;
+                    send_mouse_event(event)?;
+                }
+            }
+        }
+        send_inputs(&keyboard_inputs)
+    }
+}
+
+// 按键对应的按下/抬起事件及附加数据
+fn button_events(button: &str) -> Option<(MOUSE_EVENT_FLAGS, MOUSE_EVENT_FLAGS, i32)> {
+    match button {
+        BUTTON_LEFT => Some((MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP, 0)),
+        BUTTON_RIGHT => Some((MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, 0)),
+        BUTTON_MIDDLE => Some((MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP, 0)),
+        BUTTON_X1 => Some((MOUSEEVENTF_XDOWN, MOUSEEVENTF_XUP, XBUTTON1 as i32)),
+        BUTTON_X2 => Some((MOUSEEVENTF_XDOWN, MOUSEEVENTF_XUP, XBUTTON2 as i32)),
+        _ => None,
+    }
+}
+
+fn send_mouse_event(event: &InputEvent) -> windows::core::Result<()> {
+    unsafe {
+        match event {
+            InputEvent::MoveTo(x, y) => return SetCursorPos(*x, *y),
+            InputEvent::MoveRelative(delta_x, delta_y) => {
+                mouse_event(MOUSEEVENTF_MOVE, *delta_x, *delta_y, 0, 0)
+            }
+            InputEvent::ButtonDown(button) | InputEvent::ButtonUp(button) => {
+                let (down, up, data) = match button_events(button) {
+                    Some(events) => events,
+                    None => return Err(windows::core::Error::from_win32()),
+                };
+                let flags = if matches!(event, InputEvent::ButtonDown(_)) { down } else { up };
+                mouse_event(flags, 0, 0, data, 0);
+            }
+            InputEvent::Wheel(delta_x, delta_y) => {
+                // 垂直滚动
+                if *delta_y != 0 {
+                    mouse_event(MOUSEEVENTF_WHEEL, 0, 0, *delta_y, 0);
+                }
+                // 水平滚动
+                if *delta_x != 0 {
+                    mouse_event(MOUSEEVENTF_HWHEEL, 0, 0, *delta_x, 0);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn keyboard_input(vk: u16, scan: u16, flags: KEYBD_EVENT_FLAGS) -> INPUT {
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: VIRTUAL_KEY(vk),
+                wScan: scan,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: INJECTED_EXTRA_INFO,
+            },
+        },
+    }
+}
+
+fn send_inputs(inputs: &[INPUT]) -> windows::core::Result<()> {
+    if inputs.is_empty() {
+        return Ok(());
+    }
+    let sent = unsafe { SendInput(inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        return Err(windows::core::Error::from_win32());
+    }
+    Ok(())
+}
+
+// 记录发送的事件而不注入, 用于测试动作的顺序
+#[cfg(test)]
+#[derive(Default)]
+pub struct RecordingInjector {
+    events: std::sync::Mutex<Vec<InputEvent>>,
+}
+
+#[cfg(test)]
+impl RecordingInjector {
+    pub fn take(&self) -> Vec<InputEvent> {
+        std::mem::take(&mut *self.events.lock().unwrap())
+    }
+}
+
+#[cfg(test)]
+impl Injector for RecordingInjector {
+    fn send(&self, events: &[InputEvent]) -> windows::core::Result<()> {
+        self.events.lock().unwrap().extend(events.iter().cloned());
+        Ok(())
+    }
+}
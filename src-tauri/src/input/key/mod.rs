@@ -0,0 +1,267 @@
+pub mod win32;
+
+use indexmap::IndexMap;
+
+// 按键由物理位置确定, 与键盘布局和平台无关
+// 按键名是配置中使用的稳定名称, 字符键以美式布局上的字符命名
+// 扫描码使用PC Set 1, 扩展键带0xE0前缀
+macro_rules! keys {
+    ($($key:ident = $name:literal, $scancode:literal;)*) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Key {
+            $($key,)*
+        }
+
+        const KEYS: &[(Key, &str, u16)] = &[$((Key::$key, $name, $scancode),)*];
+    };
+}
+
+keys! {
+    Esc = "Esc", 0x01;
+    Digit1 = "1", 0x02;
+    Digit2 = "2", 0x03;
+    Digit3 = "3", 0x04;
+    Digit4 = "4", 0x05;
+    Digit5 = "5", 0x06;
+    Digit6 = "6", 0x07;
+    Digit7 = "7", 0x08;
+    Digit8 = "8", 0x09;
+    Digit9 = "9", 0x0A;
+    Digit0 = "0", 0x0B;
+    Minus = "-", 0x0C;
+    Equal = "=", 0x0D;
+    Backspace = "Backspace", 0x0E;
+    Tab = "Tab", 0x0F;
+    Q = "Q", 0x10;
+    W = "W", 0x11;
+    E = "E", 0x12;
+    R = "R", 0x13;
+    T = "T", 0x14;
+    Y = "Y", 0x15;
+    U = "U", 0x16;
+    I = "I", 0x17;
+    O = "O", 0x18;
+    P = "P", 0x19;
+    BracketLeft = "[", 0x1A;
+    BracketRight = "]", 0x1B;
+    Enter = "Enter", 0x1C;
+    LCtrl = "LCtrl", 0x1D;
+    A = "A", 0x1E;
+    S = "S", 0x1F;
+    D = "D", 0x20;
+    F = "F", 0x21;
+    G = "G", 0x22;
+    H = "H", 0x23;
+    J = "J", 0x24;
+    K = "K", 0x25;
+    L = "L", 0x26;
+    Semicolon = ";", 0x27;
+    Quote = "'", 0x28;
+    Backquote = "`", 0x29;
+    LShift = "LShift", 0x2A;
+    Backslash = "\\", 0x2B;
+    Z = "Z", 0x2C;
+    X = "X", 0x2D;
+    C = "C", 0x2E;
+    V = "V", 0x2F;
+    B = "B", 0x30;
+    N = "N", 0x31;
+    M = "M", 0x32;
+    Comma = ",", 0x33;
+    Period = ".", 0x34;
+    Slash = "/", 0x35;
+    RShift = "RShift", 0x36;
+    NumMultiply = "NumMultiply", 0x37;
+    LAlt = "LAlt", 0x38;
+    Space = "Space", 0x39;
+    CapsLock = "CapsLock", 0x3A;
+    F1 = "F1", 0x3B;
+    F2 = "F2", 0x3C;
+    F3 = "F3", 0x3D;
+    F4 = "F4", 0x3E;
+    F5 = "F5", 0x3F;
+    F6 = "F6", 0x40;
+    F7 = "F7", 0x41;
+    F8 = "F8", 0x42;
+    F9 = "F9", 0x43;
+    F10 = "F10", 0x44;
+    Pause = "Pause", 0x45;
+    ScrollLock = "ScrollLock", 0x46;
+    Num7 = "Num7", 0x47;
+    Num8 = "Num8", 0x48;
+    Num9 = "Num9", 0x49;
+    NumSubtract = "NumSubtract", 0x4A;
+    Num4 = "Num4", 0x4B;
+    Num5 = "Num5", 0x4C;
+    Num6 = "Num6", 0x4D;
+    NumAdd = "NumAdd", 0x4E;
+    Num1 = "Num1", 0x4F;
+    Num2 = "Num2", 0x50;
+    Num3 = "Num3", 0x51;
+    Num0 = "Num0", 0x52;
+    NumDecimal = "NumDecimal", 0x53;
+    IntlBackslash = "IntlBackslash", 0x56;
+    F11 = "F11", 0x57;
+    F12 = "F12", 0x58;
+    F13 = "F13", 0x64;
+    F14 = "F14", 0x65;
+    F15 = "F15", 0x66;
+    F16 = "F16", 0x67;
+    F17 = "F17", 0x68;
+    F18 = "F18", 0x69;
+    F19 = "F19", 0x6A;
+    F20 = "F20", 0x6B;
+    F21 = "F21", 0x6C;
+    F22 = "F22", 0x6D;
+    F23 = "F23", 0x6E;
+    F24 = "F24", 0x76;
+    NumEnter = "NumEnter", 0xE01C;
+    RCtrl = "RCtrl", 0xE01D;
+    NumDivide = "NumDivide", 0xE035;
+    PrintScreen = "PrintScreen", 0xE037;
+    RAlt = "RAlt", 0xE038;
+    NumLock = "NumLock", 0xE045;
+    Home = "Home", 0xE047;
+    Up = "Up", 0xE048;
+    PageUp = "PageUp", 0xE049;
+    Left = "Left", 0xE04B;
+    Right = "Right", 0xE04D;
+    End = "End", 0xE04F;
+    Down = "Down", 0xE050;
+    PageDown = "PageDown", 0xE051;
+    Insert = "Insert", 0xE052;
+    Delete = "Delete", 0xE053;
+    LWin = "LWin", 0xE05B;
+    RWin = "RWin", 0xE05C;
+    Apps = "Apps", 0xE05D;
+}
+
+impl Key {
+    fn entry(self) -> &'static (Key, &'static str, u16) {
+        KEYS.iter().find(|(key, _, _)| *key == self).unwrap()
+    }
+
+    pub fn name(self) -> &'static str {
+        self.entry().1
+    }
+
+    pub fn scancode(self) -> u16 {
+        self.entry().2
+    }
+
+    // 单个字母不区分大小写
+    pub fn from_name(name: &str) -> Option<Key> {
+        let mut chars = name.chars();
+        let name = match (chars.next(), chars.next()) {
+            (Some(c), None) => c.to_ascii_uppercase().to_string(),
+            _ => name.to_string(),
+        };
+        KEYS.iter()
+            .find(|(_, key_name, _)| *key_name == name)
+            .map(|(key, _, _)| *key)
+    }
+
+    pub fn from_scancode(scancode: u16) -> Option<Key> {
+        KEYS.iter()
+            .find(|(_, _, key_scancode)| *key_scancode == scancode)
+            .map(|(key, _, _)| *key)
+    }
+
+    // 字母, 数字和符号键, 在不同键盘布局上输入的字符不同
+    pub fn is_char(self) -> bool {
+        self.name().chars().count() == 1
+    }
+
+    // 按键在当前键盘布局上的标签, 非字符键和无法转换时使用按键名
+    pub fn label(self) -> String {
+        win32::get_layout_label(self).unwrap_or_else(|| self.name().to_string())
+    }
+}
+
+// 所有按键名, 按扫描码顺序
+pub fn get_key_names() -> Vec<&'static str> {
+    KEYS.iter().map(|(_, name, _)| *name).collect()
+}
+
+// 非字符键的按键名, 需要在keyboard.available_key中列出才会被处理
+pub fn get_named_keys() -> Vec<&'static str> {
+    KEYS.iter()
+        .filter(|(key, _, _)| !key.is_char())
+        .map(|(_, name, _)| *name)
+        .collect()
+}
+
+// hint字符在当前键盘布局上的标签, 只包含与按键名不同的, 用于overlay显示
+pub fn get_layout_labels(chars: impl Iterator<Item = char>) -> IndexMap<String, String> {
+    let mut labels = IndexMap::new();
+    for c in chars {
+        let name = c.to_string();
+        if labels.contains_key(&name) {
+            continue;
+        }
+        if let Some(label) = Key::from_name(&name).map(Key::label) {
+            if label != name {
+                labels.insert(name, label);
+            }
+        }
+    }
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn names_and_scancodes_are_unique() {
+        let names = KEYS.iter().map(|(_, name, _)| *name).collect::<HashSet<_>>();
+        let scancodes = KEYS.iter().map(|(_, _, scancode)| *scancode).collect::<HashSet<_>>();
+        assert_eq!(names.len(), KEYS.len());
+        assert_eq!(scancodes.len(), KEYS.len());
+    }
+
+    #[test]
+    fn name_and_scancode_round_trip() {
+        for (key, name, scancode) in KEYS.iter() {
+            assert_eq!(Key::from_name(name), Some(*key), "{}", name);
+            assert_eq!(Key::from_scancode(*scancode), Some(*key), "{}", name);
+            assert_eq!(key.name(), *name);
+            assert_eq!(key.scancode(), *scancode);
+        }
+    }
+
+    #[test]
+    fn single_letter_names_ignore_case() {
+        assert_eq!(Key::from_name("a"), Some(Key::A));
+        assert_eq!(Key::from_name("A"), Some(Key::A));
+        assert_eq!(Key::from_name("lctrl"), None);
+        assert_eq!(Key::from_name("Ctrl"), None);
+    }
+
+    #[test]
+    fn extended_keys_have_e0_prefix() {
+        for (key, base) in [
+            (Key::RCtrl, Key::LCtrl),
+            (Key::RAlt, Key::LAlt),
+            (Key::NumEnter, Key::Enter),
+            (Key::NumDivide, Key::Slash),
+            (Key::Up, Key::Num8),
+            (Key::Down, Key::Num2),
+            (Key::Left, Key::Num4),
+            (Key::Right, Key::Num6),
+        ] {
+            assert_eq!(key.scancode(), 0xE000 | base.scancode(), "{:?}", key);
+        }
+    }
+
+    #[test]
+    fn char_keys() {
+        assert!(Key::A.is_char());
+        assert!(Key::Semicolon.is_char());
+        assert!(!Key::F1.is_char());
+        assert!(!Key::NumEnter.is_char());
+        assert!(get_named_keys().iter().all(|name| name.chars().count() > 1));
+    }
+}
@@ -0,0 +1,235 @@
+use windows::Win32::UI::{
+    Input::KeyboardAndMouse::{GetKeyboardLayout, MapVirtualKeyExW, ToUnicodeEx, MAPVK_VSC_TO_VK_EX},
+    WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId},
+};
+
+use super::Key;
+
+// 按键对应的Windows虚拟键码, 用于注入按键和识别没有扫描码的事件
+// 字符键使用美式布局的虚拟键码, 注入的 LCtrl+C 等组合键在各布局上含义相同
+const VIRTUAL_KEYS: &[(Key, u16)] = &[
+    (Key::Backspace, 0x08),
+    (Key::Tab, 0x09),
+    (Key::Enter, 0x0D),
+    (Key::NumEnter, 0x0D),
+    (Key::Pause, 0x13),
+    (Key::CapsLock, 0x14),
+    (Key::Esc, 0x1B),
+    (Key::Space, 0x20),
+    (Key::PageUp, 0x21),
+    (Key::PageDown, 0x22),
+    (Key::End, 0x23),
+    (Key::Home, 0x24),
+    (Key::Left, 0x25),
+    (Key::Up, 0x26),
+    (Key::Right, 0x27),
+    (Key::Down, 0x28),
+    (Key::PrintScreen, 0x2C),
+    (Key::Insert, 0x2D),
+    (Key::Delete, 0x2E),
+    (Key::Digit0, 0x30),
+    (Key::Digit1, 0x31),
+    (Key::Digit2, 0x32),
+    (Key::Digit3, 0x33),
+    (Key::Digit4, 0x34),
+    (Key::Digit5, 0x35),
+    (Key::Digit6, 0x36),
+    (Key::Digit7, 0x37),
+    (Key::Digit8, 0x38),
+    (Key::Digit9, 0x39),
+    (Key::A, 0x41),
+    (Key::B, 0x42),
+    (Key::C, 0x43),
+    (Key::D, 0x44),
+    (Key::E, 0x45),
+    (Key::F, 0x46),
+    (Key::G, 0x47),
+    (Key::H, 0x48),
+    (Key::I, 0x49),
+    (Key::J, 0x4A),
+    (Key::K, 0x4B),
+    (Key::L, 0x4C),
+    (Key::M, 0x4D),
+    (Key::N, 0x4E),
+    (Key::O, 0x4F),
+    (Key::P, 0x50),
+    (Key::Q, 0x51),
+    (Key::R, 0x52),
+    (Key::S, 0x53),
+    (Key::T, 0x54),
+    (Key::U, 0x55),
+    (Key::V, 0x56),
+    (Key::W, 0x57),
+    (Key::X, 0x58),
+    (Key::Y, 0x59),
+    (Key::Z, 0x5A),
+    (Key::LWin, 0x5B),
+    (Key::RWin, 0x5C),
+    (Key::Apps, 0x5D),
+    (Key::Num0, 0x60),
+    (Key::Num1, 0x61),
+    (Key::Num2, 0x62),
+    (Key::Num3, 0x63),
+    (Key::Num4, 0x64),
+    (Key::Num5, 0x65),
+    (Key::Num6, 0x66),
+    (Key::Num7, 0x67),
+    (Key::Num8, 0x68),
+    (Key::Num9, 0x69),
+    (Key::NumMultiply, 0x6A),
+    (Key::NumAdd, 0x6B),
+    (Key::NumSubtract, 0x6D),
+    (Key::NumDecimal, 0x6E),
+    (Key::NumDivide, 0x6F),
+    (Key::F1, 0x70),
+    (Key::F2, 0x71),
+    (Key::F3, 0x72),
+    (Key::F4, 0x73),
+    (Key::F5, 0x74),
+    (Key::F6, 0x75),
+    (Key::F7, 0x76),
+    (Key::F8, 0x77),
+    (Key::F9, 0x78),
+    (Key::F10, 0x79),
+    (Key::F11, 0x7A),
+    (Key::F12, 0x7B),
+    (Key::F13, 0x7C),
+    (Key::F14, 0x7D),
+    (Key::F15, 0x7E),
+    (Key::F16, 0x7F),
+    (Key::F17, 0x80),
+    (Key::F18, 0x81),
+    (Key::F19, 0x82),
+    (Key::F20, 0x83),
+    (Key::F21, 0x84),
+    (Key::F22, 0x85),
+    (Key::F23, 0x86),
+    (Key::F24, 0x87),
+    (Key::NumLock, 0x90),
+    (Key::ScrollLock, 0x91),
+    (Key::LShift, 0xA0),
+    (Key::RShift, 0xA1),
+    (Key::LCtrl, 0xA2),
+    (Key::RCtrl, 0xA3),
+    (Key::LAlt, 0xA4),
+    (Key::RAlt, 0xA5),
+    (Key::Semicolon, 0xBA),
+    (Key::Equal, 0xBB),
+    (Key::Comma, 0xBC),
+    (Key::Minus, 0xBD),
+    (Key::Period, 0xBE),
+    (Key::Slash, 0xBF),
+    (Key::Backquote, 0xC0),
+    (Key::BracketLeft, 0xDB),
+    (Key::Backslash, 0xDC),
+    (Key::BracketRight, 0xDD),
+    (Key::Quote, 0xDE),
+    (Key::IntlBackslash, 0xE2),
+];
+
+// 扩展键标志, 对应KBDLLHOOKSTRUCT.flags中的LLKHF_EXTENDED
+const EXTENDED_FLAG: u32 = 0x01;
+
+pub fn to_virtual_key(key: Key) -> Option<u16> {
+    VIRTUAL_KEYS
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, vk)| *vk)
+}
+
+// 同一个虚拟键码对应多个按键时使用第一个, 如 Enter 和 NumEnter
+pub fn from_virtual_key(vk: u16) -> Option<Key> {
+    VIRTUAL_KEYS
+        .iter()
+        .find(|(_, k)| *k == vk)
+        .map(|(key, _)| *key)
+}
+
+// 键盘钩子事件对应的按键, 优先按扫描码识别物理位置, 其他程序注入的事件可能没有扫描码
+pub fn from_hook_event(vk: u32, scancode: u32, flags: u32) -> Option<Key> {
+    let scancode = scancode as u16;
+    let extended = if flags & EXTENDED_FLAG != 0 {
+        Key::from_scancode(0xE000 | scancode)
+    } else {
+        None
+    };
+    extended
+        .or_else(|| Key::from_scancode(scancode).filter(|_| scancode != 0))
+        .or_else(|| from_virtual_key(vk as u16))
+}
+
+// 字符键在前台窗口键盘布局上输入的字符, 转为大写与hint字符一致
+pub fn get_layout_label(key: Key) -> Option<String> {
+    if !key.is_char() {
+        return None;
+    }
+    unsafe {
+        let thread_id = GetWindowThreadProcessId(GetForegroundWindow(), None);
+        let layout = GetKeyboardLayout(thread_id);
+        let scancode = key.scancode() as u32;
+        let vk = MapVirtualKeyExW(scancode, MAPVK_VSC_TO_VK_EX, Some(layout));
+        if vk == 0 {
+            return None;
+        }
+        let key_state = [0u8; 256];
+        let mut buffer = [0u16; 4];
+        // wFlags为4时不改变键盘状态, 避免死键影响之后的输入
+        let count = ToUnicodeEx(vk, scancode, &key_state, &mut buffer, 4, Some(layout));
+        if count <= 0 {
+            return None;
+        }
+        String::from_utf16(&buffer[..count as usize])
+            .ok()
+            .map(|label| label.to_uppercase())
+            .filter(|label| !label.trim().is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn virtual_key_round_trip() {
+        for (key, vk) in VIRTUAL_KEYS.iter() {
+            assert_eq!(to_virtual_key(*key), Some(*vk), "{:?}", key);
+            // NumEnter 与 Enter 的虚拟键码相同
+            if *key != Key::NumEnter {
+                assert_eq!(from_virtual_key(*vk), Some(*key), "{:?}", key);
+            }
+        }
+        assert_eq!(from_virtual_key(0x0D), Some(Key::Enter));
+        assert_eq!(from_virtual_key(0xFF), None);
+    }
+
+    #[test]
+    fn hook_event_uses_extended_scancode() {
+        assert_eq!(from_hook_event(0xA2, 0x1D, 0), Some(Key::LCtrl));
+        assert_eq!(from_hook_event(0xA3, 0x1D, EXTENDED_FLAG), Some(Key::RCtrl));
+        assert_eq!(from_hook_event(0xA4, 0x38, 0), Some(Key::LAlt));
+        assert_eq!(from_hook_event(0xA5, 0x38, EXTENDED_FLAG), Some(Key::RAlt));
+        assert_eq!(from_hook_event(0x0D, 0x1C, 0), Some(Key::Enter));
+        assert_eq!(from_hook_event(0x0D, 0x1C, EXTENDED_FLAG), Some(Key::NumEnter));
+        assert_eq!(from_hook_event(0x26, 0x48, EXTENDED_FLAG), Some(Key::Up));
+        assert_eq!(from_hook_event(0x28, 0x50, EXTENDED_FLAG), Some(Key::Down));
+        assert_eq!(from_hook_event(0x25, 0x4B, EXTENDED_FLAG), Some(Key::Left));
+        assert_eq!(from_hook_event(0x27, 0x4D, EXTENDED_FLAG), Some(Key::Right));
+    }
+
+    #[test]
+    fn hook_event_uses_physical_position() {
+        // 关闭NumLock时小键盘8的虚拟键码为VK_UP, 扫描码仍是小键盘位置
+        assert_eq!(from_hook_event(0x26, 0x48, 0), Some(Key::Num8));
+        // 非美式布局上字符键的虚拟键码不同, 如AZERTY的A键位置虚拟键码为Q
+        assert_eq!(from_hook_event(0x51, 0x1E, 0), Some(Key::A));
+    }
+
+    #[test]
+    fn hook_event_without_scancode_uses_virtual_key() {
+        assert_eq!(from_hook_event(0xA3, 0, 0), Some(Key::RCtrl));
+        assert_eq!(from_hook_event(0x41, 0, 0), Some(Key::A));
+        // 未知的扩展扫描码按虚拟键码识别
+        assert_eq!(from_hook_event(0xA5, 0x7F, EXTENDED_FLAG), Some(Key::RAlt));
+        assert_eq!(from_hook_event(0xFF, 0, 0), None);
+    }
+}
@@ -1,9 +1,9 @@
 use crate::config;
 use crate::hint::{filter_hints, hide_hints};
-use crate::input::{executor, mouse};
+use crate::input::{executor, marks, mouse, queue};
 use log::{debug, error, info};
 use once_cell::sync::Lazy;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
 
 // 键盘状态
@@ -19,6 +19,15 @@ pub struct KeyboardState {
     pub is_dragging: bool,
     pub hold_keys: HashMap<String, bool>,
     pub double_click_key_hold: bool,
+    pub active_mode: Option<String>,
+    // 模式是通过全局快捷键直接进入的, 退出模式时一并结束会话
+    pub mode_ends_session: bool,
+    // button_down 之后重新选择hint作为拖拽目标
+    pub drag_to_hint: bool,
+    // set_mark 或 jump_mark 之后等待标记名
+    pub pending_mark: Option<marks::PendingMark>,
+    // 模式中被拦截的按下的按键, 抬起时同样拦截
+    pub consumed_keys: HashSet<String>,
 }
 
 impl KeyboardState {
@@ -34,6 +43,11 @@ impl KeyboardState {
             is_dragging: false,
             hold_keys: HashMap::<String, bool>::new(),
             double_click_key_hold: false,
+            active_mode: None,
+            mode_ends_session: false,
+            drag_to_hint: false,
+            pending_mark: None,
+            consumed_keys: HashSet::new(),
         }
     }
 }
@@ -57,15 +71,21 @@ pub fn switch_keyboard_ctrl(visible: bool, app_handle: Option<&tauri::AppHandle>
                 state.final_hint_key_hold = false;
                 state.final_hint_key_hold_start = 0;
                 state.is_dragging = false;
+                state.active_mode = None;
+                state.mode_ends_session = false;
+                state.drag_to_hint = false;
+                state.pending_mark = None;
                 if let Some(app_handle) = app_handle {
                     let app_handle_clone = app_handle.clone();
-                    tauri::async_runtime::spawn(async move {
+                    queue::enqueue("switch_keyboard_ctrl", async move {
                         mouse::show_cursor().await;
                         hide_hints(app_handle_clone).await;
                     });
                 }
             } else {
-                tauri::async_runtime::spawn(async move {
+                // 记录会话开始前的光标位置, 可以用jump_back跳回
+                marks::record_cursor();
+                queue::enqueue("switch_keyboard_ctrl", async move {
                     mouse::hide_cursor().await;
                 });
             }
@@ -73,6 +93,25 @@ pub fn switch_keyboard_ctrl(visible: bool, app_handle: Option<&tauri::AppHandle>
     }
 }
 
+// 通过全局快捷键进入模式, 若当前不在会话中则开启一个不显示hints的会话
+pub fn enter_mode(app_handle: &tauri::AppHandle, mode_name: &str) {
+    let in_session = KEYBOARD_STATE
+        .lock()
+        .map(|state| state.in_ctrl_session)
+        .unwrap_or(false);
+    if !in_session {
+        switch_keyboard_ctrl(true, Some(app_handle));
+    }
+    let configs = config::get_config().unwrap();
+    let mut state = KEYBOARD_STATE.lock().unwrap();
+    if !in_session {
+        state.mode_ends_session = true;
+    }
+    info!("[enter_mode] enter mode: {} by hotkey, in_session: {}", mode_name, in_session);
+    let mut executor = executor::Executor::new(app_handle, &configs, &mut state);
+    executor.execute(Some(config::keybinding::enter_mode_cmd(mode_name).as_str()));
+}
+
 // 检查字符是否在指定的字符集中
 fn char_in_charset(c: char, charset: &[char]) -> bool {
     charset.contains(&c)
@@ -86,7 +125,7 @@ fn filter_hints_by_state(state: &mut KeyboardState, app_handle: &tauri::AppHandl
     let prefix = state.pressed_hint_keys.clone().unwrap();
     debug!("[filter_hints_by_state] prefix: {}", prefix);
     let app_handle_clone = app_handle.clone();
-    tauri::async_runtime::spawn(async move {
+    queue::enqueue("filter_hints_by_state", async move {
         filter_hints(app_handle_clone, prefix).await;
     });
 }
@@ -95,7 +134,8 @@ fn hide_hints_when_session_end(state: &mut KeyboardState, app_handle: &tauri::Ap
     let app_handle_clone = app_handle.clone();
     let is_dragging = state.is_dragging;
     debug!("[hide_hints_when_session_end] is_dragging: {}", is_dragging);
-    tauri::async_runtime::spawn(async move {
+    queue::cancel();
+    queue::enqueue("hide_hints_when_session_end", async move {
         if is_dragging {
             mouse::mouse_drag_end().await;
         }
@@ -103,13 +143,24 @@ fn hide_hints_when_session_end(state: &mut KeyboardState, app_handle: &tauri::Ap
     });
 }
 
+// 配置重新加载前结束正在进行的会话, 丢弃排队的动作并隐藏hints
+pub fn end_session(app_handle: &tauri::AppHandle) {
+    let mut state = KEYBOARD_STATE.lock().unwrap();
+    if !state.in_ctrl_session {
+        return;
+    }
+    info!("[end_session] end session, active mode: {:?}", state.active_mode);
+    hide_hints_when_session_end(&mut state, app_handle);
+}
+
 // 处理键盘事件
 pub fn handle_keyboard_event(app_handle: &tauri::AppHandle, key: &str, is_down: bool) -> bool {
     let mut state = KEYBOARD_STATE.lock().unwrap();
 
-    // 如果hints不可见，不处理任何按键
+    // 如果hints不可见，不处理任何按键, 会话中被拦截的按键在会话结束后抬起时仍然拦截
     if !state.in_ctrl_session {
-        return false;
+        let consumed = state.consumed_keys.remove(key);
+        return consumed && !is_down;
     }
     info!(
         "[handle_keyboard_event] key: {}:{}, is_holding_at_hint:{}",
@@ -120,11 +171,20 @@ pub fn handle_keyboard_event(app_handle: &tauri::AppHandle, key: &str, is_down:
 
     let configs = config::get_config().unwrap();
     let keybindings = &configs.keybinding;
-    let modifier_keys = config::keybinding::MODIFIERS.clone();
+    let tables = config::get_derived_tables();
+
+    let active_mode = state
+        .active_mode
+        .as_ref()
+        .and_then(|mode_name| keybindings.modes.get(mode_name));
 
     // 记录状态
-    if modifier_keys.contains(&key.to_string()) {
+    if tables.modifiers.contains(&key.to_string()) {
         state.hold_keys.insert(key.to_string(), is_down);
+    } else if let Some(mode) = active_mode {
+        if mode.is_bound_key(key) {
+            state.hold_keys.insert(key.to_string(), is_down);
+        }
     } else {
         if !state.final_hint_key_hold {
             if keybindings.global.is_translate_key(key) {
@@ -137,6 +197,8 @@ pub fn handle_keyboard_event(app_handle: &tauri::AppHandle, key: &str, is_down:
                 state.hold_keys.insert(key.to_string(), is_down);
             } else if keybindings.at_hint.is_drag_key(key) {
                 state.hold_keys.insert(key.to_string(), is_down);
+            } else if keybindings.at_hint.is_navigate_key(key) {
+                state.hold_keys.insert(key.to_string(), is_down);
             }
         }
     }
@@ -154,6 +216,53 @@ pub fn handle_keyboard_event(app_handle: &tauri::AppHandle, key: &str, is_down:
             }
         }
 
+        // 按住的末位hint键会自动重复, 不作为标记名
+        let is_final_key_repeat =
+            state.final_hint_key_hold && state.final_hint_key.as_deref() == Some(key);
+        if !is_final_key_repeat {
+            if let Some(pending_mark) = state.pending_mark.take() {
+                // 单字符按键作为标记名, 其他按键取消等待并正常处理
+                if key.len() == 1 {
+                    info!("[handle_keyboard_event] mark: {:?} with name: {}", pending_mark, key);
+                    let mut executor = executor::Executor::new(app_handle, &configs, &mut state);
+                    return executor.execute_pending_mark(pending_mark, key);
+                }
+                debug!("[handle_keyboard_event] cancel pending mark: {:?} by key: {}", pending_mark, key);
+            }
+        }
+
+        if let Some(mode) = active_mode {
+            // 模式中只处理模式自身的绑定, 未绑定的按键正常传播
+            for (cmd, keys) in mode.bindings.iter() {
+                if key_in_keys(key, keys) {
+                    info!(
+                        "[handle_keyboard_event] mode: {:?} cmd:{} triggered by key: {}",
+                        state.active_mode, cmd, key
+                    );
+                    let mut executor = executor::Executor::new(app_handle, &configs, &mut state);
+                    if executor.execute_in_mode(Some(cmd.as_str())) {
+                        state.consumed_keys.insert(key.to_string());
+                        return true;
+                    }
+                }
+            }
+            return false;
+        }
+
+        for (mode_name, mode) in keybindings.modes.iter() {
+            if key_in_keys(key, &mode.enter) {
+                info!(
+                    "[handle_keyboard_event] enter mode: {} triggered by key: {}",
+                    mode_name, key
+                );
+                let mut executor = executor::Executor::new(app_handle, &configs, &mut state);
+                if executor.execute(Some(config::keybinding::enter_mode_cmd(mode_name).as_str())) {
+                    state.consumed_keys.insert(key.to_string());
+                    return true;
+                }
+            }
+        }
+
         let hint_config = &configs.hint;
         let charset_count = hint_config.charsets.len();
         if !state.final_hint_key_hold {
@@ -217,15 +326,21 @@ pub fn handle_keyboard_event(app_handle: &tauri::AppHandle, key: &str, is_down:
                     }
                 }
             }
-            let cmd_key = config::keybinding::GLOBAL_KEY_DOWN_KEYBINDINGS.clone();
-            for (cmd, keys) in cmd_key {
-                if key_in_keys(current_key, &keys) {
+            if state.drag_to_hint && current_key == config::keyboard::HINT_KEY {
+                // 拖拽中选中了目标hint
+                info!("[handle_keyboard_event] drag to hint: {:?}", state.pressed_hint_keys);
+                let mut executor = executor::Executor::new(app_handle, &configs, &mut state);
+                executor.execute_drag_to_hint();
+                return true;
+            }
+            for (cmd, keys) in tables.global_key_down_keybindings.iter() {
+                if key_in_keys(current_key, keys) {
                     info!(
                         "[handle_keyboard_event] global_key_down cmd:{} triggered by key: {}",
                         cmd, key
                     );
                     let mut executor = executor::Executor::new(app_handle, &configs, &mut state);
-                    no_propagation = executor.execute(Some(cmd));
+                    no_propagation = executor.execute(Some(cmd.as_str()));
                 }
             }
             if no_propagation {
@@ -235,7 +350,7 @@ pub fn handle_keyboard_event(app_handle: &tauri::AppHandle, key: &str, is_down:
 
         if state.final_hint_key_hold {
             // holding状态, 处理按住final_key后的操作
-            let cmd_key = config::keybinding::AT_HINT_KEYBINDINGS.clone();
+            let cmd_key = &tables.at_hint_keybindings;
             let mut current_key = key;
             if let Some(last_key) = state.final_hint_key.as_ref() {
                 // 先处理动态热键, 动态热键会覆盖配置的静态热键
@@ -259,20 +374,31 @@ pub fn handle_keyboard_event(app_handle: &tauri::AppHandle, key: &str, is_down:
                     }
                 }
             }
+            // 细分网格打开时, 网格标签优先于其他按键
+            let mut executor = executor::Executor::new(app_handle, &configs, &mut state);
+            if executor.execute_sub_grid_select(key) {
+                return true;
+            }
             for (cmd, keys) in cmd_key {
-                if key_in_keys(current_key, &keys) {
+                if key_in_keys(current_key, keys) {
                     info!(
                         "[handle_keyboard_event] at_hint cmd:{} triggered by key: {}",
                         cmd, current_key
                     );
                     let mut executor = executor::Executor::new(app_handle, &configs, &mut state);
-                    if executor.execute_at_hint(Some(cmd)) {
+                    if executor.execute_at_hint(Some(cmd.as_str())) {
                         return true;
                     }
                 }
             }
         }
     } else {
+        // 按下时被模式拦截的按键, 抬起时也不传播
+        let consumed = state.consumed_keys.remove(key);
+        if active_mode.is_some() {
+            // 模式中不处理全局的按键释放, 模式持续到主动退出
+            return consumed;
+        }
         // 处理按键释放
         let mut current_key = key;
         let mut end_session = false;
@@ -282,15 +408,14 @@ pub fn handle_keyboard_event(app_handle: &tauri::AppHandle, key: &str, is_down:
             }
         }
 
-        let cmd_key = config::keybinding::GLOBAL_KEY_UP_KEYBINDINGS.clone();
-        for (cmd, keys) in cmd_key {
-            if key_in_keys(current_key, &keys) {
+        for (cmd, keys) in tables.global_key_up_keybindings.iter() {
+            if key_in_keys(current_key, keys) {
                 info!(
                     "[handle_keyboard_event] global_key_up cmd:{} triggered by key: {}",
                     cmd, current_key
                 );
                 let mut executor = executor::Executor::new(app_handle, &configs, &mut state);
-                end_session = executor.execute(Some(cmd));
+                end_session = executor.execute(Some(cmd.as_str()));
             }
         }
 
@@ -298,6 +423,7 @@ pub fn handle_keyboard_event(app_handle: &tauri::AppHandle, key: &str, is_down:
             hide_hints_when_session_end(&mut state, app_handle);
             return true;
         }
+        return consumed;
     }
 
     false
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use log::{debug, error, info};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{self, action::MarksConfig};
+use crate::window::window::get_foreground_window;
+
+use super::mouse;
+
+const MARKS_FILE_NAME: &str = "marks.json";
+// 不按程序区分时标记保存在此键下
+const GLOBAL_MARKS_KEY: &str = "";
+
+// 等待标记名的命令, 下一个按键作为标记名
+#[derive(Debug, Clone, Copy)]
+pub enum PendingMark {
+    // 按下set_mark时记录的全局物理坐标
+    Set(i32, i32),
+    Jump,
+}
+
+// 标记的位置, 按程序保存时相对于程序窗口左上角, 否则为全局物理坐标
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Mark {
+    x: i32,
+    y: i32,
+}
+
+// 程序名 -> 标记名 -> 位置, 首次使用时从文件加载
+static MARKS_STORAGE: Lazy<Mutex<Option<HashMap<String, HashMap<String, Mark>>>>> =
+    Lazy::new(|| Mutex::new(None));
+
+// 跳转列表, index等于列表长度时表示位于最新位置之后
+struct JumpList {
+    positions: Vec<(i32, i32)>,
+    index: usize,
+}
+
+static JUMP_LIST: Lazy<Mutex<JumpList>> = Lazy::new(|| {
+    Mutex::new(JumpList {
+        positions: Vec::new(),
+        index: 0,
+    })
+});
+
+fn get_marks_path() -> Option<PathBuf> {
+    let config_path = config::get_config_path()?;
+    Some(PathBuf::from(config_path).with_file_name(MARKS_FILE_NAME))
+}
+
+fn load_marks(config: &MarksConfig) -> HashMap<String, HashMap<String, Mark>> {
+    if !config.persist {
+        return HashMap::new();
+    }
+    let path = match get_marks_path() {
+        Some(path) if path.exists() => path,
+        _ => return HashMap::new(),
+    };
+    match fs::read_to_string(&path).map(|content| serde_json::from_str(&content)) {
+        Ok(Ok(marks)) => {
+            info!("[load_marks] load marks from: {:?}", path);
+            marks
+        }
+        Ok(Err(e)) => {
+            error!("[load_marks] failed to parse {:?}: {}", path, e);
+            HashMap::new()
+        }
+        Err(e) => {
+            error!("[load_marks] failed to read {:?}: {}", path, e);
+            HashMap::new()
+        }
+    }
+}
+
+fn save_marks(marks: &HashMap<String, HashMap<String, Mark>>) {
+    let path = match get_marks_path() {
+        Some(path) => path,
+        None => {
+            error!("[save_marks] config file not found");
+            return;
+        }
+    };
+    let content = match serde_json::to_string_pretty(marks) {
+        Ok(content) => content,
+        Err(e) => {
+            error!("[save_marks] failed to serialize marks: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = config::file::write_atomic(&path.to_string_lossy(), &content) {
+        error!("[save_marks] failed to write {:?}: {}", path, e);
+    }
+}
+
+// 标记所属的程序名和位置的原点, 按程序保存时为前台窗口的左上角
+fn get_mark_scope(config: &MarksConfig) -> Option<(String, i32, i32)> {
+    if !config.persist {
+        return Some((GLOBAL_MARKS_KEY.to_string(), 0, 0));
+    }
+    match get_foreground_window() {
+        Some(window) => Some((window.process_name, window.rect.x, window.rect.y)),
+        None => {
+            error!("[get_mark_scope] failed to get foreground window");
+            None
+        }
+    }
+}
+
+// 将全局物理坐标保存为标记
+pub fn set_mark(name: &str, x: i32, y: i32, config: &MarksConfig) {
+    let (scope, origin_x, origin_y) = match get_mark_scope(config) {
+        Some(scope) => scope,
+        None => return,
+    };
+    let mut storage = match MARKS_STORAGE.lock() {
+        Ok(storage) => storage,
+        Err(_) => {
+            error!("[set_mark] failed to get MARKS_STORAGE lock");
+            return;
+        }
+    };
+    let marks = storage.get_or_insert_with(|| load_marks(config));
+    let mark = Mark {
+        x: x - origin_x,
+        y: y - origin_y,
+    };
+    info!("[set_mark] set mark: {} of: {:?} to: {:?}", name, scope, mark);
+    marks.entry(scope).or_default().insert(name.to_string(), mark);
+    if config.persist {
+        save_marks(marks);
+    }
+}
+
+// 获取标记的全局物理坐标
+pub fn get_mark(name: &str, config: &MarksConfig) -> Option<(i32, i32)> {
+    let (scope, origin_x, origin_y) = get_mark_scope(config)?;
+    let mut storage = MARKS_STORAGE.lock().ok()?;
+    let marks = storage.get_or_insert_with(|| load_marks(config));
+    let mark = marks.get(&scope)?.get(name)?;
+    debug!("[get_mark] get mark: {} of: {:?}: {:?}", name, scope, mark);
+    Some((mark.x + origin_x, mark.y + origin_y))
+}
+
+// 记录一次动作之前的光标位置, 丢弃当前位置之后的记录
+pub fn record_jump(position: (i32, i32), size: usize) {
+    let mut jump_list = match JUMP_LIST.lock() {
+        Ok(jump_list) => jump_list,
+        Err(_) => {
+            error!("[record_jump] failed to get JUMP_LIST lock");
+            return;
+        }
+    };
+    let index = jump_list.index;
+    jump_list.positions.truncate(index);
+    if jump_list.positions.last() != Some(&position) {
+        jump_list.positions.push(position);
+    }
+    let overflow = jump_list.positions.len().saturating_sub(size);
+    jump_list.positions.drain(..overflow);
+    jump_list.index = jump_list.positions.len();
+    debug!("[record_jump] record: {:?}, jump list size: {}", position, jump_list.positions.len());
+}
+
+// 在动作移动光标之前记录当前光标位置
+pub fn record_cursor() {
+    if let Some(position) = mouse::get_cursor_position() {
+        let jump_list_size = config::get_config()
+            .map(|config| config.action.marks.jump_list_size)
+            .unwrap_or_default();
+        record_jump(position, jump_list_size);
+    }
+}
+
+// 跳回上一个位置, 位于最新位置之后时先记录当前位置, 以便再跳回来
+pub fn jump_back(current: (i32, i32)) -> Option<(i32, i32)> {
+    let mut jump_list = JUMP_LIST.lock().ok()?;
+    if jump_list.index >= jump_list.positions.len() {
+        if jump_list.positions.last() != Some(&current) {
+            jump_list.positions.push(current);
+        }
+        jump_list.index = jump_list.positions.len().saturating_sub(1);
+    }
+    if jump_list.index == 0 {
+        return None;
+    }
+    jump_list.index -= 1;
+    jump_list.positions.get(jump_list.index).copied()
+}
+
+pub fn jump_forward() -> Option<(i32, i32)> {
+    let mut jump_list = JUMP_LIST.lock().ok()?;
+    if jump_list.index + 1 >= jump_list.positions.len() {
+        return None;
+    }
+    jump_list.index += 1;
+    jump_list.positions.get(jump_list.index).copied()
+}
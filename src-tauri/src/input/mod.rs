@@ -1,4 +1,14 @@
 mod executor;
 mod mouse;
 pub mod hook;
+mod inject;
+mod injector;
+pub mod key;
+pub mod marks;
 pub mod keyboard;
+mod motion;
+mod pipeline;
+pub mod prompt;
+pub mod queue;
+pub mod repeat;
+mod scroll;
@@ -0,0 +1,162 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex,
+};
+
+use log::{debug, error};
+use once_cell::sync::Lazy;
+
+use crate::{
+    config::{keybinding::DirectionKeybindingsConfig, MouseConfig},
+    hint::move_hints,
+};
+
+use super::{executor::calculate_direction_delta, keyboard::KEYBOARD_STATE, mouse, scroll};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MotionKind {
+    MoveHints,
+    MoveCursor,
+    Drag,
+    Scroll,
+}
+
+// 运动代数, 每次启动或停止时递增, 旧代数的运动循环自行退出
+static MOTION_GENERATION: AtomicU64 = AtomicU64::new(0);
+static ACTIVE_MOTION: Lazy<Mutex<Option<MotionKind>>> = Lazy::new(|| Mutex::new(None));
+// 每次移动时持有, 停止后等待正在进行的移动完成
+static MOTION_TICK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+// 开始连续移动, 直到方向键全部松开; 同类运动已在进行时忽略按键重复, target为滚动的目标位置
+pub fn start(
+    app_handle: tauri::AppHandle,
+    kind: MotionKind,
+    key_bindings: DirectionKeybindingsConfig,
+    mouse_config: MouseConfig,
+    target: Option<scroll::ScrollTarget>,
+) {
+    let generation = match ACTIVE_MOTION.lock() {
+        Ok(mut active) => {
+            if *active == Some(kind) {
+                return;
+            }
+            *active = Some(kind);
+            MOTION_GENERATION.fetch_add(1, Ordering::SeqCst) + 1
+        }
+        Err(_) => {
+            error!("[start] failed to get ACTIVE_MOTION lock");
+            return;
+        }
+    };
+    debug!("[start] start motion: {:?}, generation: {}", kind, generation);
+    tauri::async_runtime::spawn(run_motion(
+        app_handle,
+        kind,
+        key_bindings,
+        mouse_config,
+        target,
+        generation,
+    ));
+}
+
+// 当前的运动代数, 测试中用于判断连续移动是否被停止
+#[cfg(test)]
+pub fn generation() -> u64 {
+    MOTION_GENERATION.load(Ordering::SeqCst)
+}
+
+// 停止当前的连续移动
+pub fn stop() {
+    MOTION_GENERATION.fetch_add(1, Ordering::SeqCst);
+    if let Ok(mut active) = ACTIVE_MOTION.lock() {
+        *active = None;
+    }
+}
+
+// 停止连续移动并等待正在进行的一次移动完成, 队列中的动作执行前调用, 避免移动和点击等动作交错
+pub async fn stop_and_wait() {
+    stop();
+    let _tick = MOTION_TICK.lock().await;
+}
+
+// 读取当前按住的方向键和修饰键, 得到每个step的位移
+fn read_delta(
+    kind: MotionKind,
+    key_bindings: &DirectionKeybindingsConfig,
+    mouse_config: &MouseConfig,
+) -> Option<(i32, i32)> {
+    let state = KEYBOARD_STATE.lock().ok()?;
+    if !state.in_ctrl_session {
+        return None;
+    }
+    let hold_keys: Vec<String> = state
+        .hold_keys
+        .iter()
+        .filter(|(_, pressed)| **pressed)
+        .map(|(key, _)| key.clone())
+        .collect();
+    let step = match kind {
+        MotionKind::MoveHints | MotionKind::MoveCursor => mouse_config.get_translate_step(&hold_keys),
+        MotionKind::Drag => mouse_config.get_drag_step(&hold_keys),
+        MotionKind::Scroll => mouse_config.get_scroll_step(&hold_keys),
+    };
+    let (dx, dy) = calculate_direction_delta(key_bindings, &state, step.x, step.y);
+    if dx == 0 && dy == 0 {
+        return None;
+    }
+    Some((dx, dy))
+}
+
+async fn run_motion(
+    app_handle: tauri::AppHandle,
+    kind: MotionKind,
+    key_bindings: DirectionKeybindingsConfig,
+    mouse_config: MouseConfig,
+    target: Option<scroll::ScrollTarget>,
+    generation: u64,
+) {
+    let motion = mouse_config.motion.clone();
+    let tick = tokio::time::Duration::from_millis(motion.tick.max(1));
+    let dt = tick.as_secs_f64();
+    let mut rate = motion.initial_rate;
+    // 不足一个像素的位移累积到下一次
+    let (mut rest_x, mut rest_y) = (0.0, 0.0);
+    loop {
+        let tick_guard = MOTION_TICK.lock().await;
+        if MOTION_GENERATION.load(Ordering::SeqCst) != generation {
+            break;
+        }
+        let (step_x, step_y) = match read_delta(kind, &key_bindings, &mouse_config) {
+            Some(delta) => delta,
+            None => break,
+        };
+        // 斜向移动时保持速度不变
+        let factor = if step_x != 0 && step_y != 0 {
+            std::f64::consts::FRAC_1_SQRT_2
+        } else {
+            1.0
+        };
+        rest_x += step_x as f64 * rate * dt * factor;
+        rest_y += step_y as f64 * rate * dt * factor;
+        let (dx, dy) = (rest_x.trunc() as i32, rest_y.trunc() as i32);
+        rest_x -= dx as f64;
+        rest_y -= dy as f64;
+        if dx != 0 || dy != 0 {
+            match kind {
+                MotionKind::MoveHints => move_hints(app_handle.clone(), (dx, dy)).await,
+                MotionKind::MoveCursor | MotionKind::Drag => mouse::mouse_move_relative(dx, dy).await,
+                MotionKind::Scroll => scroll::scroll(dx, dy, target, &mouse_config.scroll).await,
+            }
+        }
+        rate = (rate + motion.acceleration * dt).min(motion.max_rate);
+        drop(tick_guard);
+        tokio::time::sleep(tick).await;
+    }
+    debug!("[run_motion] motion: {:?} stopped, generation: {}", kind, generation);
+    // start在持有锁时更新代数, 加锁后再比较, 避免清除新启动的运动
+    if let Ok(mut active) = ACTIVE_MOTION.lock() {
+        if MOTION_GENERATION.load(Ordering::SeqCst) == generation {
+            *active = None;
+        }
+    }
+}
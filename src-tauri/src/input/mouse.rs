@@ -1,14 +1,22 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use crate::monitor::MONITORS_STORAGE;
 use log::{error, info};
 use serde::{Deserialize, Serialize};
-use windows::Win32::UI::Input::KeyboardAndMouse::{
-    mouse_event, MOUSEEVENTF_HWHEEL, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP,
-    MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP, MOUSEEVENTF_MOVE, MOUSEEVENTF_RIGHTDOWN,
-    MOUSEEVENTF_RIGHTUP, MOUSEEVENTF_WHEEL,
-};
-use windows::Win32::UI::WindowsAndMessaging::SetCursorPos;
+use windows::Win32::UI::Input::KeyboardAndMouse::GetDoubleClickTime;
+use windows::Win32::Foundation::POINT;
+use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
 
-fn move_to(monitor: usize, x: i32, y: i32) -> windows::core::Result<()> {
+use crate::config::action::{is_button, ClickSpec, BUTTON_LEFT, BUTTON_MIDDLE, BUTTON_RIGHT};
+
+use super::inject;
+use super::injector::{self, InputEvent};
+
+// 拖拽时左键是否处于按下状态
+static LEFT_BUTTON_HELD: AtomicBool = AtomicBool::new(false);
+
+// 将显示器内的逻辑坐标转换为全局物理坐标
+pub fn to_global_point(monitor: usize, x: i32, y: i32) -> Option<(i32, i32)> {
     // 获取显示器信息
     if let Ok(monitors) = MONITORS_STORAGE.lock() {
         if let Some(monitor_info) = monitors.get(monitor) {
@@ -28,77 +36,97 @@ fn move_to(monitor: usize, x: i32, y: i32) -> windows::core::Result<()> {
             }
 
             // 计算全局坐标
-            let global_x = monitor_info.x + x;
-            let global_y = monitor_info.y + y;
-
-            unsafe { SetCursorPos(global_x, global_y) }
+            Some((monitor_info.x + x, monitor_info.y + y))
         } else {
-            error!("[move_to] monitor not found: {}", monitor);
-            Err(windows::core::Error::from_win32())
+            error!("[to_global_point] monitor not found: {}", monitor);
+            None
         }
     } else {
-        error!("[move_to] failed to get MONITORS_STORAGE lock");
-        Err(windows::core::Error::from_win32())
+        error!("[to_global_point] failed to get MONITORS_STORAGE lock");
+        None
     }
 }
 
-fn move_relative(delta_x: i32, delta_y: i32) -> windows::core::Result<()> {
-    unsafe {
-        mouse_event(MOUSEEVENTF_MOVE, delta_x, delta_y, 0, 0);
-        Ok(())
-    }
+// 将全局物理坐标转换为所在显示器内的逻辑坐标, 与 to_global_point 相反
+pub fn to_monitor_point(x: i32, y: i32) -> Option<(usize, i32, i32)> {
+    let monitors = match MONITORS_STORAGE.lock() {
+        Ok(monitors) => monitors,
+        Err(_) => {
+            error!("[to_monitor_point] failed to get MONITORS_STORAGE lock");
+            return None;
+        }
+    };
+    monitors
+        .iter()
+        .enumerate()
+        .find(|(_, monitor_info)| {
+            x >= monitor_info.x
+                && x < monitor_info.x + monitor_info.width
+                && y >= monitor_info.y
+                && y < monitor_info.y + monitor_info.height
+        })
+        .map(|(index, monitor_info)| {
+            (
+                index,
+                ((x - monitor_info.x) as f64 / monitor_info.scale_factor).round() as i32,
+                ((y - monitor_info.y) as f64 / monitor_info.scale_factor).round() as i32,
+            )
+        })
 }
 
-fn click_left() -> windows::core::Result<()> {
+// 获取光标的全局物理坐标
+pub fn get_cursor_position() -> Option<(i32, i32)> {
+    let mut point = POINT::default();
     unsafe {
-        mouse_event(MOUSEEVENTF_LEFTDOWN, 0, 0, 0, 0);
-        mouse_event(MOUSEEVENTF_LEFTUP, 0, 0, 0, 0);
-        Ok(())
+        if let Err(e) = GetCursorPos(&mut point) {
+            error!("[get_cursor_position] failed: {}", e);
+            return None;
+        }
     }
+    Some((point.x, point.y))
 }
 
-fn click_right() -> windows::core::Result<()> {
-    unsafe {
-        mouse_event(MOUSEEVENTF_RIGHTDOWN, 0, 0, 0, 0);
-        mouse_event(MOUSEEVENTF_RIGHTUP, 0, 0, 0, 0);
-        Ok(())
+fn move_to(monitor: usize, x: i32, y: i32) -> windows::core::Result<()> {
+    match to_global_point(monitor, x, y) {
+        Some((global_x, global_y)) => move_to_global(global_x, global_y),
+        None => Err(windows::core::Error::from_win32()),
     }
 }
 
-fn click_middle() -> windows::core::Result<()> {
-    unsafe {
-        mouse_event(MOUSEEVENTF_MIDDLEDOWN, 0, 0, 0, 0);
-        mouse_event(MOUSEEVENTF_MIDDLEUP, 0, 0, 0, 0);
-        Ok(())
-    }
+fn move_to_global(x: i32, y: i32) -> windows::core::Result<()> {
+    injector::send(&[InputEvent::MoveTo(x, y)])
+}
+
+fn move_relative(delta_x: i32, delta_y: i32) -> windows::core::Result<()> {
+    injector::send(&[InputEvent::MoveRelative(delta_x, delta_y)])
+}
+
+fn click(button: &str) -> windows::core::Result<()> {
+    injector::send(&[
+        InputEvent::ButtonDown(button.to_string()),
+        InputEvent::ButtonUp(button.to_string()),
+    ])
+}
+
+// 连续点击之间的间隔, 取系统双击时间的1/10(默认500毫秒时为50毫秒)
+fn click_interval() -> u64 {
+    let double_click_time = unsafe { GetDoubleClickTime() } as u64;
+    (double_click_time / 10).max(10)
 }
 
 fn start_drag() -> windows::core::Result<()> {
-    unsafe {
-        mouse_event(MOUSEEVENTF_LEFTDOWN, 0, 0, 0, 0);
-        Ok(())
-    }
+    injector::send(&[InputEvent::ButtonDown(BUTTON_LEFT.to_string())])?;
+    LEFT_BUTTON_HELD.store(true, Ordering::SeqCst);
+    Ok(())
 }
 
 fn end_drag() -> windows::core::Result<()> {
-    unsafe {
-        mouse_event(MOUSEEVENTF_LEFTUP, 0, 0, 0, 0);
-        Ok(())
-    }
+    LEFT_BUTTON_HELD.store(false, Ordering::SeqCst);
+    injector::send(&[InputEvent::ButtonUp(BUTTON_LEFT.to_string())])
 }
 
 fn wheel_move(delta_x: i32, delta_y: i32) -> windows::core::Result<()> {
-    unsafe {
-        // 垂直滚动
-        if delta_y != 0 {
-            mouse_event(MOUSEEVENTF_WHEEL, 0, 0, delta_y, 0);
-        }
-        // 水平滚动
-        if delta_x != 0 {
-            mouse_event(MOUSEEVENTF_HWHEEL, 0, 0, delta_x, 0);
-        }
-        Ok(())
-    }
+    injector::send(&[InputEvent::Wheel(delta_x, delta_y)])
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -108,40 +136,105 @@ pub struct Point {
 }
 
 pub async fn mouse_click_left() {
-    if let Err(e) = click_left() {
+    if let Err(e) = click(BUTTON_LEFT) {
         error!("[mouse_click_left] failed: {}", e);
     }
 }
 
 pub async fn mouse_click_right() {
-    if let Err(e) = click_right() {
+    if let Err(e) = click(BUTTON_RIGHT) {
         error!("[mouse_click_right] failed: {}", e);
     }
 }
 
 pub async fn mouse_click_middle() {
-    if let Err(e) = click_middle() {
+    if let Err(e) = click(BUTTON_MIDDLE) {
         error!("[mouse_click_middle] failed: {}", e);
     }
 }
 
 pub async fn mouse_double_click() {
-    if let Err(e) = click_left() {
+    if let Err(e) = click(BUTTON_LEFT) {
         error!("[mouse_double_click] failed: {}", e);
     }
-    // 等待15毫秒，这是Windows默认双击间隔的一小部分
-    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
-    if let Err(e) = click_left() {
+    // 等待系统双击时间的一小部分
+    tokio::time::sleep(tokio::time::Duration::from_millis(click_interval())).await;
+    if let Err(e) = click(BUTTON_LEFT) {
         error!("[mouse_double_click] failed: {}", e);
     }
 }
 
+// 按click点击: 按住修饰键, 按下按键保持press_duration毫秒后抬起, 重复count次
+pub async fn mouse_click(click: &ClickSpec, press_duration: u64) {
+    if !is_button(&click.button) {
+        error!("[mouse_click] unknown button: {}", click.button);
+        return;
+    }
+    let interval = click_interval();
+    inject::key_press(&click.modifiers).await;
+    for i in 0..click.count {
+        if let Err(e) = injector::send(&[InputEvent::ButtonDown(click.button.clone())]) {
+            error!("[mouse_click] failed: {}", e);
+        }
+        if press_duration > 0 {
+            tokio::time::sleep(tokio::time::Duration::from_millis(press_duration)).await;
+        }
+        if let Err(e) = injector::send(&[InputEvent::ButtonUp(click.button.clone())]) {
+            error!("[mouse_click] failed: {}", e);
+        }
+        if i + 1 < click.count {
+            tokio::time::sleep(tokio::time::Duration::from_millis(interval)).await;
+        }
+    }
+    inject::key_release(&click.modifiers).await;
+}
+
 pub async fn mouse_move(monitor: usize, x: i32, y: i32) {
     if let Err(e) = move_to(monitor, x, y) {
         error!("[mouse_move] failed: {}", e);
     }
 }
 
+pub async fn mouse_move_global(x: i32, y: i32) {
+    if let Err(e) = move_to_global(x, y) {
+        error!("[mouse_move_global] failed: {}", e);
+    }
+}
+
+// 以speed像素/秒的速度匀速移动到全局坐标, 用于拖拽时需要悬停响应的程序, speed为0时直接跳到目标位置
+pub async fn mouse_move_global_smooth(x: i32, y: i32, speed: u32) {
+    let (start_x, start_y) = match get_cursor_position() {
+        Some(position) if speed > 0 => position,
+        _ => return mouse_move_global(x, y).await,
+    };
+    let (dx, dy) = ((x - start_x) as f64, (y - start_y) as f64);
+    let distance = (dx * dx + dy * dy).sqrt();
+    // 每10毫秒移动一次
+    let steps = (distance / speed as f64 * 100.0).ceil() as i32;
+    for i in 1..steps {
+        let progress = i as f64 / steps as f64;
+        let step_x = start_x + (dx * progress).round() as i32;
+        let step_y = start_y + (dy * progress).round() as i32;
+        if let Err(e) = move_to_global(step_x, step_y) {
+            error!("[mouse_move_global_smooth] failed: {}", e);
+            return;
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+    }
+    mouse_move_global(x, y).await;
+}
+
+// 估算从当前光标位置匀速移动到全局坐标所需的时间
+pub fn smooth_move_duration(x: i32, y: i32, speed: u32) -> std::time::Duration {
+    let (start_x, start_y) = match get_cursor_position() {
+        Some(position) if speed > 0 => position,
+        _ => return std::time::Duration::ZERO,
+    };
+    let (dx, dy) = ((x - start_x) as f64, (y - start_y) as f64);
+    let distance = (dx * dx + dy * dy).sqrt();
+    std::time::Duration::from_secs_f64(distance / speed as f64)
+}
+
 pub async fn mouse_move_relative(delta_x: i32, delta_y: i32) {
     if let Err(e) = move_relative(delta_x, delta_y) {
         error!("[mouse_move_relative] failed: {}", e);
@@ -160,6 +253,16 @@ pub async fn mouse_drag_end() {
     }
 }
 
+// 释放拖拽中仍按住的左键, 用于结束或取消拖拽, 左键未按住时不发送事件
+pub async fn mouse_release_held() {
+    if LEFT_BUTTON_HELD.load(Ordering::SeqCst) {
+        info!("[mouse_release_held] release held left button");
+        if let Err(e) = end_drag() {
+            error!("[mouse_release_held] failed: {}", e);
+        }
+    }
+}
+
 pub async fn mouse_wheel_move(delta_x: i32, delta_y: i32) {
     if let Err(e) = wheel_move(delta_x, delta_y) {
         error!("[mouse_wheel_move] failed: {}", e);
@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use log::{debug, info};
+
+use crate::{
+    config::action::{ClickSpec, PipelineStep},
+    hint::hide_hints,
+};
+
+use super::{inject, mouse};
+
+// 流水线中等待和输入文本步骤的总时长, 用于放宽流水线的超时时间
+pub fn total_wait(steps: &[PipelineStep], type_interval: u64) -> Duration {
+    steps
+        .iter()
+        .map(|step| match step {
+            PipelineStep::Wait { ms } => Duration::from_millis(*ms),
+            PipelineStep::Type { text } => inject::type_duration(text, type_interval),
+            _ => Duration::ZERO,
+        })
+        .sum()
+}
+
+// 按顺序执行流水线中的步骤, 作为动作队列中的一个动作运行, 随队列一起取消
+// origin为hint所在显示器和显示器内的逻辑坐标, move步骤相对它偏移, 为空时相对光标偏移
+pub async fn run_pipeline(
+    app_handle: tauri::AppHandle,
+    name: String,
+    steps: Vec<PipelineStep>,
+    origin: Option<(usize, i32, i32)>,
+    type_interval: u64,
+    press_duration: u64,
+) {
+    info!("[run_pipeline] start pipeline: {}, steps: {}", name, steps.len());
+    for (index, step) in steps.iter().enumerate() {
+        debug!("[run_pipeline] pipeline: {} step {}: {:?}", name, index, step);
+        match step {
+            PipelineStep::Move { x, y } => {
+                if let Some((monitor_id, hint_x, hint_y)) = origin {
+                    mouse::mouse_move(monitor_id, hint_x + x, hint_y + y).await;
+                } else if *x != 0 || *y != 0 {
+                    mouse::mouse_move_relative(*x, *y).await;
+                }
+            }
+            PipelineStep::Click {
+                button,
+                count,
+                modifiers,
+            } => {
+                let click = ClickSpec {
+                    button: button.to_lowercase(),
+                    count: *count,
+                    modifiers: modifiers.clone(),
+                };
+                mouse::mouse_click(&click, press_duration).await;
+            }
+            PipelineStep::Key { keys } => inject::key_combo(keys).await,
+            PipelineStep::Type { text } => inject::type_text(text, type_interval).await,
+            PipelineStep::Wait { ms } => {
+                tokio::time::sleep(tokio::time::Duration::from_millis(*ms)).await;
+            }
+            PipelineStep::Exit => hide_hints(app_handle.clone()).await,
+        }
+    }
+    info!("[run_pipeline] pipeline: {} finished", name);
+}
@@ -0,0 +1,149 @@
+use log::{debug, error, info};
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+
+use crate::config;
+
+use super::{
+    inject, marks, mouse, queue,
+    repeat::{self, ActionTarget, RepeatableAction},
+};
+
+pub const PROMPT_WINDOW_LABEL: &str = "prompt";
+// 输入框窗口的逻辑尺寸
+const PROMPT_WIDTH: f64 = 400.0;
+const PROMPT_HEIGHT: f64 = 44.0;
+// 输入框显示在hint下方的距离, 物理像素
+const PROMPT_OFFSET_Y: i32 = 12;
+
+// 等待输入的目标: hint所在显示器和显示器内的逻辑坐标, 以及用于重复的操作点
+#[derive(Debug, Clone)]
+struct PromptTarget {
+    position: Option<(usize, i32, i32)>,
+    target: Option<ActionTarget>,
+}
+
+// 输入框打开期间等待输入的目标, 提交或取消后清空
+static PENDING_PROMPT: Lazy<Mutex<Option<PromptTarget>>> = Lazy::new(|| Mutex::new(None));
+
+// 点击position处的元素使其获得焦点, 再逐字符输入文本, position为空时直接输入到当前焦点
+pub async fn click_and_type(position: Option<(usize, i32, i32)>, text: &str, type_interval: u64) {
+    if let Some((monitor_id, x, y)) = position {
+        marks::record_cursor();
+        mouse::mouse_move(monitor_id, x, y).await;
+        mouse::mouse_click_left().await;
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    }
+    inject::type_text(text, type_interval).await;
+}
+
+// 在hint处打开输入框, hints会话需要先结束, 否则键盘钩子会拦截输入
+pub fn open_prompt(
+    app_handle: &tauri::AppHandle,
+    position: Option<(usize, i32, i32)>,
+    target: Option<ActionTarget>,
+) {
+    info!("[open_prompt] open prompt at: {:?}", position);
+    if let Ok(mut pending) = PENDING_PROMPT.lock() {
+        *pending = Some(PromptTarget { position, target });
+    } else {
+        error!("[open_prompt] failed to get PENDING_PROMPT lock");
+        return;
+    }
+
+    let window = match app_handle.get_webview_window(PROMPT_WINDOW_LABEL) {
+        Some(window) => window,
+        None => {
+            let window = tauri::WebviewWindowBuilder::new(
+                app_handle,
+                PROMPT_WINDOW_LABEL,
+                tauri::WebviewUrl::App("prompt.html".into()),
+            )
+            .title(PROMPT_WINDOW_LABEL)
+            .decorations(false)
+            .resizable(false)
+            .skip_taskbar(true)
+            .always_on_top(true)
+            .inner_size(PROMPT_WIDTH, PROMPT_HEIGHT)
+            .visible(false)
+            .build();
+            match window {
+                Ok(window) => window,
+                Err(e) => {
+                    error!("[open_prompt] create prompt window failed: {}", e);
+                    return;
+                }
+            }
+        }
+    };
+
+    let point = position.and_then(|(monitor_id, x, y)| mouse::to_global_point(monitor_id, x, y));
+    let result = match point {
+        Some((x, y)) => window.set_position(tauri::PhysicalPosition::new(x, y + PROMPT_OFFSET_Y)),
+        None => window.center(),
+    };
+    if let Err(e) = result {
+        error!("[open_prompt] set position failed: {}", e);
+    }
+    if let Err(e) = window.emit("show-prompt", ()) {
+        error!("[open_prompt] show-prompt failed: {}", e);
+    }
+    if let Err(e) = window.show() {
+        error!("[open_prompt] show prompt window failed: {}", e);
+    }
+    if let Err(e) = window.set_focus() {
+        error!("[open_prompt] focus prompt window failed: {}", e);
+    }
+}
+
+fn close_prompt(app_handle: &tauri::AppHandle) -> Option<PromptTarget> {
+    if let Some(window) = app_handle.get_webview_window(PROMPT_WINDOW_LABEL) {
+        if let Err(e) = window.hide() {
+            error!("[close_prompt] hide prompt window failed: {}", e);
+        }
+    }
+    match PENDING_PROMPT.lock() {
+        Ok(mut pending) => pending.take(),
+        Err(_) => {
+            error!("[close_prompt] failed to get PENDING_PROMPT lock");
+            None
+        }
+    }
+}
+
+// 输入框提交的文本, 关闭输入框后点击hint并输入
+#[tauri::command]
+pub fn submit_prompt_text_for_frontend(app_handle: tauri::AppHandle, text: String) {
+    let prompt = match close_prompt(&app_handle) {
+        Some(prompt) => prompt,
+        None => {
+            debug!("[submit_prompt_text_for_frontend] no pending prompt");
+            return;
+        }
+    };
+    if text.is_empty() {
+        return;
+    }
+    let type_interval = config::get_config().unwrap().action.type_interval;
+    let timeout = queue::ACTION_TIMEOUT + inject::type_duration(&text, type_interval);
+    queue::enqueue_with_timeout("type_text", timeout, async move {
+        click_and_type(prompt.position, &text, type_interval).await;
+        let action = RepeatableAction::TypeText {
+            text,
+            click: prompt.position.is_some(),
+        };
+        match prompt.target {
+            Some(target) => repeat::record_target(action, target),
+            None => repeat::record_action(action, ""),
+        }
+    });
+}
+
+// 输入框取消或失去焦点时关闭, 不输入文本
+#[tauri::command]
+pub fn cancel_prompt_text_for_frontend(app_handle: tauri::AppHandle) {
+    if close_prompt(&app_handle).is_some() {
+        debug!("[cancel_prompt_text_for_frontend] prompt cancelled");
+    }
+}
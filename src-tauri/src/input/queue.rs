@@ -0,0 +1,242 @@
+use std::{future::Future, pin::Pin, time::Duration};
+
+use log::{debug, error, info};
+use once_cell::sync::Lazy;
+use tokio::sync::{mpsc, watch};
+
+use super::{inject, motion, mouse, scroll};
+
+// 队列中最多积压的动作数, 超出后丢弃新的动作
+const QUEUE_CAPACITY: usize = 32;
+// 单个动作的默认超时时间
+pub const ACTION_TIMEOUT: Duration = Duration::from_secs(5);
+// 只改变hints、网格或模式显示的动作, 不移动光标也不点击, 执行时不打断连续移动和滚动
+const NEUTRAL_ACTIONS: &[&str] = &["enter_mode", "filter_hints_by_state", "move_hints", "sub_grid"];
+
+type ActionFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+struct QueuedAction {
+    name: &'static str,
+    generation: u64,
+    timeout: Duration,
+    future: ActionFuture,
+}
+
+// 取消代数, 每次取消时递增, 旧代数的动作不再执行
+static CANCEL_SENDER: Lazy<watch::Sender<u64>> = Lazy::new(|| watch::channel(0).0);
+
+// 动作队列, 首次使用时启动唯一的执行任务, 保证动作按入队顺序执行
+static ACTION_SENDER: Lazy<mpsc::Sender<QueuedAction>> = Lazy::new(|| {
+    let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+    tauri::async_runtime::spawn(run_actions(receiver));
+    sender
+});
+
+// 将动作加入队列, 使用默认超时时间, 队列已满时丢弃动作并返回false
+pub fn enqueue<F>(name: &'static str, action: F) -> bool
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    enqueue_with_timeout(name, ACTION_TIMEOUT, action)
+}
+
+pub fn enqueue_with_timeout<F>(name: &'static str, timeout: Duration, action: F) -> bool
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let queued = QueuedAction {
+        name,
+        generation: *CANCEL_SENDER.borrow(),
+        timeout,
+        future: Box::pin(action),
+    };
+    match ACTION_SENDER.try_send(queued) {
+        Ok(_) => {
+            debug!("[enqueue] action: {}", name);
+            true
+        }
+        Err(mpsc::error::TrySendError::Full(_)) => {
+            error!("[enqueue] action queue is full, drop action: {}", name);
+            false
+        }
+        Err(mpsc::error::TrySendError::Closed(_)) => {
+            error!("[enqueue] action queue is closed, drop action: {}", name);
+            false
+        }
+    }
+}
+
+// 取消正在执行和尚未执行的动作以及连续移动和滚动, 会话结束时调用
+pub fn cancel() {
+    motion::stop();
+    scroll::stop();
+    CANCEL_SENDER.send_modify(|generation| *generation += 1);
+    debug!("[cancel] cancel queued actions, generation: {}", *CANCEL_SENDER.borrow());
+}
+
+// 被中断的动作可能正处于拖拽或组合键中, 释放仍按住的左键和按键
+async fn release_held() {
+    mouse::mouse_release_held().await;
+    inject::key_release_held().await;
+}
+
+async fn run_actions(mut receiver: mpsc::Receiver<QueuedAction>) {
+    let mut cancel_receiver = CANCEL_SENDER.subscribe();
+    while let Some(action) = receiver.recv().await {
+        let generation = *cancel_receiver.borrow_and_update();
+        if action.generation != generation {
+            debug!("[run_actions] skip cancelled action: {}", action.name);
+            release_held().await;
+            continue;
+        }
+        // 连续移动和平滑滚动不经过队列, 先停止它们再执行移动光标或点击的动作, 连续的滚动动作保留剩余的滚动距离
+        if !NEUTRAL_ACTIONS.contains(&action.name) {
+            motion::stop_and_wait().await;
+            if action.name != scroll::SCROLL_ACTION {
+                scroll::stop_and_wait().await;
+            }
+        }
+        tokio::select! {
+            biased;
+            result = tokio::time::timeout(action.timeout, action.future) => {
+                if result.is_err() {
+                    error!("[run_actions] action: {} timed out after {:?}", action.name, action.timeout);
+                    release_held().await;
+                }
+            }
+            _ = cancel_receiver.changed() => {
+                info!("[run_actions] action: {} cancelled", action.name);
+                release_held().await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::sync::oneshot;
+
+    use super::*;
+    use crate::config::action::BUTTON_LEFT;
+    use crate::input::injector::{set_injector, InputEvent, RecordingInjector};
+
+    // 队列和注入器是全局的, 测试需要依次执行
+    static TEST_LOCK: Mutex<()> = Mutex::new(());
+
+    fn with_recorder<F>(test: impl FnOnce(Arc<RecordingInjector>) -> F)
+    where
+        F: Future<Output = ()>,
+    {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let recorder = Arc::new(RecordingInjector::default());
+        let previous = set_injector(recorder.clone());
+        tauri::async_runtime::block_on(test(recorder));
+        set_injector(previous);
+    }
+
+    // 等待之前入队的动作执行完成
+    async fn flush() {
+        let (sender, receiver) = oneshot::channel();
+        assert!(enqueue("flush", async move {
+            let _ = sender.send(());
+        }));
+        receiver.await.unwrap();
+    }
+
+    fn left_down() -> InputEvent {
+        InputEvent::ButtonDown(BUTTON_LEFT.to_string())
+    }
+
+    fn left_up() -> InputEvent {
+        InputEvent::ButtonUp(BUTTON_LEFT.to_string())
+    }
+
+    #[test]
+    fn runs_actions_in_order() {
+        with_recorder(|recorder| async move {
+            enqueue("move_to_hint", mouse::mouse_move_global(10, 20));
+            enqueue("left_click", mouse::mouse_click_left());
+            enqueue("button_down", mouse::mouse_drag_start());
+            // 拖拽中退出时抬起左键
+            enqueue("exit", mouse::mouse_drag_end());
+            flush().await;
+            assert_eq!(
+                recorder.take(),
+                vec![
+                    InputEvent::MoveTo(10, 20),
+                    left_down(),
+                    left_up(),
+                    left_down(),
+                    left_up(),
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn cancel_drops_queued_actions_and_releases_held() {
+        with_recorder(|recorder| async move {
+            let (started_sender, started) = oneshot::channel();
+            enqueue("drag", async move {
+                inject::key_press(&["LCtrl".to_string()]).await;
+                mouse::mouse_drag_start().await;
+                let _ = started_sender.send(());
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            });
+            enqueue("move_to_hint", mouse::mouse_move_global(10, 20));
+            started.await.unwrap();
+            cancel();
+            flush().await;
+            assert_eq!(
+                recorder.take(),
+                vec![
+                    InputEvent::KeyDown(0xA2),
+                    left_down(),
+                    left_up(),
+                    InputEvent::KeyUp(0xA2),
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn neutral_actions_keep_motion_running() {
+        with_recorder(|_| async move {
+            let before = motion::generation();
+            let (neutral_sender, neutral) = oneshot::channel();
+            enqueue("filter_hints_by_state", async move {
+                let _ = neutral_sender.send(motion::generation());
+            });
+            assert_eq!(neutral.await.unwrap(), before);
+            // 点击前停止连续移动
+            let (click_sender, click) = oneshot::channel();
+            enqueue("left_click", async move {
+                let _ = click_sender.send(motion::generation());
+            });
+            assert!(click.await.unwrap() > before);
+        });
+    }
+
+    #[test]
+    fn drops_actions_beyond_capacity() {
+        with_recorder(|recorder| async move {
+            let (started_sender, started) = oneshot::channel();
+            let (release_sender, release) = oneshot::channel::<()>();
+            enqueue("block", async move {
+                let _ = started_sender.send(());
+                let _ = release.await;
+            });
+            started.await.unwrap();
+            // 执行中的动作已离开队列, 之后最多积压QUEUE_CAPACITY个
+            for _ in 0..QUEUE_CAPACITY {
+                assert!(enqueue("left_click", mouse::mouse_click_left()));
+            }
+            assert!(!enqueue("left_click", mouse::mouse_click_left()));
+            release_sender.send(()).unwrap();
+            flush().await;
+            assert_eq!(recorder.take().len(), QUEUE_CAPACITY * 2);
+        });
+    }
+}
@@ -0,0 +1,256 @@
+use crate::config;
+use crate::config::action::{ClickSpec, REPEAT_TARGET_ELEMENT};
+use crate::element::WINDOWS_UI_ELEMENTS_MAP_STORAGE;
+use crate::hint::hint::get_hint_by_text;
+use log::{debug, error, info};
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::{inject, mouse, pipeline, queue};
+
+// 可以被重复执行的动作
+#[derive(Debug, Clone, PartialEq)]
+pub enum RepeatableAction {
+    MoveTo,
+    LeftClick,
+    RightClick,
+    MiddleClick,
+    DoubleClick,
+    // 按click:命令的按键、修饰键和次数点击
+    Click(ClickSpec),
+    // 按名称执行流水线, 操作点为流水线开始时的hint位置
+    Pipeline(String),
+    // 输入文本, click为true时先点击目标使其获得焦点
+    TypeText { text: String, click: bool },
+    // 发送组合键, click为true时先点击目标
+    SendKeys { combo: String, click: bool },
+}
+
+impl RepeatableAction {
+    // 不点击目标的动作重复时不移动光标, 直接作用于当前焦点
+    fn moves_to_target(&self) -> bool {
+        !matches!(
+            self,
+            RepeatableAction::TypeText { click: false, .. }
+                | RepeatableAction::SendKeys { click: false, .. }
+        )
+    }
+
+    // 超出普通动作的执行时间, 如流水线的等待步骤和逐字符输入
+    fn extra_duration(&self, config: &config::Config) -> Duration {
+        match self {
+            RepeatableAction::Pipeline(name) => config
+                .action
+                .pipelines
+                .get(name)
+                .map(|steps| pipeline::total_wait(steps, config.action.type_interval))
+                .unwrap_or_default(),
+            RepeatableAction::TypeText { text, .. } => {
+                inject::type_duration(text, config.action.type_interval)
+            }
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+// 动作所作用的元素, 用于在当前界面中重新定位该元素
+#[derive(Debug, Clone)]
+struct ElementAnchor {
+    window_handle: i64,
+    control_type: i32,
+    // 元素中心相对窗口左上角的偏移
+    window_offset: (i32, i32),
+    size: (i32, i32),
+    // 操作点相对元素中心的偏移
+    point_offset: (i32, i32),
+}
+
+// 动作的操作点和所作用的元素
+#[derive(Debug, Clone)]
+pub struct ActionTarget {
+    // 操作点的全局物理坐标
+    point: (i32, i32),
+    anchor: Option<ElementAnchor>,
+}
+
+#[derive(Debug, Clone)]
+struct LastAction {
+    action: RepeatableAction,
+    target: ActionTarget,
+}
+
+// 上一次完成的动作
+static LAST_ACTION_STORAGE: Lazy<Mutex<Option<LastAction>>> = Lazy::new(|| Mutex::new(None));
+
+fn get_window_position(window_handle: i64) -> Option<(i32, i32)> {
+    let windows_map = WINDOWS_UI_ELEMENTS_MAP_STORAGE.lock().ok()?;
+    windows_map
+        .keys()
+        .find(|window| window.window_handle == window_handle)
+        .map(|window| (window.x, window.y))
+}
+
+// 取得操作点point和hint所在的元素, hints在动作中可能被隐藏, 需要在动作开始前取得
+pub fn capture_target(hint_text: &str, point: (i32, i32)) -> ActionTarget {
+    let anchor = get_hint_by_text(hint_text).and_then(|hint| {
+        let bounds = hint.bounds?;
+        let (window_x, window_y) = get_window_position(hint.window_handle)?;
+        let center_x = bounds.x + bounds.width / 2;
+        let center_y = bounds.y + bounds.height / 2;
+        Some(ElementAnchor {
+            window_handle: hint.window_handle,
+            control_type: hint.control_type,
+            window_offset: (center_x - window_x, center_y - window_y),
+            size: (bounds.width, bounds.height),
+            point_offset: (point.0 - center_x, point.1 - center_y),
+        })
+    });
+    ActionTarget { point, anchor }
+}
+
+// 记录一次已完成的动作, 操作点取动作完成后的光标位置
+pub fn record_action(action: RepeatableAction, hint_text: &str) {
+    if let Some(point) = mouse::get_cursor_position() {
+        record_target(action, capture_target(hint_text, point));
+    }
+}
+
+// 记录一次已完成的动作, 操作点和元素在动作开始前取得
+pub fn record_target(action: RepeatableAction, target: ActionTarget) {
+    debug!(
+        "[record_target] action: {:?}, point: {:?}, anchor: {:?}",
+        action, target.point, target.anchor
+    );
+    if let Ok(mut last_action) = LAST_ACTION_STORAGE.lock() {
+        *last_action = Some(LastAction { action, target });
+    } else {
+        error!("[record_target] failed to get LAST_ACTION_STORAGE lock");
+    }
+}
+
+// 在当前界面中查找与记录最接近的同类元素, 返回新的操作点
+fn resolve_anchor(anchor: &ElementAnchor) -> Option<(i32, i32)> {
+    let windows_map = WINDOWS_UI_ELEMENTS_MAP_STORAGE.lock().ok()?;
+    let (window, elements) = windows_map
+        .iter()
+        .find(|(window, _)| window.window_handle == anchor.window_handle)?;
+    let expected_x = window.x + anchor.window_offset.0;
+    let expected_y = window.y + anchor.window_offset.1;
+    // 位置与尺寸的总偏差超过元素本身的尺寸, 视为已不是同一个元素
+    let max_score = anchor.size.0 + anchor.size.1;
+    elements
+        .iter()
+        .filter(|element| element.control_type == anchor.control_type)
+        .map(|element| {
+            let distance = (element.x - expected_x).abs() + (element.y - expected_y).abs();
+            let size_diff =
+                (element.width - anchor.size.0).abs() + (element.height - anchor.size.1).abs();
+            (distance + size_diff, element)
+        })
+        .min_by_key(|(score, _)| *score)
+        .filter(|(score, _)| *score <= max_score)
+        .map(|(_, element)| {
+            (
+                element.x + anchor.point_offset.0,
+                element.y + anchor.point_offset.1,
+            )
+        })
+}
+
+// 重复动作在队列中的超时时间, 按上一次动作放宽
+pub fn repeat_timeout() -> Duration {
+    let config = config::get_config().unwrap();
+    let extra = LAST_ACTION_STORAGE
+        .lock()
+        .ok()
+        .and_then(|last_action| {
+            last_action
+                .as_ref()
+                .map(|last_action| last_action.action.extra_duration(&config))
+        })
+        .unwrap_or_default();
+    queue::ACTION_TIMEOUT + extra
+}
+
+// 重复上一次完成的动作, 没有可重复的动作时返回false
+pub async fn repeat_last_action(app_handle: tauri::AppHandle) -> bool {
+    let last_action = match LAST_ACTION_STORAGE.lock() {
+        Ok(last_action) => last_action.clone(),
+        Err(_) => {
+            error!("[repeat_last_action] failed to get LAST_ACTION_STORAGE lock");
+            return false;
+        }
+    };
+    let last_action = match last_action {
+        Some(last_action) => last_action,
+        None => {
+            info!("[repeat_last_action] no action to repeat");
+            return false;
+        }
+    };
+
+    let config = config::get_config().unwrap();
+    let target = &last_action.target;
+    let point = if config.action.repeat_target == REPEAT_TARGET_ELEMENT {
+        match target.anchor.as_ref().and_then(resolve_anchor) {
+            Some(point) => point,
+            None => {
+                debug!("[repeat_last_action] element not resolved, fallback to point: {:?}", target.point);
+                target.point
+            }
+        }
+    } else {
+        target.point
+    };
+    info!(
+        "[repeat_last_action] repeat action: {:?} at: {:?}",
+        last_action.action, point
+    );
+
+    if last_action.action.moves_to_target() {
+        mouse::mouse_move_global(point.0, point.1).await;
+    }
+    match last_action.action {
+        RepeatableAction::MoveTo => {}
+        RepeatableAction::LeftClick => mouse::mouse_click_left().await,
+        RepeatableAction::RightClick => mouse::mouse_click_right().await,
+        RepeatableAction::MiddleClick => mouse::mouse_click_middle().await,
+        RepeatableAction::DoubleClick => mouse::mouse_double_click().await,
+        RepeatableAction::Click(click) => {
+            mouse::mouse_click(&click, config.action.click_press_duration).await
+        }
+        RepeatableAction::Pipeline(name) => {
+            let steps = match config.action.pipelines.get(&name) {
+                Some(steps) => steps.clone(),
+                None => {
+                    error!("[repeat_last_action] pipeline not found: {}", name);
+                    return false;
+                }
+            };
+            pipeline::run_pipeline(
+                app_handle,
+                name,
+                steps,
+                mouse::to_monitor_point(point.0, point.1),
+                config.action.type_interval,
+                config.action.click_press_duration,
+            )
+            .await;
+        }
+        RepeatableAction::TypeText { text, click } => {
+            if click {
+                mouse::mouse_click_left().await;
+                tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+            }
+            inject::type_text(&text, config.action.type_interval).await;
+        }
+        RepeatableAction::SendKeys { combo, click } => {
+            if click {
+                mouse::mouse_click_left().await;
+            }
+            inject::key_combo(&config::keybinding::split_combo(&combo)).await;
+        }
+    }
+    true
+}
@@ -0,0 +1,219 @@
+use std::sync::Mutex;
+
+use log::{debug, error};
+use once_cell::sync::Lazy;
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, POINT, WPARAM},
+    System::Threading::GetCurrentProcessId,
+    UI::WindowsAndMessaging::{
+        GetWindowThreadProcessId, IsChild, PostMessageW, WindowFromPoint, WM_MOUSEHWHEEL,
+        WM_MOUSEWHEEL,
+    },
+};
+
+use crate::config::mouse::ScrollConfig;
+
+use super::{inject, mouse};
+
+// 滚轮消息中表示Shift按下的标志(MK_SHIFT)
+const MK_SHIFT: usize = 0x0004;
+
+// 滚动动作在队列中的名称, 连续的滚动动作之间不停止平滑滚动
+pub const SCROLL_ACTION: &str = "scroll_hints";
+
+// 直接向窗口发送滚动时的目标位置(全局物理坐标)和hint所在的窗口, window_handle为0时按位置查找窗口
+#[derive(Debug, Clone, Copy)]
+pub struct ScrollTarget {
+    pub x: i32,
+    pub y: i32,
+    pub window_handle: i64,
+}
+
+struct ScrollState {
+    // 尚未发送的滚动距离
+    remaining: (f64, f64),
+    // 滚动的目标, 为空时在光标处滚动
+    target: Option<ScrollTarget>,
+    running: bool,
+    // 会话结束时停止滚动, 包括惯性
+    cancelled: bool,
+}
+
+static SCROLL_STATE: Lazy<Mutex<ScrollState>> = Lazy::new(|| {
+    Mutex::new(ScrollState {
+        remaining: (0.0, 0.0),
+        target: None,
+        running: false,
+        cancelled: false,
+    })
+});
+
+// 每次发送滚动时持有, 停止后等待正在进行的发送完成
+static SCROLL_TICK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+// 滚动dx, dy, 平滑滚动时只累加距离, 由后台任务逐步发送
+pub async fn scroll(delta_x: i32, delta_y: i32, target: Option<ScrollTarget>, config: &ScrollConfig) {
+    if !config.smooth {
+        send_wheel(delta_x, delta_y, target, config).await;
+        return;
+    }
+    let start = match SCROLL_STATE.lock() {
+        Ok(mut state) => {
+            state.remaining.0 += delta_x as f64;
+            state.remaining.1 += delta_y as f64;
+            state.target = target;
+            state.cancelled = false;
+            let start = !state.running;
+            state.running = true;
+            start
+        }
+        Err(_) => {
+            error!("[scroll] failed to get SCROLL_STATE lock");
+            return;
+        }
+    };
+    if start {
+        tauri::async_runtime::spawn(run_smooth_scroll(config.clone()));
+    }
+}
+
+// 停止平滑滚动和惯性
+pub fn stop() {
+    if let Ok(mut state) = SCROLL_STATE.lock() {
+        state.remaining = (0.0, 0.0);
+        state.cancelled = state.running;
+    }
+}
+
+// 停止平滑滚动并等待正在进行的一次发送完成
+pub async fn stop_and_wait() {
+    stop();
+    let _tick = SCROLL_TICK.lock().await;
+}
+
+async fn run_smooth_scroll(config: ScrollConfig) {
+    let tick = config.tick.max(1) as f64;
+    // 每次发送剩余距离的一部分, 形成先快后慢的缓动, 约duration毫秒内完成
+    let fraction = 1.0 - (-3.0 * tick / config.duration.max(1) as f64).exp();
+    let decay = if config.inertia > 0 {
+        (-3.0 * tick / config.inertia as f64).exp()
+    } else {
+        0.0
+    };
+    let mut velocity = (0.0, 0.0);
+    // 不足一个单位的滚动累积到下一次
+    let mut carry = (0.0, 0.0);
+    loop {
+        let tick_guard = SCROLL_TICK.lock().await;
+        let (step, target) = match SCROLL_STATE.lock() {
+            Ok(mut state) => {
+                if state.cancelled {
+                    state.cancelled = false;
+                    state.running = false;
+                    break;
+                }
+                let (rx, ry) = state.remaining;
+                let step = if rx.abs() >= 1.0 || ry.abs() >= 1.0 {
+                    let step = (rx * fraction, ry * fraction);
+                    state.remaining = (rx - step.0, ry - step.1);
+                    velocity = step;
+                    step
+                } else {
+                    // 输入结束, 按惯性继续滚动并逐渐减速
+                    velocity = (velocity.0 * decay, velocity.1 * decay);
+                    velocity
+                };
+                if step.0.abs() < 0.5 && step.1.abs() < 0.5 {
+                    state.remaining = (0.0, 0.0);
+                    state.running = false;
+                    break;
+                }
+                (step, state.target)
+            }
+            Err(_) => {
+                error!("[run_smooth_scroll] failed to get SCROLL_STATE lock");
+                return;
+            }
+        };
+        carry = (carry.0 + step.0, carry.1 + step.1);
+        let (dx, dy) = (carry.0.trunc() as i32, carry.1.trunc() as i32);
+        carry = (carry.0 - dx as f64, carry.1 - dy as f64);
+        if dx != 0 || dy != 0 {
+            send_wheel(dx, dy, target, &config).await;
+        }
+        drop(tick_guard);
+        tokio::time::sleep(tokio::time::Duration::from_millis(tick as u64)).await;
+    }
+    debug!("[run_smooth_scroll] smooth scroll finished");
+}
+
+async fn send_wheel(delta_x: i32, delta_y: i32, target: Option<ScrollTarget>, config: &ScrollConfig) {
+    if let Some(target) = target.filter(|_| config.target_window) {
+        if delta_y != 0 {
+            post_wheel(WM_MOUSEWHEEL, delta_y, 0, &target);
+        }
+        if delta_x != 0 {
+            if config.horizontal_fallback {
+                // Shift+垂直滚轮, 向右滚动对应向下滚动
+                post_wheel(WM_MOUSEWHEEL, -delta_x, MK_SHIFT, &target);
+            } else {
+                post_wheel(WM_MOUSEHWHEEL, delta_x, 0, &target);
+            }
+        }
+        return;
+    }
+    if delta_x != 0 && config.horizontal_fallback {
+        let shift = vec!["LShift".to_string()];
+        if delta_y != 0 {
+            mouse::mouse_wheel_move(0, delta_y).await;
+        }
+        inject::key_press(&shift).await;
+        mouse::mouse_wheel_move(0, -delta_x).await;
+        inject::key_release(&shift).await;
+        return;
+    }
+    mouse::mouse_wheel_move(delta_x, delta_y).await;
+}
+
+// 是否为本程序的窗口, 如overlay窗口
+fn is_own_window(hwnd: HWND) -> bool {
+    let mut process_id = 0;
+    unsafe {
+        GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+        process_id == GetCurrentProcessId()
+    }
+}
+
+// 接收滚轮消息的窗口: 目标位置下属于hint所在窗口的子窗口, 否则为hint所在窗口, 跳过overlay窗口
+fn find_target_window(target: &ScrollTarget) -> Option<HWND> {
+    let window = HWND(target.window_handle as *mut _);
+    unsafe {
+        let hwnd = WindowFromPoint(POINT { x: target.x, y: target.y });
+        if target.window_handle != 0 && hwnd != window && !IsChild(window, hwnd).as_bool() {
+            return Some(window);
+        }
+        if hwnd.is_invalid() || is_own_window(hwnd) {
+            return None;
+        }
+        Some(hwnd)
+    }
+}
+
+// 向指定位置下的窗口发送滚轮消息, 不移动光标
+fn post_wheel(message: u32, delta: i32, key_state: usize, target: &ScrollTarget) {
+    let (x, y) = (target.x, target.y);
+    unsafe {
+        let hwnd = match find_target_window(target) {
+            Some(hwnd) => hwnd,
+            None => {
+                error!("[post_wheel] no window at ({}, {})", x, y);
+                return;
+            }
+        };
+        let wparam = WPARAM(((delta as i16 as u16 as usize) << 16) | key_state);
+        let lparam = LPARAM((((y as i16 as u16 as u32) << 16) | (x as i16 as u16 as u32)) as isize);
+        if let Err(e) = PostMessageW(Some(hwnd), message, wparam, lparam) {
+            error!("[post_wheel] failed: {}", e);
+        }
+    }
+}
@@ -8,13 +8,20 @@ pub mod monitor;
 pub mod utils;
 pub mod window;
 
-use config::{get_config_for_frontend, get_hint_types_styles, hint::get_hint_default_style, save_config_for_frontend};
+use config::{
+    get_active_profile_for_frontend, get_config_backups_for_frontend,
+    get_config_errors_for_frontend, get_config_for_frontend, get_config_schema_for_frontend,
+    get_hint_types_styles, hint::get_hint_default_style, keyboard::get_named_keys_for_frontend,
+    restore_config_backup_for_frontend, save_config_for_frontend, switch_profile_for_frontend,
+};
 use hint::{ overlay::OVERLAY_HANDLES_STORAGE, show_hints};
+use input::prompt::{cancel_prompt_text_for_frontend, submit_prompt_text_for_frontend};
 use log::{error, info, warn};
-use std::{panic, str::FromStr};
+use once_cell::sync::Lazy;
+use std::{panic, str::FromStr, sync::Mutex};
 use tauri::{
     image::Image,
-    menu::{MenuBuilder, MenuItemBuilder},
+    menu::{CheckMenuItemBuilder, Menu, MenuBuilder, MenuItemBuilder, SubmenuBuilder},
     tray::{TrayIconBuilder, TrayIconEvent},
     AppHandle, Emitter, Manager, WindowEvent,
 };
@@ -27,6 +34,46 @@ use windows::Win32::UI::WindowsAndMessaging::{
 };
 use windows::Win32::Graphics::Dwm::DwmSetWindowAttribute;
 
+const TRAY_ID: &str = "main";
+const TRAY_TOOLTIP: &str = "ScreenBuoy";
+// 托盘菜单中切换profile的菜单项id前缀, 后接profile名
+const PROFILE_MENU_PREFIX: &str = "profile:";
+
+// 托盘菜单, 有profile时加入切换profile的子菜单并勾选当前profile
+fn build_tray_menu(
+    app_handle: &AppHandle,
+    config: &config::Config,
+) -> tauri::Result<Menu<tauri::Wry>> {
+    let exit_item = MenuItemBuilder::with_id("exit", "Exit").build(app_handle)?;
+    let restart_item = MenuItemBuilder::with_id("restart", "Restart").build(app_handle)?;
+    let settings_item = MenuItemBuilder::with_id("settings", "Settings").build(app_handle)?;
+
+    let mut tray_menu = MenuBuilder::new(app_handle).item(&settings_item);
+    if !config.profiles.is_empty() {
+        let active_profile = config::profile::get_active_profile(config);
+        let mut profile_menu = SubmenuBuilder::new(app_handle, "Profile");
+        for name in config::profile::get_profile_names(config) {
+            let label = if name.is_empty() { "Base" } else { name.as_str() };
+            let item = CheckMenuItemBuilder::with_id(format!("{}{}", PROFILE_MENU_PREFIX, name), label)
+                .checked(name == active_profile)
+                .build(app_handle)?;
+            profile_menu = profile_menu.item(&item);
+        }
+        tray_menu = tray_menu.item(&profile_menu.build()?);
+    }
+    tray_menu.item(&restart_item).item(&exit_item).build()
+}
+
+// 托盘提示中显示当前profile
+fn get_tray_tooltip(config: &config::Config) -> String {
+    let active_profile = config::profile::get_active_profile(config);
+    if active_profile.is_empty() {
+        TRAY_TOOLTIP.to_string()
+    } else {
+        format!("{} - {}", TRAY_TOOLTIP, active_profile)
+    }
+}
+
 pub fn setup_tray(
     app_handle: &AppHandle,
     config: &config::Config,
@@ -36,22 +83,21 @@ pub fn setup_tray(
         return Ok(());
     }
 
-    let exit_item = MenuItemBuilder::with_id("exit", "Exit").build(app_handle)?;
-    let restart_item = MenuItemBuilder::with_id("restart", "Restart").build(app_handle)?;
-    let settings_item = MenuItemBuilder::with_id("settings", "Settings").build(app_handle)?;
-
-    let tray_menu = MenuBuilder::new(app_handle)
-        .item(&settings_item)
-        .item(&restart_item)
-        .item(&exit_item)
-        .build()?;
+    let tray_menu = build_tray_menu(app_handle, config)?;
 
     let tray_icon = Image::from_bytes(include_bytes!("../icons/icon.ico"))?;
 
-    let _tray_icon = TrayIconBuilder::new()
+    let _tray_icon = TrayIconBuilder::with_id(TRAY_ID)
         .menu(&tray_menu)
+        .tooltip(get_tray_tooltip(config))
         .on_menu_event(move |tray_handle, event| {
             let app_handle = tray_handle.app_handle();
+            if let Some(name) = event.id.as_ref().strip_prefix(PROFILE_MENU_PREFIX) {
+                if let Err(e) = switch_profile(app_handle, name) {
+                    error!("[setup_tray] switch profile failed: {:?}", e);
+                }
+                return;
+            }
             match event.id.as_ref() {
                 "exit" => {
                     app_handle.exit(0);
@@ -84,20 +130,76 @@ pub fn setup_tray(
     Ok(())
 }
 
+// 按配置更新托盘菜单和提示, 没有托盘图标时不处理
+fn update_tray(app_handle: &AppHandle, config: &config::Config) {
+    let tray = match app_handle.tray_by_id(TRAY_ID) {
+        Some(tray) => tray,
+        None => return,
+    };
+    match build_tray_menu(app_handle, config) {
+        Ok(menu) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                error!("[update_tray] set menu failed: {}", e);
+            }
+        }
+        Err(e) => error!("[update_tray] build menu failed: {}", e),
+    }
+    if let Err(e) = tray.set_tooltip(Some(get_tray_tooltip(config))) {
+        error!("[update_tray] set tooltip failed: {}", e);
+    }
+}
+
+// 当前注册的全局快捷键, 配置重新加载时整体替换
+#[derive(Default)]
+struct RegisteredShortcuts {
+    main: Option<Shortcut>,
+    repeat: Option<Shortcut>,
+    profile: Option<Shortcut>,
+    modes: Vec<(Shortcut, String)>,
+}
+
+impl RegisteredShortcuts {
+    fn all(&self) -> Vec<Shortcut> {
+        let mut shortcuts = Vec::new();
+        let named = self.main.iter().chain(self.repeat.iter()).chain(self.profile.iter());
+        for shortcut in named.chain(self.modes.iter().map(|(s, _)| s)) {
+            if !shortcuts.contains(shortcut) {
+                shortcuts.push(*shortcut);
+            }
+        }
+        shortcuts
+    }
+}
+
+static REGISTERED_SHORTCUTS: Lazy<Mutex<RegisteredShortcuts>> =
+    Lazy::new(|| Mutex::new(RegisteredShortcuts::default()));
+
+// 串行执行快捷键的注册, 快捷键处理函数会锁定REGISTERED_SHORTCUTS, 调用插件时不能持有它
+static REGISTER_LOCK: Mutex<()> = Mutex::new(());
+
 pub fn setup_shortcut(
     app_handle: &AppHandle,
     config: &config::Config,
     main_window: tauri::WebviewWindow,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let hotkey_buoy = config.keybinding.hotkey_buoy.clone();
-    let main_shortcut: Shortcut = FromStr::from_str(&hotkey_buoy)?;
     let main_window_clone = main_window.clone();
-
-    info!("[setup_shortcut] main_shortcut: {}", hotkey_buoy);
     app_handle.plugin(
         tauri_plugin_global_shortcut::Builder::new()
-            .with_handler(move |_app, shortcut, event| {
-                if shortcut == &main_shortcut {
+            .with_handler(move |app, shortcut, event| {
+                let (is_main, is_repeat, is_profile, mode_name) = {
+                    let shortcuts = REGISTERED_SHORTCUTS.lock().unwrap();
+                    (
+                        shortcuts.main.as_ref() == Some(shortcut),
+                        shortcuts.repeat.as_ref() == Some(shortcut),
+                        shortcuts.profile.as_ref() == Some(shortcut),
+                        shortcuts
+                            .modes
+                            .iter()
+                            .find(|(s, _)| s == shortcut)
+                            .map(|(_, mode_name)| mode_name.clone()),
+                    )
+                };
+                if is_main {
                     match event.state() {
                         ShortcutState::Pressed => {
                             info!("[setup_shortcut] main shortcut pressed");
@@ -108,18 +210,220 @@ pub fn setup_shortcut(
                         }
                         ShortcutState::Released => {}
                     }
+                } else if is_repeat {
+                    if let ShortcutState::Pressed = event.state() {
+                        info!("[setup_shortcut] repeat shortcut pressed");
+                        let app_handle = app.clone();
+                        let timeout = input::repeat::repeat_timeout();
+                        input::queue::enqueue_with_timeout("repeat", timeout, async move {
+                            input::marks::record_cursor();
+                            input::repeat::repeat_last_action(app_handle).await;
+                        });
+                    }
+                } else if is_profile {
+                    if let ShortcutState::Pressed = event.state() {
+                        info!("[setup_shortcut] profile shortcut pressed");
+                        let app_handle = app.clone();
+                        tauri::async_runtime::spawn(async move {
+                            switch_to_next_profile(&app_handle);
+                        });
+                    }
+                } else if let Some(mode_name) = mode_name {
+                    if let ShortcutState::Pressed = event.state() {
+                        info!("[setup_shortcut] mode shortcut pressed: {}", mode_name);
+                        let app_handle = app.clone();
+                        tauri::async_runtime::spawn(async move {
+                            input::keyboard::enter_mode(&app_handle, &mode_name);
+                        });
+                    }
                 }
             })
             .build(),
     )?;
 
-    if let Err(e) = app_handle.global_shortcut().register(main_shortcut) {
-        error!("[✗] 注册快捷键失败: {}", e);
-        return Err(e.into());
+    register_shortcuts(app_handle, config)
+}
+
+// 按配置注册全局快捷键并注销不再使用的, 配置解析或注册失败时保留旧的快捷键
+pub fn register_shortcuts(
+    app_handle: &AppHandle,
+    config: &config::Config,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let hotkey_buoy = config.keybinding.hotkey_buoy.clone();
+    let main_shortcut: Shortcut = FromStr::from_str(&hotkey_buoy)?;
+    let hotkey_repeat = config.keybinding.hotkey_repeat.clone();
+    let repeat_shortcut: Option<Shortcut> = if hotkey_repeat.is_empty() {
+        None
+    } else {
+        Some(FromStr::from_str(&hotkey_repeat)?)
+    };
+    let hotkey_profile = config.profile.hotkey.clone();
+    let profile_shortcut: Option<Shortcut> = if hotkey_profile.is_empty() {
+        None
+    } else {
+        Some(FromStr::from_str(&hotkey_profile)?)
+    };
+
+    let mut mode_shortcuts: Vec<(Shortcut, String)> = Vec::new();
+    for (mode_name, mode) in config.keybinding.modes.iter() {
+        if !mode.hotkey.is_empty() {
+            mode_shortcuts.push((FromStr::from_str(&mode.hotkey)?, mode_name.clone()));
+        }
+    }
+
+    let new_shortcuts = RegisteredShortcuts {
+        main: Some(main_shortcut),
+        repeat: repeat_shortcut,
+        profile: profile_shortcut,
+        modes: mode_shortcuts,
+    };
+
+    let _register_guard = REGISTER_LOCK.lock().unwrap();
+    let old = REGISTERED_SHORTCUTS.lock().unwrap().all();
+    let new = new_shortcuts.all();
+    let global_shortcut = app_handle.global_shortcut();
+    info!("[register_shortcuts] main_shortcut: {}, repeat_shortcut: {}", hotkey_buoy, hotkey_repeat);
+
+    // 先注册新增的快捷键, 失败时注销本次注册的, 原来的快捷键保持可用
+    let mut added = Vec::new();
+    for shortcut in new.iter().filter(|shortcut| !old.contains(shortcut)) {
+        if let Err(e) = global_shortcut.register(*shortcut) {
+            error!("[register_shortcuts] register shortcut failed: {}", e);
+            for shortcut in added {
+                if let Err(e) = global_shortcut.unregister(shortcut) {
+                    warn!("[register_shortcuts] unregister shortcut failed: {}", e);
+                }
+            }
+            return Err(e.into());
+        }
+        added.push(*shortcut);
+    }
+    // 再注销不再使用的快捷键
+    for shortcut in old.iter().filter(|shortcut| !new.contains(shortcut)) {
+        if let Err(e) = global_shortcut.unregister(*shortcut) {
+            warn!("[register_shortcuts] unregister shortcut failed: {}", e);
+        }
+    }
+    *REGISTERED_SHORTCUTS.lock().unwrap() = new_shortcuts;
+    Ok(())
+}
+
+// 应用重新加载的配置: 结束当前会话, 重建派生表和hint文本列表, 重新注册快捷键并通知overlay刷新样式
+pub fn apply_config(app_handle: &AppHandle, config: &config::Config) {
+    info!("[apply_config] apply reloaded config");
+    input::keyboard::end_session(app_handle);
+    config::clear_app_override();
+    config::reload_derived_tables(config);
+    hint::init_hint_text_list_storage();
+    if let Err(e) = register_shortcuts(app_handle, config) {
+        error!("[apply_config] register shortcuts failed: {}", e);
+    }
+    if let Err(e) = set_auto_start(app_handle, config) {
+        error!("[apply_config] set auto start failed: {}", e);
+    }
+    update_tray(app_handle, config);
+    if let Some(window) = app_handle.get_webview_window("main") {
+        if let Err(e) = window.emit("reload-styles", ()) {
+            error!("[apply_config] reload-styles failed: {}", e);
+        }
+    }
+}
+
+// 切换profile并应用配置, 通知设置窗口刷新
+pub fn switch_profile(app_handle: &AppHandle, name: &str) -> Result<(), Vec<config::ConfigError>> {
+    let config = config::switch_profile(name)?;
+    apply_config(app_handle, &config);
+    if let Some(window) = app_handle.get_webview_window("main") {
+        if let Err(e) = window.emit("config-reloaded", config) {
+            error!("[switch_profile] config-reloaded failed: {}", e);
+        }
     }
     Ok(())
 }
 
+// 依次切换到下一个profile, 最后一个之后回到基础配置
+pub fn switch_to_next_profile(app_handle: &AppHandle) {
+    let config = match config::get_base_config() {
+        Some(config) => config,
+        None => return,
+    };
+    let name = config::profile::get_next_profile(&config);
+    if let Err(e) = switch_profile(app_handle, &name) {
+        error!("[switch_to_next_profile] switch to {} failed: {:?}", name, e);
+    }
+}
+
+// 按显示器布局自动选择profile, 没有匹配的规则时保持当前profile
+pub fn select_profile_by_monitors(app_handle: &AppHandle) {
+    let config = match config::get_base_config() {
+        Some(config) => config,
+        None => return,
+    };
+    let monitors = monitor::MONITORS_STORAGE.lock().unwrap().clone();
+    let name = match config::profile::select_by_monitors(&config, &monitors) {
+        Some(name) => name,
+        None => return,
+    };
+    if name == config::profile::get_active_profile(&config) {
+        return;
+    }
+    info!("[select_profile_by_monitors] {} monitor(s), select profile: {}", monitors.len(), name);
+    if let Err(e) = switch_profile(app_handle, &name) {
+        error!("[select_profile_by_monitors] switch to {} failed: {:?}", name, e);
+    }
+}
+
+// 正在运行的配置文件监听线程, 退出时停止
+static CONFIG_WATCHER: Lazy<Mutex<Option<config::ConfigWatcher>>> = Lazy::new(|| Mutex::new(None));
+
+// 监听配置文件的外部修改, 重新加载后通知设置窗口刷新, 出错时通知设置窗口显示错误
+pub fn setup_config_watcher(app_handle: &AppHandle) {
+    stop_config_watcher();
+    let app_handle = app_handle.clone();
+    let watcher = config::watch_config_file(move |result| {
+        let window = match app_handle.get_webview_window("main") {
+            Some(window) => window,
+            None => return,
+        };
+        let errors = match result {
+            Ok(config) => {
+                apply_config(&app_handle, &config);
+                if let Err(e) = window.emit("config-reloaded", config) {
+                    error!("[setup_config_watcher] config-reloaded failed: {}", e);
+                }
+                Vec::new()
+            }
+            Err(errors) => errors,
+        };
+        if let Err(e) = window.emit("config-errors", errors) {
+            error!("[setup_config_watcher] config-errors failed: {}", e);
+        }
+    });
+    *CONFIG_WATCHER.lock().unwrap() = Some(watcher);
+}
+
+// 停止监听配置文件
+pub fn stop_config_watcher() {
+    let watcher = CONFIG_WATCHER.lock().unwrap().take();
+    if let Some(watcher) = watcher {
+        watcher.stop();
+    }
+}
+
+// 显示设置窗口并在其中提示配置错误
+pub fn report_config_error(app_handle: &AppHandle, config_error: config::ConfigError) {
+    error!("[report_config_error] {}", config_error);
+    config::add_config_error(config_error);
+    if let Some(window) = app_handle.get_webview_window("main") {
+        if let Err(e) = window.show() {
+            error!("[report_config_error] show main window failed: {}", e);
+        }
+        if let Err(e) = window.emit("config-errors", config::get_config_errors()) {
+            error!("[report_config_error] config-errors failed: {}", e);
+        }
+    }
+}
+
 pub fn set_auto_start(
     app_handle: &AppHandle,
     config: &config::Config,
@@ -186,7 +490,16 @@ pub fn create_app_builder() -> tauri::Builder<tauri::Wry> {
             get_hint_default_style,
             get_hint_types_styles,
             get_config_for_frontend,
+            get_config_errors_for_frontend,
             save_config_for_frontend,
+            get_config_backups_for_frontend,
+            restore_config_backup_for_frontend,
+            get_active_profile_for_frontend,
+            switch_profile_for_frontend,
+            get_config_schema_for_frontend,
+            get_named_keys_for_frontend,
+            submit_prompt_text_for_frontend,
+            cancel_prompt_text_for_frontend,
         ])
         .on_window_event(|window, event| {
             if let WindowEvent::CloseRequested { api, .. } = event {
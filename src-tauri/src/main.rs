@@ -7,30 +7,44 @@ use screen_buoy::hint::create_overlay_windows;
 use screen_buoy::utils::logger::init_logger;
 use screen_buoy::input;
 use screen_buoy::monitor::monitor;
+use screen_buoy::report_config_error;
+use screen_buoy::select_profile_by_monitors;
 use screen_buoy::set_auto_start;
+use screen_buoy::setup_config_watcher;
 use screen_buoy::setup_panic_handler;
 use screen_buoy::setup_shortcut;
 use screen_buoy::setup_tray;
+use screen_buoy::stop_config_watcher;
 use tauri::Manager;
 use windows::Win32::System::Com::{CoInitializeEx, CoUninitialize, COINIT_APARTMENTTHREADED};
-use std::env;
 
 fn main() {
-    // 自动切换到 exe 所在目录, 为了解决windows自动启动时workding directory读取不到配置文件的问题
-    if !cfg!(debug_assertions) {
-        if let Ok(exe_path) = std::env::current_exe() {
-            if let Some(exe_dir) = exe_path.parent() {
-                let _ = std::env::set_current_dir(exe_dir);
-            }
+    // ScreenBuoy.exe schema [<file>]: 输出配置的JSON Schema后退出, 不创建, 升级或备份配置文件
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() == Some(config::schema::SCHEMA_COMMAND) {
+        config::schema::attach_parent_console();
+        let path = args.next().filter(|arg| !arg.starts_with("--"));
+        if let Err(e) = config::schema::write_schema(&config::read_config(), path.as_deref()) {
+            eprintln!("failed to write schema: {}", e);
+            std::process::exit(1);
         }
+        return;
     }
+
     // Initialize config first
     config::init_config();
     let config = config::get_config().unwrap();
-    let config_for_manage = config.clone();
 
     // Initialize logger
-    let _ = init_logger(config.system.logging_level.clone());
+    let _ = init_logger(
+        config.system.logging_level.clone(),
+        config::location::get_log_dir(),
+    );
+    info!("config file: {}", config::location::get_config_file());
+    let config_errors = config::get_config_errors();
+    for e in config_errors.iter() {
+        error!("[✗] config error: {}", e);
+    }
     
     // Initialize COM
     unsafe {
@@ -57,8 +71,8 @@ fn main() {
         // Setup main window
         let main_window = app_handle.get_webview_window("main").unwrap();
 
-        // Handle window visibility
-        if config.system.start_in_tray {
+        // Handle window visibility, show settings when config has errors
+        if config.system.start_in_tray && config_errors.is_empty() {
             if let Err(e) = main_window.hide() {
                 error!("[✗] hide main window failed: {}", e);
             }
@@ -92,10 +106,26 @@ fn main() {
         create_overlay_windows(&app_handle);
         info!("[✓] overlay windows created");
 
-        // Setup shortcuts
-        setup_shortcut(&app_handle, &config, main_window.clone())
-            .expect("Failed to setup shortcuts");
-        info!("[✓] shortcuts setup");
+        // Setup shortcuts, a hotkey that cannot be registered is shown in the settings window
+        match setup_shortcut(&app_handle, &config, main_window.clone()) {
+            Ok(()) => info!("[✓] shortcuts setup"),
+            Err(e) => report_config_error(
+                &app_handle,
+                config::ConfigError::invalid(
+                    &config::location::get_config_file(),
+                    "keybinding",
+                    &format!("failed to register hotkeys: {}", e),
+                ),
+            ),
+        }
+
+        // Select profile by monitor layout
+        select_profile_by_monitors(&app_handle);
+        info!("[✓] profile selected");
+
+        // Watch config file for external edits
+        setup_config_watcher(&app_handle);
+        info!("[✓] config watcher started");
 
         // set autostart
         set_auto_start(&app_handle, &config).expect("Failed to setup auto start");
@@ -110,11 +140,12 @@ fn main() {
         .build(tauri::generate_context!("Tauri.toml"))
         .expect("error while building tauri application");
 
-    app.manage(config_for_manage);
-
     app.run(|_app_handle, event| {
         if let tauri::RunEvent::Exit = event {
             info!("application is exiting, cleaning up resources...");
+            stop_config_watcher();
+            info!("[✓] config watcher stopped");
+
             input::hook::cleanup();
             info!("[✓] keyboard hook cleaned up");
 
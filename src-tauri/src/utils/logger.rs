@@ -1,9 +1,11 @@
+use std::path::PathBuf;
+
 use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
 
-pub fn init_logger(log_level: String) -> Result<(), Box<dyn std::error::Error>> {
+pub fn init_logger(log_level: String, log_dir: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
     Logger::try_with_str(log_level)?
         .log_to_stdout()
-        .log_to_file(FileSpec::default().directory("logs").basename("screen-buoy"))
+        .log_to_file(FileSpec::default().directory(log_dir).basename("screen-buoy"))
         .rotate(
             Criterion::Size(3_000_000),
             Naming::Numbers,
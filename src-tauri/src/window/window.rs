@@ -6,10 +6,14 @@ use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 use std::ptr;
 use windows::core::BOOL;
-use windows::Win32::Foundation::{HWND, LPARAM, RECT};
+use windows::core::PWSTR;
+use windows::Win32::Foundation::{CloseHandle, HWND, LPARAM, RECT};
+use windows::Win32::System::Threading::{
+    OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+};
 use windows::Win32::UI::Input::KeyboardAndMouse::IsWindowEnabled;
 use windows::Win32::UI::WindowsAndMessaging::{
-    EnumWindows, GetClassNameW, GetClientRect, GetTopWindow, GetWindow, GetWindowLongW, GetWindowRect, GetWindowTextW, IsIconic, IsWindowVisible, GWL_EXSTYLE, GW_HWNDNEXT, WS_EX_TOOLWINDOW, WS_EX_TRANSPARENT
+    EnumWindows, GetClassNameW, GetClientRect, GetForegroundWindow, GetTopWindow, GetWindow, GetWindowLongW, GetWindowRect, GetWindowTextW, GetWindowThreadProcessId, IsIconic, IsWindowVisible, GWL_EXSTYLE, GW_HWNDNEXT, WS_EX_TOOLWINDOW, WS_EX_TRANSPARENT
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,6 +44,73 @@ impl PartialEq for WindowElement {
 
 impl Eq for WindowElement {}
 
+// 前台窗口及其所属程序
+#[derive(Debug, Clone)]
+pub struct AppWindow {
+    pub window_handle: i64,
+    pub rect: Rect,
+    pub class_name: String,
+    pub title: String,
+    // 进程的可执行文件名, 如 notepad.exe
+    pub process_name: String,
+}
+
+pub fn get_foreground_window() -> Option<AppWindow> {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0.is_null() {
+            debug!("[get_foreground_window] no foreground window");
+            return None;
+        }
+        let mut rect = RECT::default();
+        if let Err(e) = GetWindowRect(hwnd, &mut rect) {
+            error!("[get_foreground_window] GetWindowRect failed: {:?}", e);
+            return None;
+        }
+        let mut title = [0u16; 512];
+        let mut class_name = [0u16; 512];
+        let title_len = GetWindowTextW(hwnd, &mut title).max(0) as usize;
+        let class_name_len = GetClassNameW(hwnd, &mut class_name).max(0) as usize;
+        Some(AppWindow {
+            window_handle: hwnd.0 as i64,
+            rect: Rect::new(rect.left, rect.top, rect.right - rect.left, rect.bottom - rect.top),
+            class_name: String::from_utf16_lossy(&class_name[..class_name_len]),
+            title: String::from_utf16_lossy(&title[..title_len]),
+            process_name: get_process_name(hwnd)?,
+        })
+    }
+}
+
+// 获取窗口所属进程的可执行文件名
+pub fn get_process_name(hwnd: HWND) -> Option<String> {
+    unsafe {
+        let mut process_id = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+        let process = match OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id) {
+            Ok(process) => process,
+            Err(e) => {
+                error!("[get_process_name] OpenProcess {} failed: {:?}", process_id, e);
+                return None;
+            }
+        };
+        let mut buffer = [0u16; 1024];
+        let mut size = buffer.len() as u32;
+        let result = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut size,
+        );
+        let _ = CloseHandle(process);
+        if let Err(e) = result {
+            error!("[get_process_name] QueryFullProcessImageNameW failed: {:?}", e);
+            return None;
+        }
+        let path = String::from_utf16_lossy(&buffer[..size as usize]);
+        path.rsplit('\\').next().map(|name| name.to_lowercase())
+    }
+}
+
 pub fn get_all_windows() -> Vec<WindowElement> {
     let mut windows: Vec<WindowElement> = Vec::new();
     unsafe {
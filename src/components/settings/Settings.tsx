@@ -1,7 +1,8 @@
 import React, { useEffect, useState } from 'react';
-import { Tabs, Form, message, Spin, Select, Space } from 'antd';
+import { Tabs, Form, message, Spin, Select, Space, Alert } from 'antd';
 import { invoke } from '@tauri-apps/api/core';
-import type { Config } from '../../types/config';
+import { listen } from '@tauri-apps/api/event';
+import type { Config, ConfigError } from '../../types/config';
 import { HintSettings } from './sections/HintSettings';
 import { KeyboardSettings } from './sections/KeyboardSettings';
 import { MouseSettings } from './sections/MouseSettings';
@@ -20,12 +21,33 @@ const Settings: React.FC = () => {
   const [form] = Form.useForm<Config>();
   const [loading, setLoading] = useState(true);
   const [initialConfig, setInitialConfig] = useState<Config | null>(null);
-  const [availableKeysState, setAvailableKeysState] = useState<Record<string, number> | undefined>(undefined);
+  const [availableKeysState, setAvailableKeysState] = useState<string[] | undefined>(undefined);
+  const [configErrors, setConfigErrors] = useState<ConfigError[]>([]);
+  const [profileNames, setProfileNames] = useState<string[]>([]);
+  const [activeProfile, setActiveProfile] = useState<string>('');
 
   useEffect(() => {
     loadConfig();
   }, []);
 
+  // 配置文件被外部修改并重新加载后刷新表单
+  useEffect(() => {
+    const unlistenReload = listen<Config>('config-reloaded', (event) => {
+      console.log("[Settings.tsx] Config reloaded from file");
+      form.setFieldsValue(event.payload);
+      setAvailableKeysState(event.payload.keyboard?.available_key);
+      setProfileNames(Object.keys(event.payload.profiles ?? {}));
+      invoke<string>('get_active_profile_for_frontend').then(setActiveProfile);
+    });
+    const unlistenErrors = listen<ConfigError[]>('config-errors', (event) => {
+      setConfigErrors(event.payload);
+    });
+    return () => {
+      unlistenReload.then(unlisten => unlisten());
+      unlistenErrors.then(unlisten => unlisten());
+    };
+  }, [form]);
+
   const loadConfig = async () => {
     try {
       setLoading(true);
@@ -33,6 +55,9 @@ const Settings: React.FC = () => {
       console.log("Config loaded from backend:", JSON.stringify(config, null, 2));
       setInitialConfig(config);
       setAvailableKeysState(config.keyboard?.available_key);
+      setProfileNames(Object.keys(config.profiles ?? {}));
+      setActiveProfile(await invoke<string>('get_active_profile_for_frontend'));
+      setConfigErrors(await invoke<ConfigError[]>('get_config_errors_for_frontend'));
     } catch (err) {
       message.error('Failed to load config');
       console.error('Failed to load config:', err);
@@ -45,8 +70,13 @@ const Settings: React.FC = () => {
     try {
       console.log("[Settings.tsx] Saving config:", JSON.stringify(values, null, 2));
       await invoke('save_config_for_frontend', { config: values });
+      setConfigErrors([]);
     } catch (err) {
-      message.error('Failed to save config');
+      if (Array.isArray(err)) {
+        setConfigErrors(err as ConfigError[]);
+      } else {
+        message.error('Failed to save config');
+      }
       console.error('Failed to save config:', err);
     }
   }, 500);
@@ -73,6 +103,31 @@ const Settings: React.FC = () => {
     i18nInstance.changeLanguage(lng);
   };
 
+  // 切换后后端会发送config-reloaded事件刷新表单
+  const handleProfileChange = async (name: string) => {
+    try {
+      await invoke('switch_profile_for_frontend', { name });
+      setActiveProfile(name);
+    } catch (err) {
+      if (Array.isArray(err)) {
+        setConfigErrors(err as ConfigError[]);
+      }
+      message.error(t('settings.switchProfileFailed'));
+      console.error('Failed to switch profile:', err);
+    }
+  };
+
+  const formatConfigError = (error: ConfigError) => {
+    switch (error.kind) {
+      case 'read':
+        return `${error.path}: ${error.message}`;
+      case 'parse':
+        return `${error.path}${error.line > 0 ? `:${error.line}:${error.column}` : ''}${error.field ? ` \`${error.field}\`` : ''}: ${error.message}`;
+      case 'invalid':
+        return `${error.path}: \`${error.field}\`: ${error.message}`;
+    }
+  };
+
   if (loading || !initialConfig) {
     return <div style={{ display: 'flex', justifyContent: 'center', alignItems: 'center', height: '100vh' }}><Spin size="large" /></div>;
   }
@@ -86,6 +141,17 @@ const Settings: React.FC = () => {
           <Text type="secondary" style={{ display: 'block' }}>{t('settings.restart')}</Text>
         </div>
         <Space>
+          {profileNames.length > 0 && (
+            <Select
+              value={activeProfile}
+              style={{ width: 160 }}
+              onChange={handleProfileChange}
+              options={[
+                { value: '', label: t('settings.baseProfile') },
+                ...profileNames.map((name) => ({ value: name, label: name })),
+              ]}
+            />
+          )}
           <Select
             value={i18nInstance.language}
             style={{ width: 100 }}
@@ -97,6 +163,21 @@ const Settings: React.FC = () => {
           />
         </Space>
       </div>
+      {configErrors.length > 0 && (
+        <Alert
+          type="error"
+          showIcon
+          style={{ marginBottom: 16 }}
+          message={t('settings.configErrors')}
+          description={
+            <ul style={{ margin: 0, paddingLeft: 20 }}>
+              {configErrors.map((error, index) => (
+                <li key={index}>{formatConfigError(error)}</li>
+              ))}
+            </ul>
+          }
+        />
+      )}
       <Form
         form={form}
         layout="vertical"
@@ -1,5 +1,5 @@
 import React, { useState, useEffect } from 'react';
-import { Form, Space, Typography, Spin, Input, Button, InputNumber, Collapse, Select } from 'antd';
+import { Form, Space, Typography, Spin, Input, Button, InputNumber, Collapse, Select, Switch } from 'antd';
 import type { NamePath } from 'antd/es/form/interface';
 import type { Config, HintType } from '../../../types/config';
 import { MinusCircleOutlined, PlusOutlined } from '@ant-design/icons';
@@ -484,6 +484,52 @@ export const HintSettings: React.FC<HintSettingsProps> = ({ onValuesChange }) =>
         </Form.Item>
       </Space>
 
+      {/* Sub Grid Section */}
+      <Paragraph className="config-section-title">{t('hint.subGrid')}</Paragraph>
+      <Space direction="vertical" style={{ width: '100%' }}>
+        <Form.Item
+          label={t('hint.rows')}
+          tooltip={t('hint.subGridRowsTooltip')}
+          name={['hint', 'sub_grid', 'rows']}
+          style={{ marginBottom: 8 }}
+        >
+          <InputNumber min={1} style={{ width: 100 }} />
+        </Form.Item>
+        <Form.Item
+          label={t('hint.columns')}
+          tooltip={t('hint.subGridColumnsTooltip')}
+          name={['hint', 'sub_grid', 'columns']}
+          style={{ marginBottom: 8 }}
+        >
+          <InputNumber min={1} style={{ width: 100 }} />
+        </Form.Item>
+        <Form.Item
+          label={t('hint.subGridChars')}
+          tooltip={t('hint.subGridCharsTooltip')}
+          name={['hint', 'sub_grid', 'chars']}
+          style={{ marginBottom: 8 }}
+        >
+          <Select mode="tags" style={{ width: '100%' }} />
+        </Form.Item>
+        <Form.Item
+          label={t('hint.subGridUseBounds')}
+          tooltip={t('hint.subGridUseBoundsTooltip')}
+          name={['hint', 'sub_grid', 'use_bounds']}
+          valuePropName="checked"
+          style={{ marginBottom: 8 }}
+        >
+          <Switch />
+        </Form.Item>
+        <Form.Item
+          label={t('hint.subGridRadius')}
+          tooltip={t('hint.subGridRadiusTooltip')}
+          name={['hint', 'sub_grid', 'radius']}
+          style={{ marginBottom: 0 }}
+        >
+          <InputNumber min={1} style={{ width: 100 }} />
+        </Form.Item>
+      </Space>
+
       {/* Default Style Section */}
       <Form.Item
         label={t('hint.defaultStyle')}
@@ -1,5 +1,5 @@
 import React from 'react';
-import { Form, Space, Typography, Spin, Input, Select } from 'antd';
+import { Form, Space, Typography, Spin, Input, InputNumber, Select, Switch } from 'antd';
 import type { Config } from '../../../types/config';
 import { useKeyOptions } from '../../../hooks/useKeyOptions';
 import '../../../styles/global.css';
@@ -9,7 +9,7 @@ const { Title, Text, Paragraph } = Typography;
 
 interface KeybindingSettingsProps {
   onValuesChange?: (changedValues: any, allValues: Config) => void;
-  availableKeysData?: Record<string, number>;
+  availableKeysData?: string[];
 }
 
 export const KeybindingSettings: React.FC<KeybindingSettingsProps> = ({ onValuesChange, availableKeysData }) => {
@@ -28,6 +28,61 @@ export const KeybindingSettings: React.FC<KeybindingSettingsProps> = ({ onValues
         <Input />
       </Form.Item>
 
+      <Form.Item layout="horizontal"
+        label={t('keybinding.repeatHotkey')}
+        tooltip={t('keybinding.repeatHotkeyTooltip')}
+        name={['keybinding', 'hotkey_repeat']}
+      >
+        <Input />
+      </Form.Item>
+
+      <Form.Item layout="horizontal"
+        label={t('keybinding.repeatTarget')}
+        tooltip={t('keybinding.repeatTargetTooltip')}
+        name={['action', 'repeat_target']}
+      >
+        <Select
+          style={{ width: 200 }}
+          options={[
+            { value: 'point', label: t('keybinding.repeatTargetPoint') },
+            { value: 'element', label: t('keybinding.repeatTargetElement') },
+          ]}
+        />
+      </Form.Item>
+
+      <Form.Item layout="horizontal"
+        label={t('keybinding.typeInterval')}
+        tooltip={t('keybinding.typeIntervalTooltip')}
+        name={['action', 'type_interval']}
+      >
+        <InputNumber min={0} style={{ width: 100 }} />
+      </Form.Item>
+
+      <Form.Item layout="horizontal"
+        label={t('keybinding.clickPressDuration')}
+        tooltip={t('keybinding.clickPressDurationTooltip')}
+        name={['action', 'click_press_duration']}
+      >
+        <InputNumber min={0} style={{ width: 100 }} />
+      </Form.Item>
+
+      <Form.Item layout="horizontal"
+        label={t('keybinding.jumpListSize')}
+        tooltip={t('keybinding.jumpListSizeTooltip')}
+        name={['action', 'marks', 'jump_list_size']}
+      >
+        <InputNumber min={1} style={{ width: 100 }} />
+      </Form.Item>
+
+      <Form.Item layout="horizontal"
+        label={t('keybinding.persistMarks')}
+        tooltip={t('keybinding.persistMarksTooltip')}
+        name={['action', 'marks', 'persist']}
+        valuePropName="checked"
+      >
+        <Switch />
+      </Form.Item>
+
       {/* Global Shortcuts Section */}
       <Paragraph className="config-section-title">{t('keybinding.globalKeybindings')}</Paragraph>
       <Form.Item
@@ -100,6 +155,104 @@ export const KeybindingSettings: React.FC<KeybindingSettingsProps> = ({ onValues
         />
       </Form.Item>
 
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.repeat')}
+        tooltip={t('keybinding.repeatTooltip')}
+        name={['keybinding', 'global', 'repeat']}
+      >
+        <Select
+          mode="tags"
+          style={{ width: '100%' }}
+          options={keyOptions}
+          placeholder={t('keybinding.selectKeys')}
+        />
+      </Form.Item>
+
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.buttonUp')}
+        tooltip={t('keybinding.buttonUpTooltip')}
+        name={['keybinding', 'global', 'button_up']}
+      >
+        <Select
+          mode="tags"
+          style={{ width: '100%' }}
+          options={keyOptions}
+          placeholder={t('keybinding.selectKeys')}
+        />
+      </Form.Item>
+
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.setMark')}
+        tooltip={t('keybinding.setMarkTooltip')}
+        name={['keybinding', 'global', 'set_mark']}
+      >
+        <Select
+          mode="tags"
+          style={{ width: '100%' }}
+          options={keyOptions}
+          placeholder={t('keybinding.selectKeys')}
+        />
+      </Form.Item>
+
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.jumpMark')}
+        tooltip={t('keybinding.jumpMarkTooltip')}
+        name={['keybinding', 'global', 'jump_mark']}
+      >
+        <Select
+          mode="tags"
+          style={{ width: '100%' }}
+          options={keyOptions}
+          placeholder={t('keybinding.selectKeys')}
+        />
+      </Form.Item>
+
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.jumpBack')}
+        tooltip={t('keybinding.jumpBackTooltip')}
+        name={['keybinding', 'global', 'jump_back']}
+      >
+        <Select
+          mode="tags"
+          style={{ width: '100%' }}
+          options={keyOptions}
+          placeholder={t('keybinding.selectKeys')}
+        />
+      </Form.Item>
+
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.jumpForward')}
+        tooltip={t('keybinding.jumpForwardTooltip')}
+        name={['keybinding', 'global', 'jump_forward']}
+      >
+        <Select
+          mode="tags"
+          style={{ width: '100%' }}
+          options={keyOptions}
+          placeholder={t('keybinding.selectKeys')}
+        />
+      </Form.Item>
+
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.capturePin')}
+        tooltip={t('keybinding.capturePinTooltip')}
+        name={['keybinding', 'global', 'capture_pin']}
+      >
+        <Select
+          mode="tags"
+          style={{ width: '100%' }}
+          options={keyOptions}
+          placeholder={t('keybinding.selectKeys')}
+        />
+      </Form.Item>
+
       {/* Move (Translate) Directions for Global */}
       <Form.Item
         layout="horizontal"
@@ -262,6 +415,146 @@ export const KeybindingSettings: React.FC<KeybindingSettingsProps> = ({ onValues
         />
       </Form.Item>
 
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.repeat')}
+        tooltip={t('keybinding.repeatTooltip')}
+        name={['keybinding', 'at_hint', 'repeat']}
+      >
+        <Select
+          mode="tags"
+          style={{ width: '100%' }}
+          options={keyOptions}
+          placeholder={t('keybinding.selectKeys')}
+        />
+      </Form.Item>
+
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.buttonDown')}
+        tooltip={t('keybinding.buttonDownTooltip')}
+        name={['keybinding', 'at_hint', 'button_down']}
+      >
+        <Select
+          mode="tags"
+          style={{ width: '100%' }}
+          options={keyOptions}
+          placeholder={t('keybinding.selectKeys')}
+        />
+      </Form.Item>
+
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.buttonUp')}
+        tooltip={t('keybinding.buttonUpTooltip')}
+        name={['keybinding', 'at_hint', 'button_up']}
+      >
+        <Select
+          mode="tags"
+          style={{ width: '100%' }}
+          options={keyOptions}
+          placeholder={t('keybinding.selectKeys')}
+        />
+      </Form.Item>
+
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.selectParent')}
+        tooltip={t('keybinding.selectParentTooltip')}
+        name={['keybinding', 'at_hint', 'select_parent']}
+      >
+        <Select
+          mode="tags"
+          style={{ width: '100%' }}
+          options={keyOptions}
+          placeholder={t('keybinding.selectKeys')}
+        />
+      </Form.Item>
+
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.selectChild')}
+        tooltip={t('keybinding.selectChildTooltip')}
+        name={['keybinding', 'at_hint', 'select_child']}
+      >
+        <Select
+          mode="tags"
+          style={{ width: '100%' }}
+          options={keyOptions}
+          placeholder={t('keybinding.selectKeys')}
+        />
+      </Form.Item>
+
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.selectNext')}
+        tooltip={t('keybinding.selectNextTooltip')}
+        name={['keybinding', 'at_hint', 'select_next']}
+      >
+        <Select
+          mode="tags"
+          style={{ width: '100%' }}
+          options={keyOptions}
+          placeholder={t('keybinding.selectKeys')}
+        />
+      </Form.Item>
+
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.selectPrev')}
+        tooltip={t('keybinding.selectPrevTooltip')}
+        name={['keybinding', 'at_hint', 'select_prev']}
+      >
+        <Select
+          mode="tags"
+          style={{ width: '100%' }}
+          options={keyOptions}
+          placeholder={t('keybinding.selectKeys')}
+        />
+      </Form.Item>
+
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.subGrid')}
+        tooltip={t('keybinding.subGridTooltip')}
+        name={['keybinding', 'at_hint', 'sub_grid']}
+      >
+        <Select
+          mode="tags"
+          style={{ width: '100%' }}
+          options={keyOptions}
+          placeholder={t('keybinding.selectKeys')}
+        />
+      </Form.Item>
+
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.setMarkAtHint')}
+        tooltip={t('keybinding.setMarkAtHintTooltip')}
+        name={['keybinding', 'at_hint', 'set_mark']}
+      >
+        <Select
+          mode="tags"
+          style={{ width: '100%' }}
+          options={keyOptions}
+          placeholder={t('keybinding.selectKeys')}
+        />
+      </Form.Item>
+
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.promptText')}
+        tooltip={t('keybinding.promptTextTooltip')}
+        name={['keybinding', 'at_hint', 'prompt_text']}
+      >
+        <Select
+          mode="tags"
+          style={{ width: '100%' }}
+          options={keyOptions}
+          placeholder={t('keybinding.selectKeys')}
+        />
+      </Form.Item>
+
       {/* Move (Translate) Directions for At Hint */}
       <Form.Item
         layout="horizontal"
@@ -295,6 +588,39 @@ export const KeybindingSettings: React.FC<KeybindingSettingsProps> = ({ onValues
       >
         <Select mode="tags" style={{ width: '100%' }} options={keyOptions} placeholder={t('keybinding.selectKeys')} />
       </Form.Item>
+      {/* Navigate Directions for At Hint */}
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.navigateUp')}
+        tooltip={t('keybinding.navigateUpTooltip')}
+        name={['keybinding', 'at_hint', 'navigate', 'up']}
+      >
+        <Select mode="tags" style={{ width: '100%' }} options={keyOptions} placeholder={t('keybinding.selectKeys')} />
+      </Form.Item>
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.navigateDown')}
+        tooltip={t('keybinding.navigateDownTooltip')}
+        name={['keybinding', 'at_hint', 'navigate', 'down']}
+      >
+        <Select mode="tags" style={{ width: '100%' }} options={keyOptions} placeholder={t('keybinding.selectKeys')} />
+      </Form.Item>
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.navigateLeft')}
+        tooltip={t('keybinding.navigateLeftTooltip')}
+        name={['keybinding', 'at_hint', 'navigate', 'left']}
+      >
+        <Select mode="tags" style={{ width: '100%' }} options={keyOptions} placeholder={t('keybinding.selectKeys')} />
+      </Form.Item>
+      <Form.Item
+        layout="horizontal"
+        label={t('keybinding.navigateRight')}
+        tooltip={t('keybinding.navigateRightTooltip')}
+        name={['keybinding', 'at_hint', 'navigate', 'right']}
+      >
+        <Select mode="tags" style={{ width: '100%' }} options={keyOptions} placeholder={t('keybinding.selectKeys')} />
+      </Form.Item>
       {/* Drag Directions for At Hint */}
       <Form.Item
         layout="horizontal"
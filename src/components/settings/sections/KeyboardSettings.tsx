@@ -1,9 +1,10 @@
 import React, { useEffect, useState } from 'react';
-import { Form, Space, Typography, Button, Input, List, Spin, Collapse, Select, InputNumber, Tooltip } from 'antd';
+import { Form, Space, Typography, Button, Input, List, Spin, Collapse, Select, Tooltip } from 'antd';
 import { MinusCircleOutlined, PlusOutlined } from '@ant-design/icons';
 import type { Config, KeyboardConfig, LeftRightConfig } from '../../../types/config';
 import '../../../styles/global.css';
 import { useTranslation } from 'react-i18next';
+import { invoke } from '@tauri-apps/api/core';
 
 const { Title, Paragraph } = Typography;
 const { Panel } = Collapse;
@@ -13,11 +14,6 @@ interface KeyboardSettingsProps {
 }
 
 // Define types for the array format used for local state management
-interface AvailableKeyItem {
-  id: number; // Add an id for stable key in React list
-  keyName: string;
-  virtualKey: number;
-}
 interface LeftRightMapItem {
   id: number; // Add an id for stable key
   keyName: string;
@@ -26,12 +22,6 @@ interface LeftRightMapItem {
 }
 
 // Helper function to convert Record to Array
-const recordToAvailableKeyList = (record: Record<string, number>): AvailableKeyItem[] => {
-  return Object.entries(record)
-    .map(([keyName, virtualKey], index) => ({ id: index, keyName, virtualKey }))
-    .sort((a, b) => a.virtualKey - b.virtualKey);
-};
-
 const recordToLeftRightMapList = (record: Record<string, LeftRightConfig>): LeftRightMapItem[] => {
   return Object.entries(record).map(([keyName, value], index) => ({
     id: index,
@@ -42,15 +32,6 @@ const recordToLeftRightMapList = (record: Record<string, LeftRightConfig>): Left
 };
 
 // Helper function to convert Array back to Record
-const availableKeyListToRecord = (list: AvailableKeyItem[]): Record<string, number> => {
-  return list.reduce((acc, { keyName, virtualKey }) => {
-    if (keyName) { // Ensure keyName is not empty
-      acc[keyName] = virtualKey;
-    }
-    return acc;
-  }, {} as Record<string, number>);
-};
-
 const leftRightMapListToRecord = (list: LeftRightMapItem[]): Record<string, LeftRightConfig> => {
   return list.reduce((acc, { keyName, left, right }) => {
     if (keyName) { // Ensure keyName is not empty
@@ -63,17 +44,19 @@ const leftRightMapListToRecord = (list: LeftRightMapItem[]): Record<string, Left
 export const KeyboardSettings: React.FC<KeyboardSettingsProps> = ({ onValuesChange }) => {
   const { t } = useTranslation();
   const form = Form.useFormInstance<Config>();
-  const [availableKeyList, setAvailableKeyList] = useState<AvailableKeyItem[]>([]);
+  const [namedKeys, setNamedKeys] = useState<string[]>([]);
   const [leftRightMapList, setLeftRightMapList] = useState<LeftRightMapItem[]>([]);
+  const availableKeys = Form.useWatch(['keyboard', 'available_key'], form) as string[] | undefined;
 
-  // Special keys that shouldn't be removed or have their key name changed
-  const specialKeys = ["HintKey", "HintRightKey", "HintLeftKey"];
+  // 可以列入 available_key 的按键名, 字母, 数字和符号键总是可用
+  useEffect(() => {
+    invoke<string[]>('get_named_keys_for_frontend').then(setNamedKeys);
+  }, []);
 
   // Load initial data from form into local state
   useEffect(() => {
     const initialKeyboardConfig = form.getFieldValue('keyboard') as KeyboardConfig;
-    if (initialKeyboardConfig && initialKeyboardConfig.available_key && initialKeyboardConfig.map_left_right) {
-      setAvailableKeyList(recordToAvailableKeyList(initialKeyboardConfig.available_key));
+    if (initialKeyboardConfig && initialKeyboardConfig.map_left_right) {
       setLeftRightMapList(recordToLeftRightMapList(initialKeyboardConfig.map_left_right));
     }
     // Intentionally not depending on the lists themselves to avoid loops on initial set
@@ -81,15 +64,11 @@ export const KeyboardSettings: React.FC<KeyboardSettingsProps> = ({ onValuesChan
   }, [form]); // Rerun if form instance changes
 
   // Function to update form state whenever local list state changes
-  const updateFormState = (newAvailableKeys?: AvailableKeyItem[], newLeftRightMap?: LeftRightMapItem[]) => {
+  const updateFormState = (newLeftRightMap?: LeftRightMapItem[]) => {
     const currentKeyboardConfig = form.getFieldValue('keyboard') as KeyboardConfig;
     const updatedKeyboardConfig: Partial<KeyboardConfig> = {};
-    let changedFieldName: 'available_key' | 'map_left_right' | null = null;
+    let changedFieldName: 'map_left_right' | null = null;
 
-    if (newAvailableKeys) {
-      updatedKeyboardConfig.available_key = availableKeyListToRecord(newAvailableKeys);
-      changedFieldName = 'available_key';
-    }
     if (newLeftRightMap) {
       updatedKeyboardConfig.map_left_right = leftRightMapListToRecord(newLeftRightMap);
       changedFieldName = 'map_left_right';
@@ -115,43 +94,13 @@ export const KeyboardSettings: React.FC<KeyboardSettingsProps> = ({ onValuesChan
     }
   };
 
-  // --- Handlers for Available Keys ---
-  const handleAvailableKeyNameChange = (id: number, newKeyName: string) => {
-    const newList = availableKeyList.map(item =>
-      item.id === id ? { ...item, keyName: newKeyName.trim() } : item
-    );
-    setAvailableKeyList(newList);
-    updateFormState(newList, undefined);
-  };
-
-  const handleAvailableKeyValueChange = (id: number, newVirtualKey: number | null) => {
-    const newList = availableKeyList.map(item =>
-      item.id === id ? { ...item, virtualKey: newVirtualKey ?? 0 } : item
-    );
-    setAvailableKeyList(newList);
-    updateFormState(newList, undefined);
-  };
-
-  const handleAddAvailableKey = () => {
-    const newId = availableKeyList.length ? Math.max(...availableKeyList.map(i => i.id)) + 1 : 0;
-    const newList = [...availableKeyList, { id: newId, keyName: `NewKey${newId}`, virtualKey: 0 }];
-    setAvailableKeyList(newList);
-    updateFormState(newList, undefined);
-  };
-
-  const handleRemoveAvailableKey = (id: number) => {
-    const newList = availableKeyList.filter(item => item.id !== id);
-    setAvailableKeyList(newList);
-    updateFormState(newList, undefined);
-  };
-
   // --- Handlers for Left Right Map ---
   const handleLeftRightKeyNameChange = (id: number, newKeyName: string) => {
     const newList = leftRightMapList.map(item =>
       item.id === id ? { ...item, keyName: newKeyName.trim() } : item
     );
     setLeftRightMapList(newList);
-    updateFormState(undefined, newList);
+    updateFormState(newList);
   };
 
   const handleLeftRightLeftChange = (id: number, newLeftValue: string) => {
@@ -159,7 +108,7 @@ export const KeyboardSettings: React.FC<KeyboardSettingsProps> = ({ onValuesChan
       item.id === id ? { ...item, left: newLeftValue || null } : item
     );
     setLeftRightMapList(newList);
-    updateFormState(undefined, newList);
+    updateFormState(newList);
   };
 
   const handleLeftRightRightChange = (id: number, newRightValue: string) => {
@@ -167,27 +116,31 @@ export const KeyboardSettings: React.FC<KeyboardSettingsProps> = ({ onValuesChan
       item.id === id ? { ...item, right: newRightValue || null } : item
     );
     setLeftRightMapList(newList);
-    updateFormState(undefined, newList);
+    updateFormState(newList);
   };
 
   const handleAddLeftRightMap = () => {
     const newId = leftRightMapList.length ? Math.max(...leftRightMapList.map(i => i.id)) + 1 : 0;
     const newList = [...leftRightMapList, { id: newId, keyName: `NewMap${newId}`, left: null, right: null }];
     setLeftRightMapList(newList);
-    updateFormState(undefined, newList);
+    updateFormState(newList);
   };
 
   const handleRemoveLeftRightMap = (id: number) => {
     const newList = leftRightMapList.filter(item => item.id !== id);
     setLeftRightMapList(newList);
-    updateFormState(undefined, newList);
+    updateFormState(newList);
   };
 
 
-  // Options for Propagation Modifier Select (needs available keys from local state now)
-  const keyOptionsForSelect = availableKeyList.map(item => ({
-    label: item.keyName,
-    value: item.keyName
+  // Options for Propagation Modifier Select
+  const keyOptionsForSelect = (availableKeys ?? []).map(key => ({
+    label: key,
+    value: key
+  }));
+  const namedKeyOptions = namedKeys.map(key => ({
+    label: key,
+    value: key
   }));
 
   return (
@@ -213,9 +166,9 @@ export const KeyboardSettings: React.FC<KeyboardSettingsProps> = ({ onValuesChan
             <List
               bordered
               dataSource={[
-                { keyName: 'HintKey', virtualKey: '-', tooltip: t('keyboard.hintKeyTooltip') },
-                { keyName: 'HintRightKey', virtualKey: '-', tooltip: t('keyboard.hintRightKeyTooltip') },
-                { keyName: 'HintLeftKey', virtualKey: '-', tooltip: t('keyboard.hintLeftKeyTooltip') },
+                { keyName: 'HintKey', tooltip: t('keyboard.hintKeyTooltip') },
+                { keyName: 'HintRightKey', tooltip: t('keyboard.hintRightKeyTooltip') },
+                { keyName: 'HintLeftKey', tooltip: t('keyboard.hintLeftKeyTooltip') },
               ]}
               rowKey={item => item.keyName}
               renderItem={item => (
@@ -230,63 +183,19 @@ export const KeyboardSettings: React.FC<KeyboardSettingsProps> = ({ onValuesChan
                         placeholder={t('keyboard.keyName')}
                       />
                     </Tooltip>
-                    <Input
-                      addonBefore={t('keyboard.vk')}
-                      value={item.virtualKey}
-                      disabled
-                      style={{ width: '150px', fontWeight: 'bold' }}
-                      placeholder={t('keyboard.vk')}
-                    />
                   </Space>
                 </List.Item>
               )}
             />
           </Space>
-          <List
-            bordered
-            dataSource={availableKeyList}
-            rowKey="id"
-            renderItem={(item) => {
-              const isSpecial = specialKeys.includes(item.keyName);
-              return (
-                <List.Item key={item.id}>
-                  <Space align="center">
-                    <Input
-                      addonBefore={t('keyboard.key')}
-                      value={item.keyName}
-                      onChange={(e) => handleAvailableKeyNameChange(item.id, e.target.value)}
-                      disabled={isSpecial}
-                      style={{ width: '250px' }}
-                      placeholder={t('keyboard.keyName')}
-                    />
-                    <InputNumber
-                      addonBefore={t('keyboard.vk')}
-                      value={item.virtualKey}
-                      onChange={(value) => handleAvailableKeyValueChange(item.id, value)}
-                      style={{ width: '150px' }}
-                      placeholder={t('keyboard.vk')}
-                    />
-                    {!isSpecial && (
-                      <Button
-                        danger
-                        icon={<MinusCircleOutlined />}
-                        onClick={() => handleRemoveAvailableKey(item.id)}
-                      />
-                    )}
-                  </Space>
-                </List.Item>
-              );
-            }}
-          />
-          <Button
-            type="dashed"
-            onClick={handleAddAvailableKey}
-            block
-            icon={<PlusOutlined />}
-            style={{ marginTop: '10px' }}
-          >
-            {t('keyboard.addAvailableKey')}
-          </Button>
+          <Form.Item name={['keyboard', 'available_key']} noStyle>
+            <Select
+              mode="multiple"
+              placeholder={t('keyboard.selectKeys')}
+              style={{ width: '100%' }}
+              options={namedKeyOptions}
+            />
+          </Form.Item>
         </Panel>
       </Collapse>
 
@@ -1,5 +1,5 @@
 import React, { useState, useEffect } from 'react';
-import { Form, InputNumber, List, Space, Spin, Typography, Select, Button, Flex, Tooltip } from 'antd';
+import { Form, InputNumber, List, Space, Spin, Typography, Select, Button, Flex, Tooltip, Switch } from 'antd';
 import { Config, MouseStep, MouseConfig } from '@/types/config';
 import { MinusCircleOutlined, PlusOutlined } from '@ant-design/icons';
 import { useKeyOptions } from '../../../hooks/useKeyOptions';
@@ -10,7 +10,7 @@ const { Title, Text, Paragraph } = Typography;
 
 interface MouseSettingsProps {
   onValuesChange?: (changedValues: any, allValues: Config) => void;
-  availableKeysData?: Record<string, number>;
+  availableKeysData?: string[];
 }
 
 export const MouseSettings: React.FC<MouseSettingsProps> = ({ onValuesChange, availableKeysData }) => {
@@ -245,6 +245,98 @@ export const MouseSettings: React.FC<MouseSettingsProps> = ({ onValuesChange, av
       {renderStepList('translate', 'mouse.movementStep')}
       {renderStepList('scroll', 'mouse.scrollStep')}
       {renderStepList('drag', 'mouse.dragStep')}
+      <Form.Item
+        label={t('mouse.motionEnabled')}
+        name={['mouse', 'motion', 'enabled']}
+        valuePropName="checked"
+        tooltip={t('mouse.motionEnabledTooltip')}
+        layout="horizontal"
+      >
+        <Switch />
+      </Form.Item>
+      <Form.Item
+        label={t('mouse.motionTick')}
+        name={['mouse', 'motion', 'tick']}
+        tooltip={t('mouse.motionTickTooltip')}
+        layout="horizontal"
+      >
+        <InputNumber min={1} style={{ width: 100 }} />
+      </Form.Item>
+      <Form.Item
+        label={t('mouse.motionInitialRate')}
+        name={['mouse', 'motion', 'initial_rate']}
+        tooltip={t('mouse.motionInitialRateTooltip')}
+        layout="horizontal"
+      >
+        <InputNumber min={0} style={{ width: 100 }} />
+      </Form.Item>
+      <Form.Item
+        label={t('mouse.motionMaxRate')}
+        name={['mouse', 'motion', 'max_rate']}
+        tooltip={t('mouse.motionMaxRateTooltip')}
+        layout="horizontal"
+      >
+        <InputNumber min={0} style={{ width: 100 }} />
+      </Form.Item>
+      <Form.Item
+        label={t('mouse.motionAcceleration')}
+        name={['mouse', 'motion', 'acceleration']}
+        tooltip={t('mouse.motionAccelerationTooltip')}
+        layout="horizontal"
+      >
+        <InputNumber min={0} style={{ width: 100 }} />
+      </Form.Item>
+      <Form.Item
+        label={t('mouse.scrollSmooth')}
+        name={['mouse', 'scroll', 'smooth']}
+        valuePropName="checked"
+        tooltip={t('mouse.scrollSmoothTooltip')}
+        layout="horizontal"
+      >
+        <Switch />
+      </Form.Item>
+      <Form.Item
+        label={t('mouse.scrollDuration')}
+        name={['mouse', 'scroll', 'duration']}
+        tooltip={t('mouse.scrollDurationTooltip')}
+        layout="horizontal"
+      >
+        <InputNumber min={1} style={{ width: 100 }} />
+      </Form.Item>
+      <Form.Item
+        label={t('mouse.scrollTick')}
+        name={['mouse', 'scroll', 'tick']}
+        tooltip={t('mouse.scrollTickTooltip')}
+        layout="horizontal"
+      >
+        <InputNumber min={1} style={{ width: 100 }} />
+      </Form.Item>
+      <Form.Item
+        label={t('mouse.scrollInertia')}
+        name={['mouse', 'scroll', 'inertia']}
+        tooltip={t('mouse.scrollInertiaTooltip')}
+        layout="horizontal"
+      >
+        <InputNumber min={0} style={{ width: 100 }} />
+      </Form.Item>
+      <Form.Item
+        label={t('mouse.scrollHorizontalFallback')}
+        name={['mouse', 'scroll', 'horizontal_fallback']}
+        valuePropName="checked"
+        tooltip={t('mouse.scrollHorizontalFallbackTooltip')}
+        layout="horizontal"
+      >
+        <Switch />
+      </Form.Item>
+      <Form.Item
+        label={t('mouse.scrollTargetWindow')}
+        name={['mouse', 'scroll', 'target_window']}
+        valuePropName="checked"
+        tooltip={t('mouse.scrollTargetWindowTooltip')}
+        layout="horizontal"
+      >
+        <Switch />
+      </Form.Item>
     </Space>
   );
 }; 
\ No newline at end of file
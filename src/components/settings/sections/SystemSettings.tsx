@@ -1,7 +1,8 @@
-import React from 'react';
-import { Form, Switch, Space, Typography, Spin, Button, Select } from 'antd';
+import React, { useEffect, useState } from 'react';
+import { Form, Switch, Space, Typography, Spin, Button, Select, Popconfirm, Input, message } from 'antd';
 import { relaunch, exit } from '@tauri-apps/plugin-process';
-import { Config } from '@/types/config';
+import { invoke } from '@tauri-apps/api/core';
+import { Config, ConfigBackup, ConfigError } from '@/types/config';
 import '../../../styles/global.css';
 import { useTranslation } from 'react-i18next';
 
@@ -13,6 +14,37 @@ interface SystemSettingsProps {
 
 export const SystemSettings: React.FC<SystemSettingsProps> = ({ onValuesChange }) => {
   const { t } = useTranslation();
+  const [backups, setBackups] = useState<ConfigBackup[]>([]);
+  const [selectedBackup, setSelectedBackup] = useState<number | undefined>(undefined);
+
+  const loadBackups = async () => {
+    try {
+      setBackups(await invoke<ConfigBackup[]>('get_config_backups_for_frontend'));
+    } catch (error) {
+      console.error('[loadBackups] Failed to load config backups:', error);
+    }
+  };
+
+  useEffect(() => {
+    loadBackups();
+  }, []);
+
+  // 恢复后后端会发送config-reloaded事件刷新表单
+  const handleRestoreBackup = async () => {
+    if (selectedBackup === undefined) {
+      return;
+    }
+    try {
+      await invoke('restore_config_backup_for_frontend', { index: selectedBackup });
+      message.success(t('system.restoreBackupSuccess'));
+      setSelectedBackup(undefined);
+      loadBackups();
+    } catch (error) {
+      const errors = Array.isArray(error) ? (error as ConfigError[]) : [];
+      message.error(t('system.restoreBackupFailed', { count: errors.length }));
+      console.error('[handleRestoreBackup] Failed to restore config backup:', error);
+    }
+  };
 
   const handleRestart = async () => {
     try {
@@ -85,6 +117,61 @@ export const SystemSettings: React.FC<SystemSettingsProps> = ({ onValuesChange }
         <Switch />
       </Form.Item>
 
+      <Form.Item noStyle shouldUpdate={(prev, next) => prev.profiles !== next.profiles}>
+        {({ getFieldValue }) => (
+          <Form.Item
+            label={t('system.defaultProfile')}
+            name={['profile', 'default']}
+            tooltip={t('system.defaultProfileTooltip')}
+            layout="horizontal"
+          >
+            <Select
+              style={{ width: '200px' }}
+              options={[
+                { value: '', label: t('settings.baseProfile') },
+                ...Object.keys(getFieldValue('profiles') ?? {}).map((name) => ({ value: name, label: name })),
+              ]}
+            />
+          </Form.Item>
+        )}
+      </Form.Item>
+
+      <Form.Item
+        label={t('system.profileHotkey')}
+        name={['profile', 'hotkey']}
+        tooltip={t('system.profileHotkeyTooltip')}
+        layout="horizontal"
+      >
+        <Input style={{ width: '200px' }} />
+      </Form.Item>
+
+      <Form.Item
+        label={t('system.configBackup')}
+        tooltip={t('system.configBackupTooltip')}
+        layout="horizontal"
+      >
+        <Space>
+          <Select
+            style={{ width: '240px' }}
+            value={selectedBackup}
+            onChange={setSelectedBackup}
+            onDropdownVisibleChange={(open) => open && loadBackups()}
+            placeholder={t('system.configBackupPlaceholder')}
+            options={backups.map((backup) => ({
+              value: backup.index,
+              label: new Date(backup.modified).toLocaleString(),
+            }))}
+          />
+          <Popconfirm
+            title={t('system.restoreBackupConfirm')}
+            onConfirm={handleRestoreBackup}
+            disabled={selectedBackup === undefined}
+          >
+            <Button disabled={selectedBackup === undefined}>{t('system.restoreBackup')}</Button>
+          </Popconfirm>
+        </Space>
+      </Form.Item>
+
       <Space>
         <Button type="primary" onClick={handleRestart}>{t('system.restart')}</Button>
         <Button danger onClick={handleExit}>{t('system.exit')}</Button>
@@ -10,6 +10,8 @@ export interface HintConfig {
   style: string;
   types: Record<string, HintType>;
   grid: GridConfig;
+  sub_grid: SubGridConfig;
+  pins: PinConfig[];
 }
 
 export interface GridConfig {
@@ -20,13 +22,31 @@ export interface GridConfig {
   hint_type: string;
 }
 
+export interface PinConfig {
+  x: number;
+  y: number;
+  monitor: number;
+  window_class: string;
+  window_title: string;
+  label: string;
+  hint_type: string;
+}
+
+export interface SubGridConfig {
+  rows: number;
+  columns: number;
+  chars: string[];
+  use_bounds: boolean;
+  radius: number;
+}
+
 export interface LeftRightConfig {
   left?: string | null;
   right?: string | null;
 }
 
 export interface KeyboardConfig {
-  available_key: Record<string, number>;
+  available_key: string[];
   propagation_modifier: string[];
   map_left_right: Record<string, LeftRightConfig>;
 }
@@ -43,8 +63,27 @@ export interface MouseStepConfig {
   drag: MouseStep[];
 }
 
+export interface MotionConfig {
+  enabled: boolean;
+  tick: number;
+  initial_rate: number;
+  max_rate: number;
+  acceleration: number;
+}
+
+export interface ScrollConfig {
+  smooth: boolean;
+  duration: number;
+  tick: number;
+  inertia: number;
+  horizontal_fallback: boolean;
+  target_window: boolean;
+}
+
 export interface MouseConfig {
   step: MouseStepConfig;
+  motion: MotionConfig;
+  scroll: ScrollConfig;
 }
 
 export interface DirectionKeybindingsConfig {
@@ -61,6 +100,15 @@ export interface GlobalKeybindingConfig {
   hold_at_hint: string[];
   exit: string[];
   translate: DirectionKeybindingsConfig;
+  repeat: string[];
+  pipelines: Record<string, string[]>;
+  send_keys: Record<string, string[]>;
+  button_up: string[];
+  set_mark: string[];
+  jump_mark: string[];
+  jump_back: string[];
+  jump_forward: string[];
+  capture_pin: string[];
 }
 
 export interface AtHintKeybindingConfig {
@@ -76,12 +124,37 @@ export interface AtHintKeybindingConfig {
   translate: DirectionKeybindingsConfig;
   drag: DirectionKeybindingsConfig;
   scroll: DirectionKeybindingsConfig;
+  repeat: string[];
+  pipelines: Record<string, string[]>;
+  type_text: Record<string, string[]>;
+  send_keys: Record<string, string[]>;
+  click_send_keys: Record<string, string[]>;
+  click: Record<string, string[]>;
+  button_down: string[];
+  button_up: string[];
+  navigate: DirectionKeybindingsConfig;
+  select_parent: string[];
+  select_child: string[];
+  select_next: string[];
+  select_prev: string[];
+  sub_grid: string[];
+  set_mark: string[];
+  prompt_text: string[];
+}
+
+export interface ModeConfig {
+  enter: string[];
+  hotkey: string;
+  bindings: Record<string, string[]>;
+  indicator: string;
 }
 
 export interface KeybindingConfig {
   global: GlobalKeybindingConfig;
   at_hint: AtHintKeybindingConfig;
   hotkey_buoy: string;
+  hotkey_repeat: string;
+  modes: Record<string, ModeConfig>;
 }
 
 export interface SystemConfig {
@@ -97,11 +170,75 @@ export interface UiAutomationConfig {
   cache_ttl: number;
 }
 
+export type PipelineStep =
+  | { step: 'move'; x?: number; y?: number }
+  | { step: 'click'; button?: 'left' | 'right' | 'middle' | 'x1' | 'x2'; count?: number; modifiers?: string[] }
+  | { step: 'key'; keys: string[] }
+  | { step: 'type'; text: string }
+  | { step: 'wait'; ms: number }
+  | { step: 'exit' };
+
+export interface DragConfig {
+  drop_on_label: boolean;
+  speed: number;
+}
+
+export interface MarksConfig {
+  jump_list_size: number;
+  persist: boolean;
+}
+
+export interface ActionConfig {
+  repeat_target: 'point' | 'element';
+  pipelines: Record<string, PipelineStep[]>;
+  snippets: Record<string, string>;
+  type_interval: number;
+  click_press_duration: number;
+  drag: DragConfig;
+  marks: MarksConfig;
+}
+
+export interface ProfileRule {
+  profile: string;
+  monitors: number;
+  resolutions: string[];
+}
+
+export interface ProfileConfig {
+  default: string;
+  hotkey: string;
+  auto_select: ProfileRule[];
+}
+
+export interface AppOverride {
+  process_name: string;
+  class_name: string;
+  title: string;
+  elements: boolean;
+  hint: Record<string, any>;
+  keybinding: Record<string, any>;
+}
+
 export interface Config {
+  config_version: number;
   hint: HintConfig;
   keybinding: KeybindingConfig;
   mouse: MouseConfig;
   keyboard: KeyboardConfig;
   system: SystemConfig;
   ui_automation: UiAutomationConfig;
-} 
\ No newline at end of file
+  action: ActionConfig;
+  profile: ProfileConfig;
+  profiles: Record<string, Record<string, any>>;
+  app: AppOverride[];
+}
+
+export type ConfigError =
+  | { kind: 'read'; path: string; message: string }
+  | { kind: 'parse'; path: string; line: number; column: number; field: string; message: string }
+  | { kind: 'invalid'; path: string; field: string; message: string };
+
+export interface ConfigBackup {
+  index: number;
+  modified: number;
+}
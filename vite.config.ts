@@ -25,7 +25,8 @@ export default defineConfig({
     rollupOptions: {
       input: {
         main: 'index.html',
-        overlay: 'overlay.html'
+        overlay: 'overlay.html',
+        prompt: 'prompt.html'
       }
     }
   },